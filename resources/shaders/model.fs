@@ -6,9 +6,20 @@ in vec3 newColor;
 in vec3 ObjPos;
 
 uniform sampler2D texture_diffuse1;
+// xy = UV scale, zw = UV offset (MTL -s / -o options)
+uniform vec4 texture_diffuse1UvTransform;
 uniform float mixValue;
+uniform int useDiffuseTexture;
+uniform vec3 materialDiffuseColor;
 uniform int useGeneratedMapping;
 uniform float generatedTexScale;
+uniform int useFaceShading;
+
+// Cycles every face through 11 brightness levels so that untextured models show their facets.
+float face_brightness(int face_index)
+{
+    return float(face_index % 11) / 11.0 * 0.6 + 0.4;
+}
 
 vec3 safe_normalize(vec3 value)
 {
@@ -56,14 +67,26 @@ vec4 sample_triplanar(vec3 object_pos, float tex_scale)
 
 void main()
 {
-    vec4 colorView = vec4(newColor, 1.0);
+    vec3 faceColor = newColor;
+    if (useFaceShading == 1) {
+        faceColor = min(newColor * face_brightness(gl_PrimitiveID), vec3(1.0));
+    }
+    vec4 colorView = vec4(faceColor, 1.0);
 
     vec4 texturedView;
-    if (useGeneratedMapping == 1) {
+    if (useDiffuseTexture == 0) {
+        texturedView = vec4(materialDiffuseColor, 1.0);
+    } else if (useGeneratedMapping == 1) {
         texturedView = sample_triplanar(ObjPos, generatedTexScale);
     } else {
-        texturedView = texture(texture_diffuse1, TexCoords);
+        vec2 uv = TexCoords * texture_diffuse1UvTransform.xy + texture_diffuse1UvTransform.zw;
+        texturedView = texture(texture_diffuse1, uv);
     }
 
     FragColor = mix(colorView, texturedView, mixValue);
+
+    // Alpha cutout: fully blended-in transparent texels are dropped instead of sorted and blended.
+    if (FragColor.a < 0.5) {
+        discard;
+    }
 }
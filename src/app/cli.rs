@@ -1,37 +1,66 @@
 use std::fs::File;
 use std::path::Path;
 
+use crate::loaders::{model_extensions, model_loader_for, texture_decoder_for, texture_extensions};
+
 #[derive(Debug, Clone)]
 pub struct AppConfig {
     pub model_path: String,
-    pub texture_path: String,
+    /// Overrides every material diffuse texture when present.
+    pub texture_path: Option<String>,
+    /// `--strict`: abort when a texture cannot be loaded instead of using a placeholder.
+    pub strict_textures: bool,
 }
 
 pub fn parse_from_env() -> Result<AppConfig, String> {
     let args = std::env::args().collect::<Vec<String>>();
-    if args.len() != 3 {
+    let program = args.first().map(|s| s.as_str()).unwrap_or("scop_42");
+
+    let mut strict_textures = false;
+    let mut positional = Vec::new();
+    for arg in args.iter().skip(1) {
+        match arg.as_str() {
+            "--strict" => strict_textures = true,
+            _ => positional.push(arg.clone()),
+        }
+    }
+
+    if positional.is_empty() || positional.len() > 2 {
         return Err(format!(
-            "Usage: {} <path_to_model> <path_to_texture>\nExample: cargo run -- resources/models/teapot.obj resources/textures/brickwall.bmp",
-            args.first().map(|s| s.as_str()).unwrap_or("scop_42")
+            "Usage: {} [--strict] <path_to_model> [path_to_texture]\nExample: cargo run -- resources/models/teapot.obj resources/textures/brickwall.bmp",
+            program
         ));
     }
 
     let config = AppConfig {
-        model_path: args[1].clone(),
-        texture_path: args[2].clone(),
+        model_path: positional[0].clone(),
+        texture_path: positional.get(1).cloned(),
+        strict_textures,
     };
 
-    validate_cli_inputs(&config.model_path, &config.texture_path)?;
+    validate_cli_inputs(&config.model_path, config.texture_path.as_deref())?;
     Ok(config)
 }
 
-fn validate_cli_inputs(model_path: &str, texture_path: &str) -> Result<(), String> {
-    validate_path(model_path, "obj", "model")?;
-    validate_path(texture_path, "bmp", "texture")?;
+fn validate_cli_inputs(model_path: &str, texture_path: Option<&str>) -> Result<(), String> {
+    validate_path(model_path, "model")?;
+    if model_loader_for(Path::new(model_path)).is_none() {
+        return Err(unsupported_format("model", &model_extensions(), model_path));
+    }
+    if let Some(texture_path) = texture_path {
+        validate_path(texture_path, "texture")?;
+        if texture_decoder_for(Path::new(texture_path)).is_none() {
+            return Err(unsupported_format(
+                "texture",
+                &texture_extensions(),
+                texture_path,
+            ));
+        }
+    }
     Ok(())
 }
 
-fn validate_path(path: &str, expected_extension: &str, label: &str) -> Result<(), String> {
+fn validate_path(path: &str, label: &str) -> Result<(), String> {
     let file_path = Path::new(path);
     if !file_path.exists() {
         return Err(format!("{} file does not exist: {}", label, path));
@@ -40,18 +69,17 @@ fn validate_path(path: &str, expected_extension: &str, label: &str) -> Result<()
         return Err(format!("{} path is not a file: {}", label, path));
     }
 
-    let extension = file_path
-        .extension()
-        .and_then(|ext| ext.to_str())
-        .ok_or_else(|| format!("{} file has no extension: {}", label, path))?;
-    if !extension.eq_ignore_ascii_case(expected_extension) {
-        return Err(format!(
-            "{} file must have .{} extension: {}",
-            label, expected_extension, path
-        ));
-    }
-
     File::open(file_path)
         .map(|_| ())
         .map_err(|error| format!("Failed to open {} file '{}': {}", label, path, error))
 }
+
+/// Error for a file whose magic number and extension match no registered format.
+fn unsupported_format(label: &str, extensions: &[&str], path: &str) -> String {
+    format!(
+        "{} file must be a known format with .{} extension: {}",
+        label,
+        extensions.join(" or ."),
+        path
+    )
+}
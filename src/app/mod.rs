@@ -13,7 +13,11 @@ pub fn run_from_env() -> Result<(), AppError> {
 }
 
 pub fn run(config: AppConfig) -> Result<(), AppError> {
-    let scene_model = scene::build_scene_model(&config.model_path, &config.texture_path)
+    let fallback_texture_path = config.texture_path.as_deref().unwrap_or_default();
+    let scene_model = scene::build_scene_model(&config.model_path, fallback_texture_path)
         .map_err(AppError::SceneBuild)?;
-    renderer::run(scene_model).map_err(AppError::Renderer)
+    let render_options = renderer::RenderOptions {
+        strict_textures: config.strict_textures,
+    };
+    renderer::run(scene_model, render_options).map_err(AppError::Renderer)
 }
@@ -0,0 +1,237 @@
+//! Generated BMP conformance corpus.
+//!
+//! Every case is built from a top-down reference grid of pixels, encoded independently of
+//! `encoder` for one combination of bit depth, header version, row order, width and palette
+//! size, then decoded and compared pixel by pixel.
+
+use std::io::Cursor;
+
+use super::image::{Pixel, from_reader};
+
+const BI_RGB: u32 = 0;
+const BI_BITFIELDS: u32 = 3;
+const WIDTHS: [u32; 7] = [1, 2, 3, 5, 7, 9, 13];
+const HEIGHT: u32 = 3;
+
+#[derive(Clone, Copy, Debug)]
+struct Case {
+    bpp: u16,
+    header_size: u32,
+    width: u32,
+    top_down: bool,
+    palette_size: usize,
+}
+
+/// Deterministic palette with `size` distinct colors.
+fn palette(size: usize) -> Vec<Pixel> {
+    (0..size)
+        .map(|i| {
+            Pixel::new(
+                (i * 37 + 11) as u8,
+                (i * 91 + 5) as u8,
+                (255 - i * 13 % 256) as u8,
+            )
+        })
+        .collect()
+}
+
+/// Reference image, top row first, as palette indexes or direct colors.
+fn reference(case: &Case) -> Vec<Vec<Pixel>> {
+    let colors = palette(case.palette_size.max(1));
+    (0..HEIGHT)
+        .map(|y| {
+            (0..case.width)
+                .map(|x| {
+                    let seed = (x * 7 + y * 3) as usize;
+                    match case.bpp {
+                        1 | 4 | 8 => colors[seed % colors.len()],
+                        // 5 bits per channel, expanded the way the decoder scales them
+                        16 => {
+                            let [r, g, b] = [seed % 32, (seed * 3) % 32, 31 - seed % 32]
+                                .map(|v| ((v * 255 + 15) / 31) as u8);
+                            Pixel::new(r, g, b)
+                        }
+                        32 if case.header_size >= 108 => Pixel::with_alpha(
+                            (seed * 19) as u8,
+                            (seed * 23) as u8,
+                            (seed * 29) as u8,
+                            (seed * 31 + 1) as u8,
+                        ),
+                        _ => Pixel::new((seed * 19) as u8, (seed * 23) as u8, (seed * 29) as u8),
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn encode_row(case: &Case, row: &[Pixel], colors: &[Pixel]) -> Vec<u8> {
+    let row_size = (case.width as usize * case.bpp as usize).div_ceil(32) * 4;
+    let mut bytes = Vec::with_capacity(row_size);
+    match case.bpp {
+        1 | 4 | 8 => {
+            let bpp = case.bpp as usize;
+            bytes.resize(row_size, 0);
+            for (x, pixel) in row.iter().enumerate() {
+                let index = colors.iter().position(|c| c == pixel).unwrap() as u8;
+                let shift = 8 - bpp * (x % (8 / bpp) + 1);
+                bytes[x * bpp / 8] |= index << shift;
+            }
+        }
+        16 => {
+            for pixel in row {
+                let [r, g, b] = [pixel.r, pixel.g, pixel.b].map(|v| (v as u16 * 31 + 127) / 255);
+                bytes.extend_from_slice(&((r << 10) | (g << 5) | b).to_le_bytes());
+            }
+        }
+        24 => {
+            for pixel in row {
+                bytes.extend_from_slice(&[pixel.b, pixel.g, pixel.r]);
+            }
+        }
+        _ => {
+            for pixel in row {
+                bytes.extend_from_slice(&[pixel.b, pixel.g, pixel.r, pixel.a]);
+            }
+        }
+    }
+    bytes.resize(row_size, 0);
+    bytes
+}
+
+fn encode(case: &Case, rows: &[Vec<Pixel>]) -> Vec<u8> {
+    let indexed = matches!(case.bpp, 1 | 4 | 8);
+    let colors = palette(case.palette_size.max(1));
+    // 16-bit images with a v4/v5 header use explicit 555 masks, 32-bit ones add alpha
+    let masks: &[u32] = match (case.bpp, case.header_size >= 108) {
+        (16, true) => &[0x7C00, 0x03E0, 0x001F, 0],
+        (32, true) => &[0x00FF_0000, 0x0000_FF00, 0x0000_00FF, 0xFF00_0000],
+        _ => &[],
+    };
+
+    let mut pixel_data = Vec::new();
+    let mut file_rows: Vec<&Vec<Pixel>> = rows.iter().collect();
+    if !case.top_down {
+        file_rows.reverse();
+    }
+    for row in file_rows {
+        pixel_data.extend(encode_row(case, row, &colors));
+    }
+
+    let palette_bytes = if indexed { 4 * case.palette_size } else { 0 };
+    let pixel_offset = 14 + case.header_size + palette_bytes as u32;
+    let height = if case.top_down {
+        -(HEIGHT as i32)
+    } else {
+        HEIGHT as i32
+    };
+
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(b"BM");
+    bytes.extend_from_slice(&(pixel_offset + pixel_data.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(&[0; 4]);
+    bytes.extend_from_slice(&pixel_offset.to_le_bytes());
+    bytes.extend_from_slice(&case.header_size.to_le_bytes());
+    bytes.extend_from_slice(&(case.width as i32).to_le_bytes());
+    bytes.extend_from_slice(&height.to_le_bytes());
+    bytes.extend_from_slice(&1u16.to_le_bytes());
+    bytes.extend_from_slice(&case.bpp.to_le_bytes());
+    let compression = if masks.is_empty() {
+        BI_RGB
+    } else {
+        BI_BITFIELDS
+    };
+    bytes.extend_from_slice(&compression.to_le_bytes());
+    bytes.extend_from_slice(&(pixel_data.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(&2835i32.to_le_bytes());
+    bytes.extend_from_slice(&2835i32.to_le_bytes());
+    let num_colors = if indexed { case.palette_size as u32 } else { 0 };
+    bytes.extend_from_slice(&num_colors.to_le_bytes());
+    bytes.extend_from_slice(&0u32.to_le_bytes());
+    for mask in masks {
+        bytes.extend_from_slice(&mask.to_le_bytes());
+    }
+    bytes.resize(14 + case.header_size as usize, 0);
+    if indexed {
+        for color in &colors {
+            bytes.extend_from_slice(&[color.b, color.g, color.r, 0]);
+        }
+    }
+    bytes.extend(pixel_data);
+    bytes
+}
+
+fn corpus() -> Vec<Case> {
+    let mut cases = Vec::new();
+    for bpp in [1u16, 4, 8, 16, 24, 32] {
+        let palette_sizes: Vec<usize> = match bpp {
+            1 => vec![1, 2],
+            4 => vec![3, 16],
+            8 => vec![5, 100, 256],
+            _ => vec![0],
+        };
+        for header_size in [40, 108, 124] {
+            for &width in &WIDTHS {
+                for top_down in [false, true] {
+                    for &palette_size in &palette_sizes {
+                        cases.push(Case {
+                            bpp,
+                            header_size,
+                            width,
+                            top_down,
+                            palette_size,
+                        });
+                    }
+                }
+            }
+        }
+    }
+    cases
+}
+
+#[test]
+fn decodes_generated_corpus_pixel_exact() {
+    let cases = corpus();
+    assert!(cases.len() > 400);
+
+    for case in cases {
+        let expected = reference(&case);
+        let bytes = encode(&case, &expected);
+        let image = from_reader(&mut Cursor::new(bytes))
+            .unwrap_or_else(|error| panic!("{:?} failed to decode: {}", case, error));
+
+        assert_eq!(
+            (image.width, image.height),
+            (case.width, HEIGHT),
+            "{:?}",
+            case
+        );
+        for (x, y) in image.coordinates() {
+            assert_eq!(
+                image.get_pixel(x, y),
+                expected[y as usize][x as usize],
+                "{:?} differs at ({}, {})",
+                case,
+                x,
+                y
+            );
+        }
+    }
+}
+
+#[test]
+fn decodes_corpus_palette_sizes() {
+    for case in corpus()
+        .into_iter()
+        .filter(|case| matches!(case.bpp, 1 | 4 | 8))
+    {
+        let bytes = encode(&case, &reference(&case));
+        let image = from_reader(&mut Cursor::new(bytes)).expect("indexed case should decode");
+        assert_eq!(
+            image.color_palette.map(|palette| palette.len()),
+            Some(case.palette_size),
+            "{:?}",
+            case
+        );
+    }
+}
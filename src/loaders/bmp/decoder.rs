@@ -81,14 +81,42 @@ pub fn decode_image(bmp_data: &mut Cursor<Vec<u8>>) -> BmpResult<Image> {
     let header = read_bmp_header(bmp_data)?;
     let dib_header = read_bmp_dib_header(bmp_data)?;
 
-    let color_palette = read_color_palette(bmp_data, &dib_header)?;
+    let color_palette = read_color_palette(bmp_data, &dib_header, header.pixel_offset)?;
 
     let width = dib_header.width.unsigned_abs();
     let height = dib_header.height.unsigned_abs();
-    let padding = width % 4;
+    // 24-bit rows are padded to a multiple of 4 bytes
+    let padding = (4 - (width * 3) % 4) % 4;
 
-    let data = match color_palette {
-        Some(ref palette) => read_indexes(
+    let compression = CompressionType::from_u32(dib_header.compress_type);
+    let data = match (dib_header.bits_per_pixel, &color_palette) {
+        (16 | 32, _) => {
+            let masks = read_bitfield_masks(bmp_data, &dib_header)?;
+            read_bitfields(
+                bmp_data.get_ref(),
+                &masks,
+                width as usize,
+                height as usize,
+                dib_header.bits_per_pixel,
+                header.pixel_offset as usize,
+            )?
+        }
+        (_, Some(palette))
+            if matches!(
+                compression,
+                CompressionType::Rle8bit | CompressionType::Rle4bit
+            ) =>
+        {
+            read_rle(
+                bmp_data.get_ref(),
+                palette,
+                width as usize,
+                height as usize,
+                compression,
+                header.pixel_offset as usize,
+            )?
+        }
+        (1 | 4 | 8, Some(palette)) => read_indexes(
             bmp_data.get_mut(),
             palette,
             width as usize,
@@ -96,10 +124,20 @@ pub fn decode_image(bmp_data: &mut Cursor<Vec<u8>>) -> BmpResult<Image> {
             dib_header.bits_per_pixel,
             header.pixel_offset as usize,
         )?,
-        None => read_pixels(bmp_data, width, height, header.pixel_offset, padding as i64)?,
+        _ => read_pixels(bmp_data, width, height, header.pixel_offset, padding as i64)?,
+    };
+    // `Image` stores rows bottom-up, so top-down (negative height) images are flipped
+    let data = if dib_header.height < 0 {
+        data.chunks(width.max(1) as usize)
+            .rev()
+            .flatten()
+            .copied()
+            .collect()
+    } else {
+        data
     };
 
-    let image = Image {
+    let mut image = Image {
         header,
         dib_header: BmpDibHeader::new(width as i32, height as i32),
         color_palette,
@@ -108,6 +146,7 @@ pub fn decode_image(bmp_data: &mut Cursor<Vec<u8>>) -> BmpResult<Image> {
         padding,
         data,
     };
+    image.treat_unset_alpha_as_opaque();
 
     Ok(image)
 }
@@ -152,33 +191,70 @@ fn read_u16(bmp_data: &mut Cursor<Vec<u8>>) -> Result<u16, BmpError> {
 }
 
 fn read_bmp_dib_header(bmp_data: &mut Cursor<Vec<u8>>) -> BmpResult<BmpDibHeader> {
-    let dib_header = BmpDibHeader {
-        header_size: read_u32(bmp_data)?,
-        width: read_u32(bmp_data)? as i32,
-        height: read_u32(bmp_data)? as i32,
-        num_planes: read_u16(bmp_data)?,
-        bits_per_pixel: read_u16(bmp_data)?,
-        compress_type: read_u32(bmp_data)?,
-        data_size: read_u32(bmp_data)?,
-        hres: read_u32(bmp_data)? as i32,
-        vres: read_u32(bmp_data)? as i32,
-        num_colors: read_u32(bmp_data)?,
-        num_imp_colors: read_u32(bmp_data)?,
+    let header_size = read_u32(bmp_data)?;
+    let dib_header = match header_size {
+        // BITMAPCOREHEADER (OS/2 1.x, Windows 2.x): 16-bit dimensions and nothing else
+        12 => BmpDibHeader {
+            header_size,
+            width: read_u16(bmp_data)? as i32,
+            height: read_u16(bmp_data)? as i32,
+            num_planes: read_u16(bmp_data)?,
+            bits_per_pixel: read_u16(bmp_data)?,
+            compress_type: 0,
+            data_size: 0,
+            hres: 0,
+            vres: 0,
+            num_colors: 0,
+            num_imp_colors: 0,
+        },
+        // The OS/2 2.x header may be truncated to as little as 16 bytes; its leading fields match
+        // BITMAPINFOHEADER and omitted fields are zero.
+        16..40 => {
+            let mut fields = [0; 36];
+            bmp_data.read_exact(&mut fields[..header_size as usize - 4])?;
+            read_info_header_fields(header_size, &mut Cursor::new(fields.to_vec()))?
+        }
+        _ => read_info_header_fields(header_size, bmp_data)?,
     };
 
     match BmpVersion::from_dib_header(&dib_header) {
-        // V3 is the only version that is "fully" supported (decompressed images are the exception)
-        // We will also attempt to decode v4 and v5, but we ignore all the additional data in the header.
-        // This should not impose a big problem because neither decompression, nor 16 and 32-bit images are supported,
-        // so the decoding will likely fail due to these constraints either way.
-        Some(BmpVersion::Three) | Some(BmpVersion::Four) | Some(BmpVersion::Five) => (),
+        // V3 (including the NT variant with bitfield masks after the header), V4 and V5 are supported.
+        // Of the additional v4 and v5 header data, only the channel masks are used; color space and
+        // gamma information is ignored.
+        Some(BmpVersion::Three)
+        | Some(BmpVersion::ThreeNT)
+        | Some(BmpVersion::Four)
+        | Some(BmpVersion::Five) => (),
+        // The OS/2 headers predate bitfields, so 16- and 32-bit images do not exist for them.
+        // OS/2 2.x reuses compression 3 and 4 for Huffman 1D and RLE24, which are not supported.
+        Some(version @ (BmpVersion::Two | BmpVersion::Os2Two)) => {
+            if matches!(dib_header.bits_per_pixel, 16 | 32) {
+                return Err(BmpError::new(
+                    UnsupportedBitsPerPixel,
+                    format!(
+                        "{} images cannot have {} bits per pixel",
+                        version.as_ref(),
+                        dib_header.bits_per_pixel
+                    ),
+                ));
+            }
+            if let Some(name) = match dib_header.compress_type {
+                3 => Some("Huffman 1D"),
+                4 => Some("RLE 24-bit"),
+                _ => None,
+            } {
+                return Err(BmpError::new(
+                    UnsupportedCompressionType,
+                    format!("{} ({})", name, version.as_ref()),
+                ));
+            }
+        }
         // Otherwise, report the errors
-        Some(other) => return Err(BmpError::new(UnsupportedBmpVersion, other)),
         None => {
             return Err(BmpError::new(
                 UnsupportedHeader,
                 format!(
-                    "Only simple BMP images of version 3, 4, and 5 are currently supported. \
+                    "Only simple BMP images of version 2, 3, 4, and 5 and OS/2 BMP images are currently supported. \
                 Connot decode the image for the following header: {:?}",
                     dib_header
                 ),
@@ -188,29 +264,76 @@ fn read_bmp_dib_header(bmp_data: &mut Cursor<Vec<u8>>) -> BmpResult<BmpDibHeader
 
     match dib_header.bits_per_pixel {
         // Currently supported
-        1 | 4 | 8 | 24 => (),
+        1 | 4 | 8 | 16 | 24 | 32 => (),
         other => {
             return Err(BmpError::new(
                 UnsupportedBitsPerPixel,
                 format!(
-                    "Only 1, 4, 8, and 24 bits per pixel are currently supported, was: {}",
+                    "Only 1, 4, 8, 16, 24, and 32 bits per pixel are currently supported, was: {}",
                     other
                 ),
             ));
         }
     }
 
-    match CompressionType::from_u32(dib_header.compress_type) {
-        CompressionType::Uncompressed => (),
-        other => return Err(BmpError::new(UnsupportedCompressionType, other)),
+    match (
+        CompressionType::from_u32(dib_header.compress_type),
+        dib_header.bits_per_pixel,
+    ) {
+        (CompressionType::Uncompressed, _) | (CompressionType::BitfieldsEncoding, 16 | 32) => (),
+        (CompressionType::Rle8bit, 8) | (CompressionType::Rle4bit, 4) => {
+            if dib_header.height < 0 {
+                return Err(BmpError::new(
+                    InvalidData,
+                    "RLE compressed BMP images cannot be stored top-down",
+                ));
+            }
+        }
+        (compression @ (CompressionType::Rle8bit | CompressionType::Rle4bit), bpp) => {
+            return Err(BmpError::new(
+                UnsupportedCompressionType,
+                format!(
+                    "{} compression requires {} bits per pixel, was: {}",
+                    compression.as_ref(),
+                    if compression == CompressionType::Rle8bit {
+                        8
+                    } else {
+                        4
+                    },
+                    bpp
+                ),
+            ));
+        }
+        (other, _) => return Err(BmpError::new(UnsupportedCompressionType, other)),
     }
 
     Ok(dib_header)
 }
 
+/// Reads the BITMAPINFOHEADER fields that follow `header_size`.
+fn read_info_header_fields(
+    header_size: u32,
+    bmp_data: &mut Cursor<Vec<u8>>,
+) -> BmpResult<BmpDibHeader> {
+    Ok(BmpDibHeader {
+        header_size,
+        width: read_u32(bmp_data)? as i32,
+        height: read_u32(bmp_data)? as i32,
+        num_planes: read_u16(bmp_data)?,
+        bits_per_pixel: read_u16(bmp_data)?,
+        compress_type: read_u32(bmp_data)?,
+        data_size: read_u32(bmp_data)?,
+        hres: read_u32(bmp_data)? as i32,
+        vres: read_u32(bmp_data)? as i32,
+        num_colors: read_u32(bmp_data)?,
+        num_imp_colors: read_u32(bmp_data)?,
+    })
+}
+
 fn read_color_palette(
     bmp_data: &mut Cursor<Vec<u8>>,
     dh: &BmpDibHeader,
+    pixel_offset: u32,
 ) -> BmpResult<Option<Vec<Pixel>>> {
     let num_entries = match dh.bits_per_pixel {
         // We have a color_palette if the num_colors in the dib header is not zero
@@ -220,11 +343,20 @@ fn read_color_palette(
         _ => return Ok(None),
     };
 
-    let num_bytes = match BmpVersion::from_dib_header(dh) {
-        // Three bytes for v2. Though, this is currently not supported
-        Some(BmpVersion::Two) => return Err(BmpError::new(UnsupportedBmpVersion, BmpVersion::Two)),
-        // Each entry in the color_palette is four bytes for v3, v4, and v5
-        _ => 4,
+    let (num_bytes, num_entries) = match BmpVersion::from_dib_header(dh) {
+        // Three bytes for v2. The core header has no color count, so a palette that is smaller
+        // than `1 << bpp` is only recognizable by the pixel data starting right after it.
+        Some(BmpVersion::Two) => {
+            let palette_start = BMP_HEADER_SIZE as u32 + dh.header_size;
+            let available = pixel_offset.saturating_sub(palette_start) as usize / 3;
+            if available == 0 {
+                (3, num_entries)
+            } else {
+                (3, num_entries.min(available))
+            }
+        }
+        // Each entry in the color_palette is four bytes for OS/2 2.x, v3, v4, and v5
+        _ => (4, num_entries),
     };
 
     bmp_data.seek(SeekFrom::Start(BMP_HEADER_SIZE + dh.header_size as u64))?;
@@ -303,6 +435,257 @@ fn read_indexes(
         }
     }
     Ok(data)
+}
+
+/// Decodes RLE8 / RLE4 pixel data into palette colors.
+///
+/// Rows are produced in file (bottom-up) order, like the uncompressed paths. Pixels skipped by
+/// delta or end-of-line escapes keep palette entry 0, and runs past the row end are clipped.
+fn read_rle(
+    bmp_data: &[u8],
+    palette: &[Pixel],
+    width: usize,
+    height: usize,
+    compression: CompressionType,
+    offset: usize,
+) -> BmpResult<Vec<Pixel>> {
+    let is_rle4 = compression == CompressionType::Rle4bit;
+    let mut indexes = vec![0u8; width * height];
+    let mut stream = bmp_data.get(offset..).unwrap_or_default().iter().copied();
+    let mut next_byte = |what: &str| {
+        stream.next().ok_or_else(|| {
+            BmpError::new(
+                InvalidData,
+                format!("RLE BMP data ended unexpectedly while reading {}", what),
+            )
+        })
+    };
+
+    let (mut x, mut y) = (0usize, 0usize);
+    let mut put = |x: &mut usize, y: usize, index: u8| {
+        if *x < width && y < height {
+            indexes[y * width + *x] = index;
+        }
+        *x += 1;
+    };
+
+    while y < height {
+        let count = next_byte("a run length")?;
+        let value = next_byte("a run value")?;
+
+        if count > 0 {
+            for i in 0..count {
+                let index = if !is_rle4 {
+                    value
+                } else if i % 2 == 0 {
+                    value >> 4
+                } else {
+                    value & 0x0F
+                };
+                put(&mut x, y, index);
+            }
+            continue;
+        }
+
+        match value {
+            // End of line
+            0 => {
+                x = 0;
+                y += 1;
+            }
+            // End of bitmap
+            1 => break,
+            // Delta: move right and up (in file order) without writing pixels
+            2 => {
+                x += next_byte("a delta x offset")? as usize;
+                y += next_byte("a delta y offset")? as usize;
+            }
+            // Absolute mode: `value` literal pixels, padded to a 16-bit boundary
+            literal_count => {
+                let byte_count = if is_rle4 {
+                    (literal_count as usize).div_ceil(2)
+                } else {
+                    literal_count as usize
+                };
+                for i in 0..byte_count {
+                    let byte = next_byte("absolute mode pixels")?;
+                    if is_rle4 {
+                        put(&mut x, y, byte >> 4);
+                        if i * 2 + 1 < literal_count as usize {
+                            put(&mut x, y, byte & 0x0F);
+                        }
+                    } else {
+                        put(&mut x, y, byte);
+                    }
+                }
+                if byte_count % 2 == 1 {
+                    next_byte("absolute mode padding")?;
+                }
+            }
+        }
+    }
+
+    indexes
+        .into_iter()
+        .map(|index| {
+            palette.get(index as usize).copied().ok_or_else(|| {
+                BmpError::new(
+                    InvalidData,
+                    format!(
+                        "RLE BMP palette index {} out of bounds (palette size={})",
+                        index,
+                        palette.len()
+                    ),
+                )
+            })
+        })
+        .collect()
+}
+
+/// Channel masks of a 16- or 32-bit image.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct BitfieldMasks {
+    red: u32,
+    green: u32,
+    blue: u32,
+    alpha: u32,
+}
+
+/// Reads the channel masks for a 16- or 32-bit image.
+///
+/// With BI_BITFIELDS, the RGB masks directly follow the 40-byte part of the header: appended to it
+/// for v3, inside it for v4 and v5, which also carry an alpha mask. Uncompressed images use the
+/// default layouts (X1R5G5B5 and X8R8G8B8), except that v4 and v5 headers may still supply an
+/// alpha mask for 32-bit images.
+fn read_bitfield_masks(
+    bmp_data: &mut Cursor<Vec<u8>>,
+    dh: &BmpDibHeader,
+) -> BmpResult<BitfieldMasks> {
+    let has_v4_masks = dh.header_size >= 108;
+    bmp_data.seek(SeekFrom::Start(BMP_HEADER_SIZE + 40))?;
+
+    let masks = match CompressionType::from_u32(dh.compress_type) {
+        CompressionType::BitfieldsEncoding => BitfieldMasks {
+            red: read_u32(bmp_data)?,
+            green: read_u32(bmp_data)?,
+            blue: read_u32(bmp_data)?,
+            alpha: if has_v4_masks { read_u32(bmp_data)? } else { 0 },
+        },
+        _ if dh.bits_per_pixel == 16 => BitfieldMasks {
+            red: 0x7C00,
+            green: 0x03E0,
+            blue: 0x001F,
+            alpha: 0,
+        },
+        _ => BitfieldMasks {
+            red: 0x00FF_0000,
+            green: 0x0000_FF00,
+            blue: 0x0000_00FF,
+            alpha: if has_v4_masks {
+                bmp_data.seek(SeekFrom::Current(12))?;
+                read_u32(bmp_data)?
+            } else {
+                0
+            },
+        },
+    };
+
+    let bpp_mask = if dh.bits_per_pixel == 16 {
+        0xFFFF
+    } else {
+        u32::MAX
+    };
+    for (name, mask) in [
+        ("red", masks.red),
+        ("green", masks.green),
+        ("blue", masks.blue),
+        ("alpha", masks.alpha),
+    ] {
+        let shifted = mask.checked_shr(mask.trailing_zeros()).unwrap_or(0);
+        if mask & !bpp_mask != 0 || shifted & shifted.wrapping_add(1) != 0 {
+            return Err(BmpError::new(
+                InvalidData,
+                format!(
+                    "Invalid {} channel mask {:#010x} for a {}-bit image",
+                    name, mask, dh.bits_per_pixel
+                ),
+            ));
+        }
+    }
+    if masks.red == 0 && masks.green == 0 && masks.blue == 0 {
+        return Err(BmpError::new(
+            InvalidData,
+            "Bitfield-encoded BMP image has no color channel masks",
+        ));
+    }
+
+    Ok(masks)
+}
+
+/// Decodes 16- and 32-bit pixels by extracting each channel with its mask and scaling it to
+/// 8 bits. An all-zero alpha channel is resolved later by `Image::treat_unset_alpha_as_opaque`.
+fn read_bitfields(
+    bmp_data: &[u8],
+    masks: &BitfieldMasks,
+    width: usize,
+    height: usize,
+    bpp: u16,
+    offset: usize,
+) -> BmpResult<Vec<Pixel>> {
+    let bytes_per_pixel = bpp as usize / 8;
+    let row_stride = (width * bytes_per_pixel).div_ceil(4) * 4;
+    let end = row_stride
+        .checked_mul(height)
+        .and_then(|size| size.checked_add(offset))
+        .ok_or_else(|| BmpError::new(InvalidData, "Bitfield BMP pixel data size overflow"))?;
+    if end > bmp_data.len() {
+        return Err(BmpError::new(
+            InvalidData,
+            format!(
+                "Bitfield BMP pixel data exceeds input bounds (end={}, len={})",
+                end,
+                bmp_data.len()
+            ),
+        ));
+    }
+
+    let mut data = Vec::with_capacity(width * height);
+    for row in bmp_data[offset..end].chunks_exact(row_stride) {
+        for bytes in row[..width * bytes_per_pixel].chunks_exact(bytes_per_pixel) {
+            let value = match *bytes {
+                [lo, hi] => u16::from_le_bytes([lo, hi]) as u32,
+                [b0, b1, b2, b3] => u32::from_le_bytes([b0, b1, b2, b3]),
+                _ => unreachable!("bitfield pixels are 2 or 4 bytes wide"),
+            };
+            data.push(px!(
+                scale_channel(value, masks.red),
+                scale_channel(value, masks.green),
+                scale_channel(value, masks.blue),
+                if masks.alpha == 0 {
+                    255
+                } else {
+                    scale_channel(value, masks.alpha)
+                }
+            ));
+        }
+    }
+
+    Ok(data)
+}
+
+/// Extracts the bits selected by `mask` and scales them to the full 0..=255 range.
+fn scale_channel(value: u32, mask: u32) -> u8 {
+    if mask == 0 {
+        return 0;
+    }
+    let bits = mask.count_ones();
+    let channel = (value & mask) >> mask.trailing_zeros();
+    if bits >= 8 {
+        (channel >> (bits - 8)) as u8
+    } else {
+        let max = (1u32 << bits) - 1;
+        ((channel * 255 + max / 2) / max) as u8
+    }
 }
 
 fn read_pixels(
@@ -407,3 +790,374 @@ fn test_calculate_bit_index() {
     assert_eq!(bi.next(), Some(0b1111_0001));
     assert_eq!(bi.next(), None);
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::{BmpErrorKind, decode_image};
+    use crate::loaders::bmp::image::{Image, Pixel};
+
+    const PALETTE: [[u8; 3]; 4] = [[0, 0, 0], [255, 0, 0], [0, 255, 0], [0, 0, 255]];
+
+    /// Builds a BMP file. `masks` are written right after the 40-byte part of the DIB header,
+    /// which is then zero-padded to `header_size` (masks of v3 images extend past the header).
+    fn bmp_file(
+        header_size: u32,
+        (width, height): (i32, i32),
+        bpp: u16,
+        compression: u32,
+        masks: &[u32],
+        palette: &[[u8; 3]],
+        pixels: &[u8],
+    ) -> Vec<u8> {
+        let masks_end = 40 + 4 * masks.len() as u32;
+        let pixel_offset = 14 + header_size.max(masks_end) + 4 * palette.len() as u32;
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(b"BM");
+        bytes.extend_from_slice(&(pixel_offset + pixels.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&[0; 4]);
+        bytes.extend_from_slice(&pixel_offset.to_le_bytes());
+        bytes.extend_from_slice(&header_size.to_le_bytes());
+        bytes.extend_from_slice(&width.to_le_bytes());
+        bytes.extend_from_slice(&height.to_le_bytes());
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&bpp.to_le_bytes());
+        bytes.extend_from_slice(&compression.to_le_bytes());
+        bytes.extend_from_slice(&(pixels.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&[0; 8]);
+        bytes.extend_from_slice(&(palette.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&[0; 4]);
+        for mask in masks {
+            bytes.extend_from_slice(&mask.to_le_bytes());
+        }
+        bytes.resize(14 + header_size.max(masks_end) as usize, 0);
+        for [r, g, b] in palette {
+            bytes.extend_from_slice(&[*b, *g, *r, 0]);
+        }
+        bytes.extend_from_slice(pixels);
+        bytes
+    }
+
+    /// Builds a V3 BMP with a four-entry palette around hand-written RLE pixel data.
+    fn rle_bmp(width: i32, height: i32, bpp: u16, compression: u32, pixels: &[u8]) -> Vec<u8> {
+        bmp_file(40, (width, height), bpp, compression, &[], &PALETTE, pixels)
+    }
+
+    fn decode(bytes: Vec<u8>) -> Image {
+        decode_image(&mut Cursor::new(bytes)).expect("image should decode")
+    }
+
+    /// Palette indexes of the decoded image, top row first.
+    fn decoded_indexes(bytes: Vec<u8>) -> Vec<Vec<usize>> {
+        let image = decode(bytes);
+        (0..image.get_height())
+            .map(|y| {
+                (0..image.get_width())
+                    .map(|x| {
+                        let pixel = image.get_pixel(x, y);
+                        PALETTE
+                            .iter()
+                            .position(|&[r, g, b]| pixel == Pixel::new(r, g, b))
+                            .expect("decoded pixel should come from the palette")
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn decodes_rle8_runs_absolute_mode_and_escapes() {
+        let pixels = [
+            // Bottom row: run of three 1s, then an absolute run of 3 pixels padded to a word
+            0x03, 0x01, 0x00, 0x03, 0x02, 0x03, 0x01, 0x00, 0x00, 0x00,
+            // Middle row: delta right by 2 and up by 1, skipping the rest of this row
+            0x00, 0x02, 0x02, 0x01, // Top row: continue at x = 2
+            0x04, 0x03, 0x00, 0x01,
+        ];
+        let rows = decoded_indexes(rle_bmp(6, 3, 8, 1, &pixels));
+
+        assert_eq!(
+            rows,
+            vec![
+                vec![0, 0, 3, 3, 3, 3],
+                vec![0, 0, 0, 0, 0, 0],
+                vec![1, 1, 1, 2, 3, 1],
+            ]
+        );
+    }
+
+    #[test]
+    fn decodes_rle4_alternating_runs_and_odd_absolute_counts() {
+        let pixels = [
+            // Bottom row: five pixels alternating 1 and 2
+            0x05, 0x12, 0x00, 0x00,
+            // Top row: absolute mode with three nibbles (two bytes, already word aligned)
+            0x00, 0x03, 0x32, 0x10, 0x02, 0x33, 0x00, 0x01,
+        ];
+        let rows = decoded_indexes(rle_bmp(5, 2, 4, 2, &pixels));
+
+        assert_eq!(rows, vec![vec![3, 2, 1, 3, 3], vec![1, 2, 1, 2, 1]]);
+    }
+
+    #[test]
+    fn clips_runs_past_row_end_and_stops_at_end_of_bitmap() {
+        let pixels = [0x06, 0x02, 0x00, 0x01, 0x04, 0x03];
+        let rows = decoded_indexes(rle_bmp(4, 2, 8, 1, &pixels));
+
+        assert_eq!(rows, vec![vec![0, 0, 0, 0], vec![2, 2, 2, 2]]);
+    }
+
+    #[test]
+    fn rejects_truncated_and_mismatched_rle_data() {
+        let truncated = decode_image(&mut Cursor::new(rle_bmp(4, 1, 8, 1, &[0x00, 0x04, 0x01])))
+            .expect_err("truncated absolute run should fail");
+        assert!(matches!(truncated.kind, BmpErrorKind::InvalidData));
+
+        let wrong_depth = decode_image(&mut Cursor::new(rle_bmp(4, 1, 4, 1, &[0x00, 0x01])))
+            .expect_err("RLE8 with 4 bits per pixel should fail");
+        assert!(matches!(
+            wrong_depth.kind,
+            BmpErrorKind::UnsupportedCompressionType
+        ));
+
+        let top_down = decode_image(&mut Cursor::new(rle_bmp(4, -1, 8, 1, &[0x00, 0x01])))
+            .expect_err("top-down RLE should fail");
+        assert!(matches!(top_down.kind, BmpErrorKind::InvalidData));
+    }
+
+    #[test]
+    fn decodes_16_bit_565_bitfields_with_row_padding() {
+        // Three pixels per row: 6 bytes padded to 8
+        let pixels = [0x00, 0xF8, 0xE0, 0x07, 0x1F, 0x00, 0x00, 0x00];
+        let image = decode(bmp_file(
+            40,
+            (3, 1),
+            16,
+            3,
+            &[0xF800, 0x07E0, 0x001F],
+            &[],
+            &pixels,
+        ));
+
+        assert_eq!(image.get_pixel(0, 0), Pixel::new(255, 0, 0));
+        assert_eq!(image.get_pixel(1, 0), Pixel::new(0, 255, 0));
+        assert_eq!(image.get_pixel(2, 0), Pixel::new(0, 0, 255));
+        assert!(!image.has_alpha());
+    }
+
+    #[test]
+    fn uncompressed_16_bit_uses_555_layout() {
+        let pixels = [0xFF, 0x7F, 0x10, 0x42];
+        let image = decode(bmp_file(40, (2, 1), 16, 0, &[], &[], &pixels));
+
+        assert_eq!(image.get_pixel(0, 0), Pixel::new(255, 255, 255));
+        assert_eq!(image.get_pixel(1, 0), Pixel::new(132, 132, 132));
+    }
+
+    #[test]
+    fn decodes_32_bit_v4_and_v5_with_alpha_mask() {
+        let pixels = [0x30, 0x20, 0x10, 0x80, 0xFF, 0xFF, 0xFF, 0xFF];
+        let masks = [0x00FF_0000, 0x0000_FF00, 0x0000_00FF, 0xFF00_0000];
+        for header_size in [108, 124] {
+            let image = decode(bmp_file(header_size, (2, 1), 32, 3, &masks, &[], &pixels));
+
+            assert_eq!(
+                image.get_pixel(0, 0),
+                Pixel::with_alpha(0x10, 0x20, 0x30, 0x80)
+            );
+            assert_eq!(image.get_pixel(1, 0), Pixel::new(255, 255, 255));
+            assert!(image.has_alpha());
+        }
+    }
+
+    #[test]
+    fn custom_32_bit_masks_are_honored() {
+        // RGBA byte order: red in the lowest byte, alpha in the highest
+        let pixels = [0x10, 0x20, 0x30, 0x40];
+        let masks = [0x0000_00FF, 0x0000_FF00, 0x00FF_0000, 0xFF00_0000];
+        let image = decode(bmp_file(124, (1, 1), 32, 3, &masks, &[], &pixels));
+
+        assert_eq!(
+            image.get_pixel(0, 0),
+            Pixel::with_alpha(0x10, 0x20, 0x30, 0x40)
+        );
+    }
+
+    #[test]
+    fn images_without_usable_alpha_are_opaque() {
+        let pixels = [0x30, 0x20, 0x10, 0x00];
+        let v3 = decode(bmp_file(40, (1, 1), 32, 0, &[], &[], &pixels));
+        assert_eq!(v3.get_pixel(0, 0), Pixel::new(0x10, 0x20, 0x30));
+
+        // Alpha mask present, but every alpha value is zero
+        let masks = [0x00FF_0000, 0x0000_FF00, 0x0000_00FF, 0xFF00_0000];
+        let zero_alpha = decode(bmp_file(108, (1, 1), 32, 3, &masks, &[], &pixels));
+        assert!(!zero_alpha.has_alpha());
+    }
+
+    #[test]
+    fn rejects_invalid_bitfield_masks_and_depths() {
+        let pixels = [0; 4];
+        let split_mask = decode_image(&mut Cursor::new(bmp_file(
+            40,
+            (1, 1),
+            32,
+            3,
+            &[0x00F0_0F00, 0x0000_00F0, 0x0000_000F],
+            &[],
+            &pixels,
+        )))
+        .expect_err("non-contiguous mask should fail");
+        assert!(matches!(split_mask.kind, BmpErrorKind::InvalidData));
+
+        let wide_mask = decode_image(&mut Cursor::new(bmp_file(
+            40,
+            (2, 1),
+            16,
+            3,
+            &[0x000F_0000, 0x00F0, 0x000F],
+            &[],
+            &pixels,
+        )))
+        .expect_err("mask wider than 16 bits should fail");
+        assert!(matches!(wide_mask.kind, BmpErrorKind::InvalidData));
+
+        let bitfields_24 = decode_image(&mut Cursor::new(bmp_file(
+            40,
+            (1, 1),
+            24,
+            3,
+            &[0xFF0000, 0xFF00, 0xFF],
+            &[],
+            &pixels,
+        )))
+        .expect_err("24-bit bitfields should fail");
+        assert!(matches!(
+            bitfields_24.kind,
+            BmpErrorKind::UnsupportedCompressionType
+        ));
+    }
+
+    /// Builds an OS/2 1.x BMP with a BITMAPCOREHEADER and three-byte palette entries.
+    fn core_bmp(width: u16, height: u16, bpp: u16, palette: &[[u8; 3]], pixels: &[u8]) -> Vec<u8> {
+        let pixel_offset = 14 + 12 + 3 * palette.len() as u32;
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(b"BM");
+        bytes.extend_from_slice(&(pixel_offset + pixels.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&[0; 4]);
+        bytes.extend_from_slice(&pixel_offset.to_le_bytes());
+        bytes.extend_from_slice(&12u32.to_le_bytes());
+        bytes.extend_from_slice(&width.to_le_bytes());
+        bytes.extend_from_slice(&height.to_le_bytes());
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&bpp.to_le_bytes());
+        for [r, g, b] in palette {
+            bytes.extend_from_slice(&[*b, *g, *r]);
+        }
+        bytes.extend_from_slice(pixels);
+        bytes
+    }
+
+    #[test]
+    fn decodes_core_header_with_three_byte_palette() {
+        // Three palette entries only; the pixel offset marks the end of the palette
+        let pixels = [0x00, 0x01, 0x02, 0x00, 0x02, 0x02, 0x01, 0x00];
+        let image = decode(core_bmp(3, 2, 8, &PALETTE[..3], &pixels));
+
+        assert_eq!(image.color_palette.as_ref().map(Vec::len), Some(3));
+        assert_eq!(image.get_pixel(0, 0), Pixel::new(0, 255, 0));
+        assert_eq!(image.get_pixel(2, 0), Pixel::new(255, 0, 0));
+        assert_eq!(image.get_pixel(1, 1), Pixel::new(255, 0, 0));
+        assert_eq!(image.get_pixel(2, 1), Pixel::new(0, 255, 0));
+    }
+
+    #[test]
+    fn decodes_core_header_truecolor() {
+        let pixels = [
+            0x00, 0x00, 0xFF, 0x00, 0xFF, 0x00, 0x00, 0x00, // bottom row + padding
+            0xFF, 0x00, 0x00, 0xFF, 0xFF, 0xFF, 0x00, 0x00,
+        ];
+        let image = decode(core_bmp(2, 2, 24, &[], &pixels));
+
+        assert!(image.color_palette.is_none());
+        assert_eq!(image.get_pixel(0, 1), Pixel::new(255, 0, 0));
+        assert_eq!(image.get_pixel(1, 1), Pixel::new(0, 255, 0));
+        assert_eq!(image.get_pixel(0, 0), Pixel::new(0, 0, 255));
+        assert_eq!(image.get_pixel(1, 0), Pixel::new(255, 255, 255));
+    }
+
+    #[test]
+    fn decodes_os2_v2_full_and_truncated_headers() {
+        // 4 bpp, 3 pixels: indexes 1, 3, 2
+        let pixels = [0x13, 0x20, 0x00, 0x00];
+        // Headers cut before the color count have a full palette
+        let mut full_palette = PALETTE.to_vec();
+        full_palette.resize(16, [0; 3]);
+        for header_size in [24, 52, 64] {
+            let palette = if header_size < 36 {
+                &full_palette[..]
+            } else {
+                &PALETTE
+            };
+            let mut bytes = bmp_file(header_size.max(40), (3, 1), 4, 0, &[], palette, &pixels);
+            if header_size < 40 {
+                // Cut the header down, moving the palette and pixels up with it
+                let cut = 40 - header_size;
+                bytes.drain(14 + header_size as usize..54);
+                for offset in [2, 10] {
+                    let value =
+                        u32::from_le_bytes(bytes[offset..offset + 4].try_into().expect("4 bytes"));
+                    bytes[offset..offset + 4].copy_from_slice(&(value - cut).to_le_bytes());
+                }
+                bytes[14..18].copy_from_slice(&header_size.to_le_bytes());
+            }
+            let image = decode(bytes);
+            assert_eq!(image.get_pixel(0, 0), Pixel::new(255, 0, 0));
+            assert_eq!(image.get_pixel(1, 0), Pixel::new(0, 0, 255));
+            assert_eq!(image.get_pixel(2, 0), Pixel::new(0, 255, 0));
+        }
+
+        // A 16-byte header ends after the bit depth; the palette has the full 2 entries for 1 bpp
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(b"BM");
+        bytes.extend_from_slice(&(14u32 + 16 + 8 + 4).to_le_bytes());
+        bytes.extend_from_slice(&[0; 4]);
+        bytes.extend_from_slice(&(14u32 + 16 + 8).to_le_bytes());
+        bytes.extend_from_slice(&16u32.to_le_bytes());
+        bytes.extend_from_slice(&2i32.to_le_bytes());
+        bytes.extend_from_slice(&1i32.to_le_bytes());
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&[0, 0, 0, 0, 255, 255, 255, 0]);
+        bytes.extend_from_slice(&[0b0100_0000, 0, 0, 0]);
+        let image = decode(bytes);
+        assert_eq!(image.get_pixel(0, 0), Pixel::new(0, 0, 0));
+        assert_eq!(image.get_pixel(1, 0), Pixel::new(255, 255, 255));
+    }
+
+    #[test]
+    fn rejects_os2_huffman_and_bitfield_depths() {
+        let huffman = decode_image(&mut Cursor::new(bmp_file(
+            64,
+            (8, 1),
+            1,
+            3,
+            &[],
+            &PALETTE[..2],
+            &[0; 4],
+        )))
+        .expect_err("OS/2 Huffman 1D should fail");
+        assert!(matches!(
+            huffman.kind,
+            BmpErrorKind::UnsupportedCompressionType
+        ));
+
+        let core_32 = decode_image(&mut Cursor::new(core_bmp(1, 1, 32, &[], &[0; 4])))
+            .expect_err("32-bit core header should fail");
+        assert!(matches!(
+            core_32.kind,
+            BmpErrorKind::UnsupportedBitsPerPixel
+        ));
+    }
+}
@@ -0,0 +1,357 @@
+use std::io::{self, Write};
+
+use super::image::{BmpDibHeader, BmpHeader, Image, Pixel};
+use super::quantize::{map_to_palette, median_cut_palette};
+
+// The BmpHeader always has a size of 14 bytes
+const BMP_HEADER_SIZE: u32 = 14;
+// BITMAPINFOHEADER, used for 24-bit images
+const V3_HEADER_SIZE: u32 = 40;
+// BITMAPV4HEADER, needed for the alpha mask of 32-bit images
+const V4_HEADER_SIZE: u32 = 108;
+const BI_RGB: u32 = 0;
+const BI_BITFIELDS: u32 = 3;
+// 'sRGB' as a little-endian tag
+const LCS_SRGB: u32 = 0x7352_4742;
+
+/// Settings for palettized (1, 4 or 8 bits per pixel) BMP export.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct IndexedBmpOptions {
+    /// 1, 4 or 8; the palette holds at most `2^bits_per_pixel` colors.
+    pub bits_per_pixel: u16,
+    /// Apply Floyd–Steinberg dithering when the image has more colors than the palette.
+    pub dither: bool,
+}
+
+impl Default for IndexedBmpOptions {
+    fn default() -> Self {
+        IndexedBmpOptions {
+            bits_per_pixel: 8,
+            dither: false,
+        }
+    }
+}
+
+/// Encodes the `Image` as a BMP file.
+///
+/// Opaque images are written as 24-bit BGR with a BITMAPINFOHEADER. Images containing any
+/// translucent pixel are written as 32-bit BGRA with a BITMAPV4HEADER and BITFIELDS masks, so
+/// the alpha channel survives. Rows are stored bottom-up and padded to 4 bytes.
+pub fn encode_image(bmp_image: &Image) -> io::Result<Vec<u8>> {
+    let bpp = if bmp_image.has_alpha() { 32 } else { 24 };
+    encode_with_depth(bmp_image, bpp)
+}
+
+/// Encodes the `Image` as a palettized BMP with a BITMAPINFOHEADER.
+///
+/// The palette is built by median cut (see `quantize`), so images with few enough colors are
+/// stored losslessly. Indexes are packed most significant bits first; alpha is dropped.
+pub fn encode_indexed_image(bmp_image: &Image, options: IndexedBmpOptions) -> io::Result<Vec<u8>> {
+    let bpp = options.bits_per_pixel;
+    if !matches!(bpp, 1 | 4 | 8) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Indexed BMPs support 1, 4, or 8 bits per pixel, was: {}",
+                bpp
+            ),
+        ));
+    }
+    validate_image(bmp_image)?;
+
+    let width = bmp_image.width as usize;
+    let height = bmp_image.height as usize;
+    let mut palette = median_cut_palette(&bmp_image.data, 1 << bpp);
+    if palette.is_empty() {
+        palette.push(Pixel::new(0, 0, 0));
+    }
+    let indexes = map_to_palette(&bmp_image.data, width, &palette, options.dither);
+
+    let row_size = (width * bpp as usize).div_ceil(32) * 4;
+    let data_size = u32::try_from(row_size * height).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Image pixel data is too large for BMP",
+        )
+    })?;
+    let palette_size = 4 * palette.len() as u32;
+
+    let mut header = BmpHeader::new(BMP_HEADER_SIZE + V3_HEADER_SIZE + palette_size, data_size);
+    header.file_size = header.pixel_offset + data_size;
+    let dib_header = BmpDibHeader {
+        bits_per_pixel: bpp,
+        data_size,
+        hres: bmp_image.dib_header.hres,
+        vres: bmp_image.dib_header.vres,
+        num_colors: palette.len() as u32,
+        ..BmpDibHeader::new(width as i32, height as i32)
+    };
+
+    let mut bmp_data = Vec::with_capacity(header.file_size as usize);
+    write_header(&mut bmp_data, &header)?;
+    write_dib_header(&mut bmp_data, &dib_header)?;
+    for color in &palette {
+        bmp_data.write_all(&[color.b, color.g, color.r, 0])?;
+    }
+
+    let pixels_per_byte = 8 / bpp as usize;
+    for row in indexes.chunks_exact(width.max(1)) {
+        let mut row_bytes = vec![0u8; row_size];
+        for (x, &index) in row.iter().enumerate() {
+            let shift = 8 - bpp as usize * (x % pixels_per_byte + 1);
+            row_bytes[x / pixels_per_byte] |= index << shift;
+        }
+        bmp_data.write_all(&row_bytes)?;
+    }
+
+    Ok(bmp_data)
+}
+
+fn validate_image(bmp_image: &Image) -> io::Result<()> {
+    let width = bmp_image.width;
+    let height = bmp_image.height;
+    if width > i32::MAX as u32 || height > i32::MAX as u32 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Image dimensions {}x{} are too large for BMP",
+                width, height
+            ),
+        ));
+    }
+    if bmp_image.data.len() != width as usize * height as usize {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Image data has {} pixels, expected {}x{}",
+                bmp_image.data.len(),
+                width,
+                height
+            ),
+        ));
+    }
+    Ok(())
+}
+
+fn encode_with_depth(bmp_image: &Image, bpp: u16) -> io::Result<Vec<u8>> {
+    validate_image(bmp_image)?;
+    let width = bmp_image.width;
+    let height = bmp_image.height;
+
+    let dib_header_size = if bpp == 32 {
+        V4_HEADER_SIZE
+    } else {
+        V3_HEADER_SIZE
+    };
+    let bytes_per_pixel = bpp as usize / 8;
+    let row_size = (width as usize * bytes_per_pixel).div_ceil(4) * 4;
+    let padding = row_size - width as usize * bytes_per_pixel;
+    let data_size = u32::try_from(row_size * height as usize).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Image pixel data is too large for BMP",
+        )
+    })?;
+
+    let header = BmpHeader::new(BMP_HEADER_SIZE + dib_header_size, data_size);
+    let dib_header = BmpDibHeader {
+        header_size: dib_header_size,
+        bits_per_pixel: bpp,
+        compress_type: if bpp == 32 { BI_BITFIELDS } else { BI_RGB },
+        data_size,
+        hres: bmp_image.dib_header.hres,
+        vres: bmp_image.dib_header.vres,
+        ..BmpDibHeader::new(width as i32, height as i32)
+    };
+
+    let mut bmp_data = Vec::with_capacity(header.file_size as usize);
+    write_header(&mut bmp_data, &header)?;
+    write_dib_header(&mut bmp_data, &dib_header)?;
+    if bpp == 32 {
+        write_v4_extension(&mut bmp_data)?;
+    }
+
+    // `data` is already stored bottom-up, as BMP rows are
+    for row in bmp_image.data.chunks_exact(width.max(1) as usize) {
+        for pixel in row {
+            if bpp == 32 {
+                bmp_data.write_all(&[pixel.b, pixel.g, pixel.r, pixel.a])?;
+            } else {
+                bmp_data.write_all(&[pixel.b, pixel.g, pixel.r])?;
+            }
+        }
+        bmp_data.write_all(&[0; 3][..padding])?;
+    }
+
+    Ok(bmp_data)
+}
+
+fn write_header(bmp_data: &mut Vec<u8>, header: &BmpHeader) -> io::Result<()> {
+    bmp_data.write_all(b"BM")?;
+    bmp_data.write_all(&header.file_size.to_le_bytes())?;
+    bmp_data.write_all(&header.creator1.to_le_bytes())?;
+    bmp_data.write_all(&header.creator2.to_le_bytes())?;
+    bmp_data.write_all(&header.pixel_offset.to_le_bytes())
+}
+
+fn write_dib_header(bmp_data: &mut Vec<u8>, dib_header: &BmpDibHeader) -> io::Result<()> {
+    bmp_data.write_all(&dib_header.header_size.to_le_bytes())?;
+    bmp_data.write_all(&dib_header.width.to_le_bytes())?;
+    bmp_data.write_all(&dib_header.height.to_le_bytes())?;
+    bmp_data.write_all(&dib_header.num_planes.to_le_bytes())?;
+    bmp_data.write_all(&dib_header.bits_per_pixel.to_le_bytes())?;
+    bmp_data.write_all(&dib_header.compress_type.to_le_bytes())?;
+    bmp_data.write_all(&dib_header.data_size.to_le_bytes())?;
+    bmp_data.write_all(&dib_header.hres.to_le_bytes())?;
+    bmp_data.write_all(&dib_header.vres.to_le_bytes())?;
+    bmp_data.write_all(&dib_header.num_colors.to_le_bytes())?;
+    bmp_data.write_all(&dib_header.num_imp_colors.to_le_bytes())
+}
+
+/// Writes the BITMAPV4HEADER fields following the 40-byte part: BGRA channel masks, the sRGB
+/// color space tag, and zeroed endpoints and gamma.
+fn write_v4_extension(bmp_data: &mut Vec<u8>) -> io::Result<()> {
+    for mask in [
+        0x00FF_0000u32,
+        0x0000_FF00,
+        0x0000_00FF,
+        0xFF00_0000,
+        LCS_SRGB,
+    ] {
+        bmp_data.write_all(&mask.to_le_bytes())?;
+    }
+    // CIEXYZTRIPLE endpoints (36 bytes) and red/green/blue gamma (12 bytes)
+    bmp_data.write_all(&[0; 48])
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::{IndexedBmpOptions, encode_image, encode_indexed_image};
+    use crate::loaders::bmp::image::{Image, Pixel, from_reader};
+
+    fn gradient(width: u32, height: u32) -> Image {
+        let mut image = Image::new(width, height);
+        for (x, y) in image.coordinates() {
+            image.set_pixel(x, y, Pixel::new((x * 40) as u8, (y * 60) as u8, 200));
+        }
+        image
+    }
+
+    #[test]
+    fn round_trips_24_bit_images_with_row_padding() {
+        for width in 1..=5 {
+            let image = gradient(width, 3);
+            let bytes = encode_image(&image).expect("image should encode");
+
+            let row_size = (width * 3).div_ceil(4) * 4;
+            assert_eq!(bytes.len() as u32, 54 + row_size * 3);
+            assert_eq!(u16::from_le_bytes([bytes[28], bytes[29]]), 24);
+
+            let decoded =
+                from_reader(&mut Cursor::new(bytes)).expect("encoded image should decode");
+            assert_eq!((decoded.width, decoded.height), (width, 3));
+            assert_eq!(decoded.data, image.data);
+        }
+    }
+
+    #[test]
+    fn round_trips_32_bit_images_with_alpha() {
+        let mut image = gradient(3, 2);
+        image.set_pixel(1, 0, Pixel::with_alpha(10, 20, 30, 0));
+        image.set_pixel(2, 1, Pixel::with_alpha(40, 50, 60, 128));
+        let mut bytes = Vec::new();
+        image.to_writer(&mut bytes).expect("image should encode");
+
+        assert_eq!(bytes.len(), 14 + 108 + 3 * 4 * 2);
+        assert_eq!(u16::from_le_bytes([bytes[28], bytes[29]]), 32);
+
+        let decoded = from_reader(&mut Cursor::new(bytes)).expect("encoded image should decode");
+        assert_eq!(decoded.data, image.data);
+        assert!(decoded.has_alpha());
+    }
+
+    #[test]
+    fn rejects_inconsistent_pixel_data() {
+        let mut image = Image::new(4, 4);
+        image.data.pop();
+        assert!(encode_image(&image).is_err());
+    }
+
+    fn indexed(bits_per_pixel: u16, dither: bool) -> IndexedBmpOptions {
+        IndexedBmpOptions {
+            bits_per_pixel,
+            dither,
+        }
+    }
+
+    #[test]
+    fn indexed_export_is_lossless_when_colors_fit() {
+        let colors = [
+            Pixel::new(255, 255, 255),
+            Pixel::new(200, 30, 30),
+            Pixel::new(30, 200, 30),
+            Pixel::new(30, 30, 200),
+        ];
+        for (bpp, width) in [(4, 7), (8, 5)] {
+            let mut image = Image::new(width, 3);
+            for (x, y) in image.coordinates() {
+                image.set_pixel(x, y, colors[((x + y) % 4) as usize]);
+            }
+
+            let bytes = encode_indexed_image(&image, indexed(bpp, false)).expect("should encode");
+            let decoded = from_reader(&mut Cursor::new(bytes)).expect("should decode");
+            assert_eq!(decoded.color_palette.as_ref().map(Vec::len), Some(4));
+            assert_eq!(decoded.data, image.data);
+        }
+    }
+
+    #[test]
+    fn one_bit_export_packs_rows_and_pads() {
+        let mut image = Image::new(9, 2);
+        for (x, y) in image.coordinates() {
+            if (x + y) % 2 == 0 {
+                image.set_pixel(x, y, Pixel::new(255, 255, 255));
+            }
+        }
+
+        let bytes = encode_indexed_image(&image, indexed(1, false)).expect("should encode");
+        // 9 pixels need 2 bytes per row, padded to 4
+        assert_eq!(bytes.len(), 14 + 40 + 2 * 4 + 2 * 4);
+        let decoded = from_reader(&mut Cursor::new(bytes)).expect("should decode");
+        assert_eq!(decoded.data, image.data);
+    }
+
+    #[test]
+    fn indexed_export_quantizes_with_optional_dithering() {
+        let mut image = Image::new(32, 8);
+        for (x, y) in image.coordinates() {
+            image.set_pixel(x, y, Pixel::new((x * 8) as u8, (y * 32) as u8, 128));
+        }
+        for dither in [false, true] {
+            let bytes = encode_indexed_image(&image, indexed(4, dither)).expect("should encode");
+            let decoded = from_reader(&mut Cursor::new(bytes)).expect("should decode");
+
+            let palette = decoded
+                .color_palette
+                .clone()
+                .expect("indexed BMP has a palette");
+            assert!(palette.len() <= 16);
+            assert!(decoded.data.iter().all(|pixel| palette.contains(pixel)));
+            let total_error: i64 = decoded
+                .data
+                .iter()
+                .zip(&image.data)
+                .map(|(a, b)| (a.r as i64 - b.r as i64).abs() + (a.g as i64 - b.g as i64).abs())
+                .sum();
+            assert!(
+                total_error / (32 * 8) < 40,
+                "mean error {}",
+                total_error / 256
+            );
+        }
+
+        assert!(encode_indexed_image(&image, indexed(2, false)).is_err());
+    }
+}
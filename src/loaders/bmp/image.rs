@@ -5,23 +5,30 @@
 use std::convert::AsRef;
 use std::fmt;
 use std::fs;
-use std::io::{Cursor, Read};
+use std::io::{self, Cursor, Read, Write};
 use std::iter::Iterator;
 use std::path::Path;
 
-use crate::loaders::bmp::decoder;
+use crate::loaders::bmp::{decoder, encoder};
+
+pub use encoder::IndexedBmpOptions;
 
 // Expose decoder's public types, structs, and enums
 pub use decoder::BmpResult;
 
-/// Macro to generate a `Pixel` from `r`, `g` and `b` values.
+/// Macro to generate a `Pixel` from `r`, `g`, `b` and optionally `a` values.
+/// Pixels without an explicit alpha value are fully opaque.
 #[macro_export]
 macro_rules! px {
     ($r:expr, $g:expr, $b:expr) => {
+        $crate::px!($r, $g, $b, 255)
+    };
+    ($r:expr, $g:expr, $b:expr, $a:expr) => {
         Pixel {
             r: $r as u8,
             g: $g as u8,
             b: $b as u8,
+            a: $a as u8,
         }
     };
 }
@@ -38,25 +45,35 @@ macro_rules! file_size {
 
 /// Common color constants accessible by names.
 /// The pixel data used in the `Image`.
-/// It has three values for the `red`, `blue` and `green` color channels, respectively.
+/// It has values for the `red`, `green`, `blue` and `alpha` channels, respectively.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct Pixel {
     pub r: u8,
     pub g: u8,
     pub b: u8,
+    pub a: u8,
 }
 
 impl Pixel {
-    /// Creates a new `Pixel`.
+    /// Creates a new, fully opaque `Pixel`.
     pub fn new(r: u8, g: u8, b: u8) -> Pixel {
-        Pixel { r, g, b }
+        Pixel { r, g, b, a: 255 }
+    }
+
+    /// Creates a new `Pixel` with an explicit alpha value.
+    pub fn with_alpha(r: u8, g: u8, b: u8, a: u8) -> Pixel {
+        Pixel { r, g, b, a }
     }
 }
 
-/// Displays the rgb values as an rgb color triple
+/// Displays the rgb values as an rgb color triple, or an rgba quadruple for translucent pixels
 impl fmt::Display for Pixel {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "rgb({}, {}, {})", self.r, self.g, self.b)
+        if self.a == 255 {
+            write!(f, "rgb({}, {}, {})", self.r, self.g, self.b)
+        } else {
+            write!(f, "rgba({}, {}, {}, {})", self.r, self.g, self.b, self.a)
+        }
     }
 }
 
@@ -77,6 +94,7 @@ impl fmt::LowerHex for Pixel {
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum BmpVersion {
     Two,
+    Os2Two,
     Three,
     ThreeNT,
     Four,
@@ -87,6 +105,8 @@ impl BmpVersion {
     pub fn from_dib_header(dib_header: &BmpDibHeader) -> Option<BmpVersion> {
         match dib_header.header_size {
             12 => Some(BmpVersion::Two),
+            // OS/2 2.x headers are 64 bytes, but may be truncated to any size from 16 bytes up
+            16..40 | 41..=64 => Some(BmpVersion::Os2Two),
             40 if dib_header.compress_type == 3 => Some(BmpVersion::ThreeNT),
             40 => Some(BmpVersion::Three),
             108 => Some(BmpVersion::Four),
@@ -100,6 +120,7 @@ impl AsRef<str> for BmpVersion {
     fn as_ref(&self) -> &str {
         match *self {
             BmpVersion::Two => "BMP Version 2",
+            BmpVersion::Os2Two => "OS/2 BMP Version 2",
             BmpVersion::Three => "BMP Version 3",
             BmpVersion::ThreeNT => "BMP Version 3 NT",
             BmpVersion::Four => "BMP Version 4",
@@ -200,7 +221,8 @@ impl BmpDibHeader {
 /// The image is accessed in row-major order from top to bottom,
 /// where point (0, 0) is defined to be in the upper left corner of the image.
 ///
-/// Currently, only uncompressed BMP images are supported.
+/// Uncompressed, RLE and bitfield-encoded BMP images are supported. Pixels carry an alpha
+/// channel, which is only decoded from 16- and 32-bit images with an alpha mask.
 #[derive(Clone, Eq, PartialEq)]
 pub struct Image {
     pub header: BmpHeader,
@@ -238,7 +260,7 @@ impl Image {
             color_palette: None,
             width,
             height,
-            padding: width % 4,
+            padding: (4 - (width * 3) % 4) % 4,
             data,
         }
     }
@@ -286,6 +308,96 @@ impl Image {
         self.data[((self.height - y - 1) * self.width + x) as usize]
     }
 
+    /// Saves the `Image` as a BMP file at `path`.
+    ///
+    /// Opaque images are written as 24-bit BMPs; images with transparency as 32-bit BMPs with
+    /// an alpha mask.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use scop_42::loaders::bmp::image::Image;
+    ///
+    /// let img = Image::new(16, 16);
+    /// img.save("black.bmp").expect("Failed to save BMP");
+    /// ```
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut bmp_file = fs::File::create(path)?;
+        self.to_writer(&mut bmp_file)
+    }
+
+    /// Writes the `Image` as a BMP file to the given writer, using the same format as `save`.
+    pub fn to_writer<W: Write>(&self, destination: &mut W) -> io::Result<()> {
+        let bmp_data = encoder::encode_image(self)?;
+        destination.write_all(&bmp_data)
+    }
+
+    /// Saves the `Image` as a palettized 1, 4 or 8-bit BMP file at `path`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use scop_42::loaders::bmp::image::{Image, IndexedBmpOptions};
+    ///
+    /// let img = Image::new(64, 64);
+    /// let options = IndexedBmpOptions { bits_per_pixel: 4, dither: true };
+    /// img.save_indexed("thumbnail.bmp", options).expect("Failed to save BMP");
+    /// ```
+    pub fn save_indexed<P: AsRef<Path>>(
+        &self,
+        path: P,
+        options: IndexedBmpOptions,
+    ) -> io::Result<()> {
+        let mut bmp_file = fs::File::create(path)?;
+        self.to_writer_indexed(&mut bmp_file, options)
+    }
+
+    /// Writes the `Image` as a palettized BMP file to the given writer.
+    pub fn to_writer_indexed<W: Write>(
+        &self,
+        destination: &mut W,
+        options: IndexedBmpOptions,
+    ) -> io::Result<()> {
+        let bmp_data = encoder::encode_indexed_image(self, options)?;
+        destination.write_all(&bmp_data)
+    }
+
+    /// Returns `true` if any pixel is not fully opaque.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use scop_42::loaders::bmp::image::{Image, Pixel};
+    ///
+    /// let mut img = Image::new(2, 2);
+    /// assert!(!img.has_alpha());
+    /// img.set_pixel(1, 1, Pixel::with_alpha(255, 0, 0, 128));
+    /// assert!(img.has_alpha());
+    /// ```
+    pub fn has_alpha(&self) -> bool {
+        self.data.iter().any(|pixel| pixel.a != 255)
+    }
+
+    /// Makes every pixel opaque if the alpha channel is zero everywhere. Many writers leave the
+    /// alpha bits of 32-bit images unset, so an all-zero channel is read as "no alpha" rather
+    /// than as a fully transparent image.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use scop_42::loaders::bmp::image::{Image, Pixel};
+    ///
+    /// let mut img = Image::new(1, 1);
+    /// img.set_pixel(0, 0, Pixel::with_alpha(255, 0, 0, 0));
+    /// img.treat_unset_alpha_as_opaque();
+    /// assert_eq!(Pixel::new(255, 0, 0), img.get_pixel(0, 0));
+    /// ```
+    pub fn treat_unset_alpha_as_opaque(&mut self) {
+        if self.data.iter().all(|pixel| pixel.a == 0) {
+            self.data.iter_mut().for_each(|pixel| pixel.a = 255);
+        }
+    }
+
     /// Returns a new `ImageIndex` that iterates over the image dimensions in top-bottom order.
     ///
     /// # Example
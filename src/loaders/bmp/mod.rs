@@ -1,4 +1,35 @@
+#[cfg(test)]
+mod conformance;
 pub mod decoder;
+pub mod encoder;
 pub mod image;
+pub mod quantize;
+
+use std::path::Path;
+
+use crate::loaders::registry::{TextureData, TextureDecoder};
 
 pub use image::open;
+
+/// Registry entry for BMP textures.
+pub struct BmpDecoder;
+
+impl TextureDecoder for BmpDecoder {
+    fn name(&self) -> &'static str {
+        "BMP"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["bmp"]
+    }
+
+    fn matches_magic(&self, header: &[u8]) -> bool {
+        header.starts_with(b"BM")
+    }
+
+    fn decode(&self, path: &Path) -> Result<TextureData, String> {
+        open(path)
+            .map(|image| TextureData::Levels(vec![image]))
+            .map_err(|error| error.to_string())
+    }
+}
@@ -0,0 +1,213 @@
+use std::collections::HashMap;
+
+use super::image::Pixel;
+
+/// Builds a palette of at most `max_colors` entries with median cut.
+///
+/// Distinct colors start in a single box. The box with the widest channel range is repeatedly
+/// split at the pixel-weighted median of that channel, and each final box contributes its
+/// weighted average color. Images that already use `max_colors` or fewer colors get exactly
+/// those colors back, so palettized export is lossless for them.
+pub fn median_cut_palette(pixels: &[Pixel], max_colors: usize) -> Vec<Pixel> {
+    let mut histogram: HashMap<[u8; 3], u32> = HashMap::new();
+    for pixel in pixels {
+        *histogram.entry([pixel.r, pixel.g, pixel.b]).or_default() += 1;
+    }
+    let mut colors: Vec<([u8; 3], u32)> = histogram.into_iter().collect();
+    // Deterministic output regardless of hash order
+    colors.sort_unstable();
+
+    if max_colors == 0 || colors.is_empty() {
+        return Vec::new();
+    }
+
+    let mut boxes = vec![colors];
+    while boxes.len() < max_colors {
+        let Some((box_index, channel)) = boxes
+            .iter()
+            .enumerate()
+            .filter(|(_, colors)| colors.len() > 1)
+            .map(|(index, colors)| {
+                let (channel, range) = widest_channel(colors);
+                (index, channel, range)
+            })
+            .max_by_key(|&(index, _, range)| (range, std::cmp::Reverse(index)))
+            .map(|(index, channel, _)| (index, channel))
+        else {
+            break;
+        };
+
+        let mut colors = boxes.swap_remove(box_index);
+        colors.sort_unstable_by_key(|&(color, _)| color[channel]);
+        let total: u32 = colors.iter().map(|&(_, count)| count).sum();
+        let mut seen = 0;
+        let split = colors
+            .iter()
+            .position(|&(_, count)| {
+                seen += count;
+                seen * 2 >= total
+            })
+            .map_or(1, |median| median + 1)
+            .clamp(1, colors.len() - 1);
+
+        let upper = colors.split_off(split);
+        boxes.push(colors);
+        boxes.push(upper);
+    }
+
+    let mut palette: Vec<Pixel> = boxes.iter().map(|colors| average_color(colors)).collect();
+    palette.sort_unstable_by_key(|pixel| (pixel.r, pixel.g, pixel.b));
+    palette.dedup();
+    palette
+}
+
+/// Maps every pixel to the index of its nearest palette entry.
+///
+/// With `dither`, the quantization error of each pixel is diffused to its unvisited neighbors
+/// using the Floyd–Steinberg weights (7/16 right, 3/16 below-left, 5/16 below, 1/16 below-right).
+/// `pixels` is processed in row order with rows of `width` pixels.
+pub fn map_to_palette(pixels: &[Pixel], width: usize, palette: &[Pixel], dither: bool) -> Vec<u8> {
+    let mut cache: HashMap<[u8; 3], u8> = HashMap::new();
+    let mut nearest = |color: [u8; 3]| {
+        *cache
+            .entry(color)
+            .or_insert_with(|| nearest_index(palette, color))
+    };
+
+    if !dither || width == 0 {
+        return pixels
+            .iter()
+            .map(|pixel| nearest([pixel.r, pixel.g, pixel.b]))
+            .collect();
+    }
+
+    let mut indexes = Vec::with_capacity(pixels.len());
+    let mut current_errors = vec![[0.0f32; 3]; width + 2];
+    let mut next_errors = vec![[0.0f32; 3]; width + 2];
+
+    for row in pixels.chunks(width) {
+        for (x, pixel) in row.iter().enumerate() {
+            // Error buffers are offset by one so x - 1 never underflows
+            let error = current_errors[x + 1];
+            let wanted = [
+                pixel.r as f32 + error[0],
+                pixel.g as f32 + error[1],
+                pixel.b as f32 + error[2],
+            ];
+            let index = nearest(wanted.map(|channel| channel.round().clamp(0.0, 255.0) as u8));
+            indexes.push(index);
+
+            let chosen = palette[index as usize];
+            let chosen = [chosen.r as f32, chosen.g as f32, chosen.b as f32];
+            for channel in 0..3 {
+                let residual = wanted[channel] - chosen[channel];
+                current_errors[x + 2][channel] += residual * 7.0 / 16.0;
+                next_errors[x][channel] += residual * 3.0 / 16.0;
+                next_errors[x + 1][channel] += residual * 5.0 / 16.0;
+                next_errors[x + 2][channel] += residual / 16.0;
+            }
+        }
+        std::mem::swap(&mut current_errors, &mut next_errors);
+        next_errors.iter_mut().for_each(|error| *error = [0.0; 3]);
+    }
+
+    indexes
+}
+
+fn widest_channel(colors: &[([u8; 3], u32)]) -> (usize, u8) {
+    (0..3)
+        .map(|channel| {
+            let (min, max) = colors
+                .iter()
+                .fold((u8::MAX, u8::MIN), |(min, max), (color, _)| {
+                    (min.min(color[channel]), max.max(color[channel]))
+                });
+            (channel, max - min)
+        })
+        .max_by_key(|&(channel, range)| (range, std::cmp::Reverse(channel)))
+        .unwrap_or((0, 0))
+}
+
+fn average_color(colors: &[([u8; 3], u32)]) -> Pixel {
+    let mut sums = [0u64; 3];
+    let mut total = 0u64;
+    for &(color, count) in colors {
+        for channel in 0..3 {
+            sums[channel] += color[channel] as u64 * count as u64;
+        }
+        total += count as u64;
+    }
+    let [r, g, b] = sums.map(|sum| ((sum + total / 2) / total.max(1)) as u8);
+    Pixel::new(r, g, b)
+}
+
+fn nearest_index(palette: &[Pixel], color: [u8; 3]) -> u8 {
+    palette
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, entry)| {
+            let dr = entry.r as i32 - color[0] as i32;
+            let dg = entry.g as i32 - color[1] as i32;
+            let db = entry.b as i32 - color[2] as i32;
+            dr * dr + dg * dg + db * db
+        })
+        .map_or(0, |(index, _)| index as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{map_to_palette, median_cut_palette};
+    use crate::loaders::bmp::image::Pixel;
+
+    fn grey_ramp(len: usize) -> Vec<Pixel> {
+        (0..len)
+            .map(|i| {
+                let value = (i * 255 / (len - 1)) as u8;
+                Pixel::new(value, value, value)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn keeps_exact_colors_when_they_fit() {
+        let pixels = vec![
+            Pixel::new(255, 0, 0),
+            Pixel::new(0, 255, 0),
+            Pixel::new(255, 0, 0),
+            Pixel::new(0, 0, 255),
+        ];
+        let palette = median_cut_palette(&pixels, 4);
+
+        assert_eq!(palette.len(), 3);
+        for pixel in &pixels {
+            assert!(palette.contains(pixel));
+        }
+    }
+
+    #[test]
+    fn reduces_to_requested_palette_size() {
+        let pixels = grey_ramp(256);
+        let palette = median_cut_palette(&pixels, 16);
+
+        assert_eq!(palette.len(), 16);
+        let indexes = map_to_palette(&pixels, 256, &palette, false);
+        for (pixel, index) in pixels.iter().zip(indexes) {
+            let entry = palette[index as usize];
+            assert!((pixel.r as i32 - entry.r as i32).abs() <= 8);
+        }
+    }
+
+    #[test]
+    fn dithering_preserves_average_intensity() {
+        // A mid grey area quantized to black and white
+        let pixels = vec![Pixel::new(128, 128, 128); 16 * 16];
+        let palette = vec![Pixel::new(0, 0, 0), Pixel::new(255, 255, 255)];
+
+        let flat = map_to_palette(&pixels, 16, &palette, false);
+        assert!(flat.iter().all(|&index| index == flat[0]));
+
+        let dithered = map_to_palette(&pixels, 16, &palette, true);
+        let white = dithered.iter().filter(|&&index| index == 1).count();
+        assert!((120..=136).contains(&white), "white pixels: {}", white);
+    }
+}
@@ -0,0 +1,481 @@
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+use crate::loaders::bmp::image::{Image, Pixel};
+
+const MAGIC: &[u8; 4] = b"DDS ";
+const HEADER_SIZE: usize = 124;
+const DX10_HEADER_SIZE: usize = 20;
+
+const DDSD_MIPMAPCOUNT: u32 = 0x2_0000;
+const DDPF_ALPHAPIXELS: u32 = 0x1;
+const DDPF_FOURCC: u32 = 0x4;
+const DDPF_RGB: u32 = 0x40;
+const DDSCAPS2_CUBEMAP: u32 = 0x200;
+const DDSCAPS2_VOLUME: u32 = 0x20_0000;
+const D3D10_RESOURCE_DIMENSION_TEXTURE2D: u32 = 3;
+
+/// How the pixels of every mip level are stored.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Format {
+    Bc1,
+    Bc2,
+    Bc3,
+    /// Uncompressed 24/32-bit pixels described by channel masks.
+    Masked {
+        bytes_per_pixel: usize,
+        masks: [u32; 4],
+    },
+}
+
+impl Format {
+    fn level_size(self, width: u32, height: u32) -> usize {
+        let (width, height) = (width as usize, height as usize);
+        match self {
+            Format::Bc1 => width.div_ceil(4) * height.div_ceil(4) * 8,
+            Format::Bc2 | Format::Bc3 => width.div_ceil(4) * height.div_ceil(4) * 16,
+            Format::Masked {
+                bytes_per_pixel, ..
+            } => width * height * bytes_per_pixel,
+        }
+    }
+}
+
+/// Loads a DDS file, returning its mip chain from the full-size level down.
+pub fn open<P: AsRef<Path>>(path: P) -> Result<Vec<Image>, String> {
+    let mut file = fs::File::open(&path).map_err(|error| {
+        format!(
+            "Failed to open DDS '{}': {}",
+            path.as_ref().display(),
+            error
+        )
+    })?;
+    from_reader(&mut file)
+}
+
+/// Reads and decodes a DDS texture from `source`.
+pub fn from_reader<R: Read>(source: &mut R) -> Result<Vec<Image>, String> {
+    let mut bytes = Vec::new();
+    source
+        .read_to_end(&mut bytes)
+        .map_err(|error| format!("Failed to read DDS data: {}", error))?;
+    decode(&bytes)
+}
+
+/// Decodes a 2D DDS texture (BC1–BC3 or uncompressed RGB/RGBA) and every mip level it stores.
+pub fn decode(bytes: &[u8]) -> Result<Vec<Image>, String> {
+    if bytes.len() < MAGIC.len() + HEADER_SIZE || &bytes[..4] != MAGIC {
+        return Err("Not a DDS file (missing 'DDS ' header)".to_string());
+    }
+    let field = |index: usize| read_u32(bytes, MAGIC.len() + index * 4);
+    if field(0) as usize != HEADER_SIZE || field(18) != 32 {
+        return Err(format!(
+            "Invalid DDS header size {} or pixel format size {}",
+            field(0),
+            field(18)
+        ));
+    }
+
+    let flags = field(1);
+    let height = field(2);
+    let width = field(3);
+    let mip_count = if flags & DDSD_MIPMAPCOUNT != 0 {
+        field(6).max(1)
+    } else {
+        1
+    };
+    let pixel_format_flags = field(19);
+    let four_cc = &bytes[MAGIC.len() + 20 * 4..MAGIC.len() + 21 * 4];
+    let caps2 = field(27);
+
+    if width == 0 || height == 0 {
+        return Err(format!(
+            "DDS texture has invalid dimensions {}x{}",
+            width, height
+        ));
+    }
+    if caps2 & (DDSCAPS2_CUBEMAP | DDSCAPS2_VOLUME) != 0 {
+        return Err("DDS cube maps and volume textures are not supported".to_string());
+    }
+    if mip_count > 32 || (width >> (mip_count - 1) == 0 && height >> (mip_count - 1) == 0) {
+        return Err(format!(
+            "DDS mip count {} is too large for {}x{}",
+            mip_count, width, height
+        ));
+    }
+
+    let mut offset = MAGIC.len() + HEADER_SIZE;
+    let format = if pixel_format_flags & DDPF_FOURCC != 0 {
+        match four_cc {
+            b"DXT1" => Format::Bc1,
+            // DXT2/DXT4 are the premultiplied-alpha variants of DXT3/DXT5
+            b"DXT2" | b"DXT3" => Format::Bc2,
+            b"DXT4" | b"DXT5" => Format::Bc3,
+            b"DX10" => {
+                let format = read_dx10_format(bytes, offset)?;
+                offset += DX10_HEADER_SIZE;
+                format
+            }
+            _ => {
+                return Err(format!(
+                    "Unsupported DDS compression '{}'",
+                    String::from_utf8_lossy(four_cc)
+                ));
+            }
+        }
+    } else if pixel_format_flags & DDPF_RGB != 0 {
+        let bit_count = field(21);
+        if bit_count != 24 && bit_count != 32 {
+            return Err(format!(
+                "Unsupported uncompressed DDS bit count {}",
+                bit_count
+            ));
+        }
+        let alpha_mask = if pixel_format_flags & DDPF_ALPHAPIXELS != 0 {
+            field(25)
+        } else {
+            0
+        };
+        Format::Masked {
+            bytes_per_pixel: bit_count as usize / 8,
+            masks: [field(22), field(23), field(24), alpha_mask],
+        }
+    } else {
+        return Err(format!(
+            "Unsupported DDS pixel format flags 0x{:X}",
+            pixel_format_flags
+        ));
+    };
+
+    let mut levels = Vec::with_capacity(mip_count as usize);
+    for level in 0..mip_count {
+        let level_width = (width >> level).max(1);
+        let level_height = (height >> level).max(1);
+        let size = format.level_size(level_width, level_height);
+        let data = bytes.get(offset..offset + size).ok_or_else(|| {
+            format!(
+                "DDS data ended inside mip level {} ({}x{})",
+                level, level_width, level_height
+            )
+        })?;
+        levels.push(decode_level(format, level_width, level_height, data));
+        offset += size;
+    }
+
+    Ok(levels)
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().expect("4 bytes"))
+}
+
+fn read_dx10_format(bytes: &[u8], offset: usize) -> Result<Format, String> {
+    if bytes.len() < offset + DX10_HEADER_SIZE {
+        return Err("DDS file is missing its DX10 header".to_string());
+    }
+    let dxgi_format = read_u32(bytes, offset);
+    let dimension = read_u32(bytes, offset + 4);
+    let array_size = read_u32(bytes, offset + 12);
+    if dimension != D3D10_RESOURCE_DIMENSION_TEXTURE2D || array_size > 1 {
+        return Err("Only single 2D DX10 DDS textures are supported".to_string());
+    }
+    match dxgi_format {
+        // BC1/BC2/BC3 in their typeless, UNORM and UNORM_SRGB variants
+        70..=72 => Ok(Format::Bc1),
+        73..=75 => Ok(Format::Bc2),
+        76..=78 => Ok(Format::Bc3),
+        _ => Err(format!("Unsupported DXGI format {}", dxgi_format)),
+    }
+}
+
+fn decode_level(format: Format, width: u32, height: u32, data: &[u8]) -> Image {
+    let mut image = Image::new(width, height);
+    match format {
+        Format::Masked {
+            bytes_per_pixel,
+            masks,
+        } => {
+            for (index, chunk) in data.chunks_exact(bytes_per_pixel).enumerate() {
+                let mut raw = [0u8; 4];
+                raw[..bytes_per_pixel].copy_from_slice(chunk);
+                let value = u32::from_le_bytes(raw);
+                let [r, g, b, a] = masks.map(|mask| extract_channel(value, mask));
+                let alpha = if masks[3] == 0 { 255 } else { a };
+                image.set_pixel(
+                    index as u32 % width,
+                    index as u32 / width,
+                    Pixel::with_alpha(r, g, b, alpha),
+                );
+            }
+        }
+        Format::Bc1 | Format::Bc2 | Format::Bc3 => {
+            let block_size = if format == Format::Bc1 { 8 } else { 16 };
+            let blocks_wide = width.div_ceil(4);
+            for (index, block) in data.chunks_exact(block_size).enumerate() {
+                let texels = match format {
+                    Format::Bc1 => decode_bc1(block, true),
+                    Format::Bc2 => decode_bc2(block),
+                    _ => decode_bc3(block),
+                };
+                let block_x = index as u32 % blocks_wide * 4;
+                let block_y = index as u32 / blocks_wide * 4;
+                for (texel, pixel) in texels.into_iter().enumerate() {
+                    let x = block_x + texel as u32 % 4;
+                    let y = block_y + texel as u32 / 4;
+                    // Levels smaller than a block still store whole blocks
+                    if x < width && y < height {
+                        image.set_pixel(x, y, pixel);
+                    }
+                }
+            }
+        }
+    }
+    image
+}
+
+/// Scales the bits selected by `mask` to the 0-255 range.
+fn extract_channel(value: u32, mask: u32) -> u8 {
+    if mask == 0 {
+        return 0;
+    }
+    let max = u64::from(mask >> mask.trailing_zeros());
+    let bits = u64::from((value & mask) >> mask.trailing_zeros());
+    ((bits * 255 + max / 2) / max) as u8
+}
+
+fn rgb565(color: u16) -> [u8; 3] {
+    let r = (color >> 11) as u8 & 0x1F;
+    let g = (color >> 5) as u8 & 0x3F;
+    let b = color as u8 & 0x1F;
+    [r << 3 | r >> 2, g << 2 | g >> 4, b << 3 | b >> 2]
+}
+
+/// Decodes a BC1 color block. BC2/BC3 color blocks always use the four-color mode, whereas
+/// BC1 switches to three colors plus transparent black when `color0 <= color1`.
+fn decode_bc1(block: &[u8], allow_transparent: bool) -> [Pixel; 16] {
+    let color0 = u16::from_le_bytes([block[0], block[1]]);
+    let color1 = u16::from_le_bytes([block[2], block[3]]);
+    let indices = u32::from_le_bytes([block[4], block[5], block[6], block[7]]);
+    let (c0, c1) = (rgb565(color0), rgb565(color1));
+
+    let mix = |weight0: u16, weight1: u16| {
+        let total = weight0 + weight1;
+        let channel =
+            |i: usize| ((u16::from(c0[i]) * weight0 + u16::from(c1[i]) * weight1) / total) as u8;
+        Pixel::new(channel(0), channel(1), channel(2))
+    };
+    let palette = if color0 > color1 || !allow_transparent {
+        [
+            Pixel::new(c0[0], c0[1], c0[2]),
+            Pixel::new(c1[0], c1[1], c1[2]),
+            mix(2, 1),
+            mix(1, 2),
+        ]
+    } else {
+        [
+            Pixel::new(c0[0], c0[1], c0[2]),
+            Pixel::new(c1[0], c1[1], c1[2]),
+            mix(1, 1),
+            Pixel::with_alpha(0, 0, 0, 0),
+        ]
+    };
+
+    std::array::from_fn(|texel| palette[(indices >> (texel * 2) & 0x3) as usize])
+}
+
+/// BC2: explicit 4-bit alpha per texel followed by a BC1 color block.
+fn decode_bc2(block: &[u8]) -> [Pixel; 16] {
+    let alphas = u64::from_le_bytes(block[..8].try_into().expect("8 bytes"));
+    let mut texels = decode_bc1(&block[8..], false);
+    for (texel, pixel) in texels.iter_mut().enumerate() {
+        pixel.a = (alphas >> (texel * 4) & 0xF) as u8 * 17;
+    }
+    texels
+}
+
+/// BC3: two alpha endpoints with 3-bit interpolation indices followed by a BC1 color block.
+fn decode_bc3(block: &[u8]) -> [Pixel; 16] {
+    let (a0, a1) = (u16::from(block[0]), u16::from(block[1]));
+    let mut palette = [0u8; 8];
+    palette[0] = a0 as u8;
+    palette[1] = a1 as u8;
+    if a0 > a1 {
+        for i in 1..7 {
+            palette[i + 1] = (((7 - i as u16) * a0 + i as u16 * a1) / 7) as u8;
+        }
+    } else {
+        for i in 1..5 {
+            palette[i + 1] = (((5 - i as u16) * a0 + i as u16 * a1) / 5) as u8;
+        }
+        palette[6] = 0;
+        palette[7] = 255;
+    }
+
+    let mut raw = [0u8; 8];
+    raw[..6].copy_from_slice(&block[2..8]);
+    let indices = u64::from_le_bytes(raw);
+    let mut texels = decode_bc1(&block[8..], false);
+    for (texel, pixel) in texels.iter_mut().enumerate() {
+        pixel.a = palette[(indices >> (texel * 3) & 0x7) as usize];
+    }
+    texels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::decode;
+    use crate::loaders::bmp::image::Pixel;
+
+    const RED: u16 = 0xF800;
+    const BLUE: u16 = 0x001F;
+
+    fn dds(width: u32, height: u32, mips: u32, pixel_format: [u32; 8], data: &[u8]) -> Vec<u8> {
+        let mut header = [0u32; 31];
+        header[0] = 124;
+        header[1] = 0x1007 | if mips > 0 { 0x2_0000 } else { 0 };
+        header[2] = height;
+        header[3] = width;
+        header[6] = mips;
+        header[18..26].copy_from_slice(&pixel_format);
+        let mut bytes = b"DDS ".to_vec();
+        for value in header {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        bytes.extend_from_slice(data);
+        bytes
+    }
+
+    fn four_cc(code: &[u8; 4]) -> [u32; 8] {
+        [32, 0x4, u32::from_le_bytes(*code), 0, 0, 0, 0, 0]
+    }
+
+    fn color_block(color0: u16, color1: u16, indices: u32) -> Vec<u8> {
+        [
+            &color0.to_le_bytes()[..],
+            &color1.to_le_bytes(),
+            &indices.to_le_bytes(),
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn decodes_bc1_four_and_three_color_blocks() {
+        // Texels 0..3 use indices 0, 1, 2, 3; the rest index 0
+        let opaque = color_block(RED, BLUE, 0b11_10_01_00);
+        let levels = decode(&dds(4, 4, 0, four_cc(b"DXT1"), &opaque)).expect("BC1 decodes");
+        assert_eq!(levels.len(), 1);
+        let image = &levels[0];
+        assert_eq!(image.get_pixel(0, 0), Pixel::new(255, 0, 0));
+        assert_eq!(image.get_pixel(1, 0), Pixel::new(0, 0, 255));
+        assert_eq!(image.get_pixel(2, 0), Pixel::new(170, 0, 85));
+        assert_eq!(image.get_pixel(3, 0), Pixel::new(85, 0, 170));
+        assert_eq!(image.get_pixel(3, 3), Pixel::new(255, 0, 0));
+        assert!(!image.has_alpha());
+
+        let punch_through = color_block(BLUE, RED, 0b11_10);
+        let levels = decode(&dds(4, 4, 0, four_cc(b"DXT1"), &punch_through)).expect("decodes");
+        assert_eq!(levels[0].get_pixel(0, 0), Pixel::new(127, 0, 127));
+        assert_eq!(levels[0].get_pixel(1, 0), Pixel::with_alpha(0, 0, 0, 0));
+    }
+
+    #[test]
+    fn decodes_bc2_and_bc3_alpha() {
+        // BC2: alpha nibbles 0, 1, ..., 15
+        let alphas: u64 = (0..16).map(|i| (i as u64) << (i * 4)).sum();
+        let block = [&alphas.to_le_bytes()[..], &color_block(BLUE, RED, 0)].concat();
+        let image = &decode(&dds(4, 4, 0, four_cc(b"DXT3"), &block)).expect("BC2")[0];
+        assert_eq!(image.get_pixel(0, 0), Pixel::with_alpha(0, 0, 255, 0));
+        assert_eq!(image.get_pixel(1, 0).a, 17);
+        assert_eq!(image.get_pixel(3, 3).a, 255);
+
+        // BC3: texel i uses alpha index i % 8
+        let indices: u64 = (0..16).map(|i| ((i % 8) as u64) << (i * 3)).sum();
+        let alpha_block = |a0: u8, a1: u8| {
+            [
+                &[a0, a1][..],
+                &indices.to_le_bytes()[..6],
+                &color_block(RED, BLUE, 0),
+            ]
+            .concat()
+        };
+        let eight = &decode(&dds(4, 4, 0, four_cc(b"DXT5"), &alpha_block(210, 0))).expect("BC3")[0];
+        let expected = [210, 0, 180, 150, 120, 90, 60, 30];
+        for (x, alpha) in expected.into_iter().enumerate() {
+            assert_eq!(eight.get_pixel(x as u32 % 4, x as u32 / 4).a, alpha);
+        }
+        let six = &decode(&dds(4, 4, 0, four_cc(b"DXT5"), &alpha_block(0, 100))).expect("BC3")[0];
+        let expected = [0, 100, 20, 40, 60, 80, 0, 255];
+        for (x, alpha) in expected.into_iter().enumerate() {
+            assert_eq!(six.get_pixel(x as u32 % 4, x as u32 / 4).a, alpha);
+        }
+        assert_eq!(six.get_pixel(0, 0), Pixel::with_alpha(255, 0, 0, 0));
+    }
+
+    #[test]
+    fn decodes_the_stored_mip_chain() {
+        // 8x4 -> 4x2 -> 2x1 -> 1x1, each level a solid color
+        let colors = [RED, BLUE, 0x07E0, 0xFFFF];
+        let blocks_per_level = [2, 1, 1, 1];
+        let mut data = Vec::new();
+        for (color, blocks) in colors.into_iter().zip(blocks_per_level) {
+            for _ in 0..blocks {
+                data.extend_from_slice(&color_block(color, 0, 0));
+            }
+        }
+        let levels = decode(&dds(8, 4, 4, four_cc(b"DXT1"), &data)).expect("mip chain decodes");
+        let sizes: Vec<_> = levels
+            .iter()
+            .map(|level| (level.get_width(), level.get_height()))
+            .collect();
+        assert_eq!(sizes, [(8, 4), (4, 2), (2, 1), (1, 1)]);
+        assert_eq!(levels[0].get_pixel(7, 3), Pixel::new(255, 0, 0));
+        assert_eq!(levels[2].get_pixel(1, 0), Pixel::new(0, 255, 0));
+        assert_eq!(levels[3].get_pixel(0, 0), Pixel::new(255, 255, 255));
+
+        assert!(decode(&dds(8, 4, 4, four_cc(b"DXT1"), &data[..data.len() - 1])).is_err());
+    }
+
+    #[test]
+    fn decodes_uncompressed_and_dx10_textures() {
+        let bgra = [
+            32,
+            0x41,
+            0,
+            32,
+            0x00FF_0000,
+            0x0000_FF00,
+            0x0000_00FF,
+            0xFF00_0000,
+        ];
+        let data = [0x10, 0x20, 0x30, 0x80, 1, 2, 3, 255];
+        let image = &decode(&dds(2, 1, 0, bgra, &data)).expect("BGRA decodes")[0];
+        assert_eq!(
+            image.get_pixel(0, 0),
+            Pixel::with_alpha(0x30, 0x20, 0x10, 0x80)
+        );
+        assert_eq!(image.get_pixel(1, 0), Pixel::new(3, 2, 1));
+
+        let mut dx10 = dds(4, 4, 0, four_cc(b"DX10"), &[]);
+        for value in [71u32, 3, 0, 1, 0] {
+            dx10.extend_from_slice(&value.to_le_bytes());
+        }
+        dx10.extend_from_slice(&color_block(RED, BLUE, 0));
+        let image = &decode(&dx10).expect("DX10 BC1 decodes")[0];
+        assert_eq!(image.get_pixel(2, 2), Pixel::new(255, 0, 0));
+    }
+
+    #[test]
+    fn rejects_unsupported_textures() {
+        let block = color_block(RED, BLUE, 0);
+        assert!(decode(&block).is_err());
+        assert!(decode(&dds(4, 4, 0, four_cc(b"ATI2"), &block)).is_err());
+
+        let mut cube = dds(4, 4, 0, four_cc(b"DXT1"), &block);
+        cube[4 + 27 * 4..4 + 28 * 4].copy_from_slice(&0x200u32.to_le_bytes());
+        assert!(decode(&cube).is_err());
+
+        let luminance = [32, 0x2_0000, 0, 8, 0xFF, 0, 0, 0];
+        assert!(decode(&dds(4, 4, 0, luminance, &[0; 16])).is_err());
+    }
+}
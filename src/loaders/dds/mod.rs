@@ -0,0 +1,28 @@
+mod decoder;
+
+use std::path::Path;
+
+use crate::loaders::registry::{TextureData, TextureDecoder};
+
+pub use decoder::{decode, from_reader, open};
+
+/// Registry entry for DDS textures.
+pub struct DdsDecoder;
+
+impl TextureDecoder for DdsDecoder {
+    fn name(&self) -> &'static str {
+        "DDS"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["dds"]
+    }
+
+    fn matches_magic(&self, header: &[u8]) -> bool {
+        header.starts_with(b"DDS ")
+    }
+
+    fn decode(&self, path: &Path) -> Result<TextureData, String> {
+        open(path).map(TextureData::Levels)
+    }
+}
@@ -0,0 +1,57 @@
+/// Decodes standard (RFC 4648) base64, ignoring ASCII whitespace and accepting missing padding.
+pub fn decode(text: &str) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::with_capacity(text.len() / 4 * 3);
+    let mut accumulator = 0u32;
+    let mut bits = 0;
+    let mut padding = 0;
+
+    for byte in text.bytes() {
+        let value = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            b'=' => {
+                padding += 1;
+                continue;
+            }
+            b' ' | b'\t' | b'\n' | b'\r' => continue,
+            _ => return Err(format!("Invalid base64 character '{}'", byte as char)),
+        };
+        if padding > 0 {
+            return Err("Base64 data continues after '=' padding".to_string());
+        }
+        accumulator = accumulator << 6 | u32::from(value);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((accumulator >> bits) as u8);
+        }
+    }
+
+    if bits >= 6 || padding > 2 {
+        return Err("Truncated base64 data".to_string());
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::decode;
+
+    #[test]
+    fn decodes_padded_and_unpadded_input() {
+        assert_eq!(decode("").unwrap(), b"");
+        assert_eq!(decode("Zg==").unwrap(), b"f");
+        assert_eq!(decode("Zm8=").unwrap(), b"fo");
+        assert_eq!(decode("Zm9v").unwrap(), b"foo");
+        assert_eq!(decode("Zm9vYg").unwrap(), b"foob");
+        assert_eq!(decode("Zm9v\nYmFy").unwrap(), b"foobar");
+        assert_eq!(decode("AP8+/w==").unwrap(), [0x00, 0xFF, 0x3E, 0xFF]);
+
+        assert!(decode("Zm9v!").is_err());
+        assert!(decode("Zg==Zg==").is_err());
+        assert!(decode("Z").is_err());
+    }
+}
@@ -0,0 +1,31 @@
+mod base64;
+mod json;
+mod parse_gltf;
+
+use std::path::Path;
+
+use crate::loaders::obj::ObjSceneData;
+use crate::loaders::registry::ModelLoader;
+
+pub use parse_gltf::{load, parse};
+
+/// Registry entry for glTF 2.0 models, both `.gltf` and binary `.glb`.
+pub struct GltfLoader;
+
+impl ModelLoader for GltfLoader {
+    fn name(&self) -> &'static str {
+        "glTF"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["glb", "gltf"]
+    }
+
+    fn matches_magic(&self, header: &[u8]) -> bool {
+        header.starts_with(b"glTF")
+    }
+
+    fn load(&self, path: &Path) -> Result<ObjSceneData, String> {
+        load(path)
+    }
+}
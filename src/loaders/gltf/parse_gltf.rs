@@ -0,0 +1,941 @@
+use std::fs;
+use std::path::Path;
+
+use super::base64;
+use super::json::{self, JsonValue};
+use crate::loaders::is_texture_path;
+use crate::loaders::obj::{
+    ObjMaterialData, ObjMeshData, ObjObjectData, ObjSceneData, ObjTextureMap,
+    generate_vertex_normals,
+};
+use crate::math::{Matrix4, Vector3, Vector4};
+
+const GLB_MAGIC: &[u8; 4] = b"glTF";
+const GLB_HEADER_SIZE: usize = 12;
+const GLB_CHUNK_JSON: u32 = 0x4E4F_534A;
+const GLB_CHUNK_BIN: u32 = 0x004E_4942;
+
+const MODE_TRIANGLES: usize = 4;
+const MODE_TRIANGLE_STRIP: usize = 5;
+const MODE_TRIANGLE_FAN: usize = 6;
+const WRAP_CLAMP_TO_EDGE: usize = 33071;
+/// Largest accessor without a `bufferView` that is zero-filled (64 MiB of `f32`s).
+const MAX_ZERO_ACCESSOR_VALUES: usize = 1 << 24;
+
+/// Loads a `.gltf` (with external or embedded buffers) or `.glb` file into the same scene
+/// data the OBJ loader produces. Every node that references a mesh becomes an object with
+/// one mesh per triangle primitive, transformed into world space.
+pub fn load(path: &Path) -> Result<ObjSceneData, String> {
+    let bytes = fs::read(path)
+        .map_err(|e| format!("Failed to open glTF file '{}': {}", path.display(), e))?;
+    let base_dir = path.parent().unwrap_or_else(|| Path::new(""));
+    parse(&bytes, base_dir).map_err(|e| format!("{} (while loading {})", e, path.display()))
+}
+
+/// Parses glTF JSON or a GLB container; relative buffer URIs are resolved against `base_dir`.
+pub fn parse(bytes: &[u8], base_dir: &Path) -> Result<ObjSceneData, String> {
+    let (json_text, binary_chunk) = if bytes.starts_with(GLB_MAGIC) {
+        split_glb(bytes)?
+    } else {
+        let text =
+            std::str::from_utf8(bytes).map_err(|_| "glTF JSON is not valid UTF-8".to_string())?;
+        (text, None)
+    };
+    let document = json::parse(json_text)?;
+
+    let version = document
+        .get("asset")
+        .and_then(|asset| asset.get("version"))
+        .and_then(JsonValue::as_str)
+        .ok_or_else(|| "glTF asset.version is missing".to_string())?;
+    if !version.starts_with("2.") {
+        return Err(format!("Unsupported glTF version {}", version));
+    }
+
+    let buffers = load_buffers(&document, binary_chunk, base_dir)?;
+    let gltf = Gltf {
+        document: &document,
+        buffers,
+    };
+
+    let materials = array(&document, "materials")
+        .iter()
+        .map(|material| gltf.material(material))
+        .collect();
+
+    let mut objects = Vec::new();
+    for root in gltf.root_nodes()? {
+        gltf.visit_node(root, Matrix4::identity(), 0, &mut objects)?;
+    }
+    if objects.is_empty() {
+        return Err("glTF file contains no triangle meshes".to_string());
+    }
+
+    Ok(ObjSceneData { objects, materials })
+}
+
+/// Splits a GLB container into its JSON chunk and optional binary chunk.
+fn split_glb(bytes: &[u8]) -> Result<(&str, Option<&[u8]>), String> {
+    let word = |offset: usize| -> Option<u32> {
+        let bytes = bytes.get(offset..offset + 4)?;
+        Some(u32::from_le_bytes(bytes.try_into().ok()?))
+    };
+    let version = word(4).ok_or_else(|| "GLB header is truncated".to_string())?;
+    if version != 2 {
+        return Err(format!("Unsupported GLB container version {}", version));
+    }
+
+    let mut chunks = Vec::new();
+    let mut offset = GLB_HEADER_SIZE;
+    while offset < bytes.len() {
+        let (Some(length), Some(kind)) = (word(offset), word(offset + 4)) else {
+            return Err("GLB chunk header is truncated".to_string());
+        };
+        let data = bytes
+            .get(offset + 8..offset + 8 + length as usize)
+            .ok_or_else(|| "GLB chunk extends past the end of the file".to_string())?;
+        chunks.push((kind, data));
+        offset += 8 + length as usize;
+    }
+
+    let json = match chunks.first() {
+        Some(&(GLB_CHUNK_JSON, data)) => std::str::from_utf8(data)
+            .map_err(|_| "GLB JSON chunk is not valid UTF-8".to_string())?,
+        _ => return Err("GLB file does not start with a JSON chunk".to_string()),
+    };
+    let binary = chunks
+        .get(1)
+        .filter(|(kind, _)| *kind == GLB_CHUNK_BIN)
+        .map(|&(_, data)| data);
+    Ok((json, binary))
+}
+
+fn load_buffers(
+    document: &JsonValue,
+    binary_chunk: Option<&[u8]>,
+    base_dir: &Path,
+) -> Result<Vec<Vec<u8>>, String> {
+    array(document, "buffers")
+        .iter()
+        .enumerate()
+        .map(|(index, buffer)| {
+            let data = match buffer.get("uri").and_then(JsonValue::as_str) {
+                Some(uri) if uri.starts_with("data:") => {
+                    let (_, encoded) = uri.split_once(";base64,").ok_or_else(|| {
+                        format!("glTF buffer {} has a data URI that is not base64", index)
+                    })?;
+                    base64::decode(encoded)?
+                }
+                Some(uri) => {
+                    let path = base_dir.join(percent_decode(uri));
+                    fs::read(&path).map_err(|e| {
+                        format!("Failed to read glTF buffer '{}': {}", path.display(), e)
+                    })?
+                }
+                None if index == 0 => binary_chunk
+                    .ok_or_else(|| "glTF buffer 0 has no URI and no GLB binary chunk".to_string())?
+                    .to_vec(),
+                None => return Err(format!("glTF buffer {} has no URI", index)),
+            };
+            let byte_length = required_index(buffer, "byteLength", "buffer")?;
+            if data.len() < byte_length {
+                return Err(format!(
+                    "glTF buffer {} holds {} bytes but declares {}",
+                    index,
+                    data.len(),
+                    byte_length
+                ));
+            }
+            Ok(data)
+        })
+        .collect()
+}
+
+/// Decodes `%XX` escapes in a relative URI.
+fn percent_decode(uri: &str) -> String {
+    let bytes = uri.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| bytes.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn array<'a>(value: &'a JsonValue, key: &str) -> &'a [JsonValue] {
+    value
+        .get(key)
+        .and_then(JsonValue::as_array)
+        .unwrap_or_default()
+}
+
+fn index(value: &JsonValue, key: &str) -> Option<usize> {
+    value.get(key).and_then(JsonValue::as_usize)
+}
+
+fn required_index(value: &JsonValue, key: &str, owner: &str) -> Result<usize, String> {
+    index(value, key).ok_or_else(|| format!("glTF {} is missing '{}'", owner, key))
+}
+
+fn numbers<const N: usize>(value: &JsonValue, key: &str, default: [f32; N]) -> [f32; N] {
+    let Some(values) = value.get(key).and_then(JsonValue::as_array) else {
+        return default;
+    };
+    let mut result = default;
+    for (slot, value) in result.iter_mut().zip(values) {
+        if let Some(number) = value.as_f64() {
+            *slot = number as f32;
+        }
+    }
+    result
+}
+
+struct Gltf<'a> {
+    document: &'a JsonValue,
+    buffers: Vec<Vec<u8>>,
+}
+
+impl Gltf<'_> {
+    fn element(&self, collection: &str, index: usize) -> Result<&JsonValue, String> {
+        array(self.document, collection).get(index).ok_or_else(|| {
+            format!(
+                "glTF references {} {} but only {} exist",
+                collection.trim_end_matches('s'),
+                index,
+                array(self.document, collection).len()
+            )
+        })
+    }
+
+    /// Nodes of the default scene, or every parentless node when there are no scenes.
+    fn root_nodes(&self) -> Result<Vec<usize>, String> {
+        let scenes = array(self.document, "scenes");
+        if !scenes.is_empty() {
+            let scene = index(self.document, "scene").unwrap_or(0);
+            let scene = self.element("scenes", scene)?;
+            return Ok(array(scene, "nodes")
+                .iter()
+                .filter_map(JsonValue::as_usize)
+                .collect());
+        }
+
+        let nodes = array(self.document, "nodes");
+        let mut is_child = vec![false; nodes.len()];
+        for node in nodes {
+            for child in array(node, "children")
+                .iter()
+                .filter_map(JsonValue::as_usize)
+            {
+                if let Some(flag) = is_child.get_mut(child) {
+                    *flag = true;
+                }
+            }
+        }
+        Ok((0..nodes.len()).filter(|&node| !is_child[node]).collect())
+    }
+
+    fn visit_node(
+        &self,
+        node_index: usize,
+        parent: Matrix4,
+        depth: usize,
+        objects: &mut Vec<ObjObjectData>,
+    ) -> Result<(), String> {
+        if depth > array(self.document, "nodes").len() {
+            return Err("glTF node hierarchy contains a cycle".to_string());
+        }
+        let node = self.element("nodes", node_index)?;
+        let world = parent * local_transform(node);
+
+        if let Some(mesh_index) = index(node, "mesh") {
+            let mesh = self.element("meshes", mesh_index)?;
+            let mut meshes = Vec::new();
+            for primitive in array(mesh, "primitives") {
+                if let Some(mesh) = self.primitive(primitive, &world)? {
+                    meshes.push(mesh);
+                }
+            }
+            if !meshes.is_empty() {
+                let name = node
+                    .get("name")
+                    .or_else(|| mesh.get("name"))
+                    .and_then(JsonValue::as_str)
+                    .unwrap_or_default();
+                objects.push(ObjObjectData {
+                    name: name.to_string(),
+                    groups: Vec::new(),
+                    meshes,
+                });
+            }
+        }
+
+        for child in array(node, "children")
+            .iter()
+            .filter_map(JsonValue::as_usize)
+        {
+            self.visit_node(child, world, depth + 1, objects)?;
+        }
+        Ok(())
+    }
+
+    /// Converts a triangle primitive into a mesh in world space; points and lines yield `None`.
+    fn primitive(
+        &self,
+        primitive: &JsonValue,
+        world: &Matrix4,
+    ) -> Result<Option<ObjMeshData>, String> {
+        let mode = index(primitive, "mode").unwrap_or(MODE_TRIANGLES);
+        if !matches!(
+            mode,
+            MODE_TRIANGLES | MODE_TRIANGLE_STRIP | MODE_TRIANGLE_FAN
+        ) {
+            return Ok(None);
+        }
+
+        let attributes = primitive
+            .get("attributes")
+            .ok_or_else(|| "glTF primitive has no attributes".to_string())?;
+        let position_accessor = required_index(attributes, "POSITION", "primitive")?;
+        let positions: Vec<[f32; 3]> = self
+            .accessor(position_accessor, &[3])?
+            .chunks_exact(3)
+            .map(|p| transform_point(world, [p[0] as f32, p[1] as f32, p[2] as f32]))
+            .collect();
+
+        let vertex_indices: Vec<usize> = match index(primitive, "indices") {
+            Some(accessor) => self
+                .accessor(accessor, &[1])?
+                .into_iter()
+                .map(|index| index as usize)
+                .collect(),
+            None => (0..positions.len()).collect(),
+        };
+        if let Some(&index) = vertex_indices.iter().find(|&&i| i >= positions.len()) {
+            return Err(format!(
+                "glTF primitive index {} is out of range for {} vertices",
+                index,
+                positions.len()
+            ));
+        }
+
+        // A mirroring transform turns counter-clockwise triangles clockwise; swap them back
+        let flip = determinant(world) < 0.0;
+        let mut triangles = assemble_triangles(&vertex_indices, mode);
+        if flip {
+            for triangle in &mut triangles {
+                triangle.swap(1, 2);
+            }
+        }
+
+        let normals = match index(attributes, "NORMAL") {
+            Some(accessor) => self
+                .accessor(accessor, &[3])?
+                .chunks_exact(3)
+                .map(|n| transform_normal(world, [n[0] as f32, n[1] as f32, n[2] as f32]))
+                .collect(),
+            None => generate_vertex_normals(&positions, &triangles),
+        };
+        let texcoords: Vec<f32> = match index(attributes, "TEXCOORD_0") {
+            // glTF puts the UV origin at the top-left; OBJ and the renderer expect bottom-left
+            Some(accessor) => self
+                .accessor(accessor, &[2])?
+                .chunks_exact(2)
+                .flat_map(|uv| [uv[0] as f32, 1.0 - uv[1] as f32])
+                .collect(),
+            None => Vec::new(),
+        };
+        let colors: Vec<f32> = match index(attributes, "COLOR_0") {
+            Some(accessor) => {
+                let components = self.accessor_components(accessor)?;
+                self.accessor(accessor, &[3, 4])?
+                    .chunks_exact(components)
+                    .flat_map(|color| [color[0] as f32, color[1] as f32, color[2] as f32])
+                    .collect()
+            }
+            None => Vec::new(),
+        };
+        for (name, length) in [
+            ("NORMAL", normals.len()),
+            ("TEXCOORD_0", texcoords.len() / 2),
+            ("COLOR_0", colors.len() / 3),
+        ] {
+            if length != 0 && length != positions.len() {
+                return Err(format!(
+                    "glTF {} has {} entries but POSITION has {}",
+                    name,
+                    length,
+                    positions.len()
+                ));
+            }
+        }
+
+        let indices = triangles
+            .iter()
+            .flatten()
+            .map(|&index| {
+                u32::try_from(index).map_err(|_| {
+                    "glTF mesh exceeds the maximum number of vertices addressable by u32 indices"
+                        .to_string()
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Some(ObjMeshData {
+            positions: positions.into_iter().flatten().collect(),
+            normals: normals.into_iter().flatten().collect(),
+            texcoords,
+            colors,
+            indices,
+            material_id: index(primitive, "material"),
+        }))
+    }
+
+    fn accessor_components(&self, accessor_index: usize) -> Result<usize, String> {
+        let accessor = self.element("accessors", accessor_index)?;
+        match accessor.get("type").and_then(JsonValue::as_str) {
+            Some("SCALAR") => Ok(1),
+            Some("VEC2") => Ok(2),
+            Some("VEC3") => Ok(3),
+            Some("VEC4") | Some("MAT2") => Ok(4),
+            Some("MAT3") => Ok(9),
+            Some("MAT4") => Ok(16),
+            other => Err(format!(
+                "glTF accessor {} has invalid type {:?}",
+                accessor_index, other
+            )),
+        }
+    }
+
+    /// Reads an accessor as flat `f64` components, checking its width against `allowed`.
+    /// Normalized integers are mapped to `[0, 1]` / `[-1, 1]`.
+    fn accessor(&self, accessor_index: usize, allowed: &[usize]) -> Result<Vec<f64>, String> {
+        let accessor = self.element("accessors", accessor_index)?;
+        let components = self.accessor_components(accessor_index)?;
+        if !allowed.contains(&components) {
+            return Err(format!(
+                "glTF accessor {} has {} components, expected {:?}",
+                accessor_index, components, allowed
+            ));
+        }
+        if accessor.get("sparse").is_some() {
+            return Err(format!(
+                "glTF accessor {} is sparse, which is not supported",
+                accessor_index
+            ));
+        }
+
+        let count = required_index(accessor, "count", "accessor")?;
+        let component_type = required_index(accessor, "componentType", "accessor")?;
+        let normalized = accessor
+            .get("normalized")
+            .and_then(JsonValue::as_bool)
+            .unwrap_or(false);
+        let component_size = match component_type {
+            5120 | 5121 => 1,
+            5122 | 5123 => 2,
+            5125 | 5126 => 4,
+            _ => {
+                return Err(format!(
+                    "glTF accessor {} has invalid componentType {}",
+                    accessor_index, component_type
+                ));
+            }
+        };
+
+        let too_large = || format!("glTF accessor {} is too large", accessor_index);
+        let value_count = count.checked_mul(components).ok_or_else(too_large)?;
+
+        // Accessors without a buffer view are all zeros; nothing in the file bounds their size
+        let Some(view_index) = index(accessor, "bufferView") else {
+            if value_count > MAX_ZERO_ACCESSOR_VALUES {
+                return Err(too_large());
+            }
+            return Ok(vec![0.0; value_count]);
+        };
+        let view = self.element("bufferViews", view_index)?;
+        let buffer = self
+            .buffers
+            .get(required_index(view, "buffer", "bufferView")?)
+            .ok_or_else(|| format!("glTF bufferView {} references a missing buffer", view_index))?;
+        let view_offset = index(view, "byteOffset").unwrap_or(0);
+        let view_length = required_index(view, "byteLength", "bufferView")?;
+        let view_data = view_offset
+            .checked_add(view_length)
+            .and_then(|view_end| buffer.get(view_offset..view_end))
+            .ok_or_else(|| format!("glTF bufferView {} exceeds its buffer", view_index))?;
+
+        let element_size = component_size * components;
+        let stride = index(view, "byteStride").unwrap_or(element_size);
+        if stride < element_size {
+            return Err(format!(
+                "glTF bufferView {} has byteStride {} below the {}-byte elements of accessor {}",
+                view_index, stride, element_size, accessor_index
+            ));
+        }
+        let offset = index(accessor, "byteOffset").unwrap_or(0);
+        // With the stride checked, fitting in the view also bounds `count`
+        let end = match count {
+            0 => Some(0),
+            _ => stride
+                .checked_mul(count - 1)
+                .and_then(|last| last.checked_add(offset))
+                .and_then(|last| last.checked_add(element_size)),
+        };
+        if end.is_none_or(|end| end > view_data.len()) {
+            return Err(format!(
+                "glTF accessor {} exceeds bufferView {}",
+                accessor_index, view_index
+            ));
+        }
+
+        let mut values = Vec::with_capacity(value_count);
+        for element in 0..count {
+            let start = offset + element * stride;
+            for component in 0..components {
+                let at = start + component * component_size;
+                let raw = &view_data[at..at + component_size];
+                values.push(read_component(raw, component_type, normalized));
+            }
+        }
+        Ok(values)
+    }
+
+    fn material(&self, material: &JsonValue) -> ObjMaterialData {
+        let pbr = material.get("pbrMetallicRoughness");
+        let base_color = pbr
+            .map(|pbr| numbers(pbr, "baseColorFactor", [1.0; 4]))
+            .unwrap_or([1.0; 4]);
+        ObjMaterialData {
+            name: material
+                .get("name")
+                .and_then(JsonValue::as_str)
+                .unwrap_or_default()
+                .to_string(),
+            diffuse_color: Some([base_color[0], base_color[1], base_color[2]]),
+            dissolve: Some(base_color[3]),
+            diffuse_texture: pbr
+                .and_then(|pbr| pbr.get("baseColorTexture"))
+                .and_then(|info| self.texture_map(info)),
+            normal_texture: material
+                .get("normalTexture")
+                .and_then(|info| self.texture_map(info)),
+            ..Default::default()
+        }
+    }
+
+    /// Resolves a texture info to an image file the renderer can decode. Images embedded in a
+    /// buffer view (the usual GLB layout) or a `data:` URI, and files without a decoder, are
+    /// reported on stderr and skipped, leaving the material's base color.
+    fn texture_map(&self, info: &JsonValue) -> Option<ObjTextureMap> {
+        let texture = self.element("textures", index(info, "index")?).ok()?;
+        let image_index = index(texture, "source")?;
+        let image = self.element("images", image_index).ok()?;
+        let uri = image
+            .get("uri")
+            .and_then(JsonValue::as_str)
+            .filter(|uri| !uri.starts_with("data:"));
+        let Some(uri) = uri else {
+            eprintln!(
+                "Warning: glTF image {} is embedded in the file, which is not supported; its material uses the base color",
+                image_index
+            );
+            return None;
+        };
+        let path = percent_decode(uri);
+        if !is_texture_path(&path) {
+            eprintln!(
+                "Warning: glTF image '{}' has no registered decoder; its material uses the base color",
+                path
+            );
+            return None;
+        }
+        let clamp = index(texture, "sampler")
+            .and_then(|sampler| self.element("samplers", sampler).ok())
+            .and_then(|sampler| index(sampler, "wrapS"))
+            == Some(WRAP_CLAMP_TO_EDGE);
+        Some(ObjTextureMap {
+            path,
+            clamp,
+            ..Default::default()
+        })
+    }
+}
+
+fn read_component(raw: &[u8], component_type: usize, normalized: bool) -> f64 {
+    match component_type {
+        5120 => {
+            let value = raw[0] as i8 as f64;
+            if normalized {
+                (value / 127.0).max(-1.0)
+            } else {
+                value
+            }
+        }
+        5121 => {
+            let value = raw[0] as f64;
+            if normalized { value / 255.0 } else { value }
+        }
+        5122 => {
+            let value = i16::from_le_bytes([raw[0], raw[1]]) as f64;
+            if normalized {
+                (value / 32767.0).max(-1.0)
+            } else {
+                value
+            }
+        }
+        5123 => {
+            let value = u16::from_le_bytes([raw[0], raw[1]]) as f64;
+            if normalized { value / 65535.0 } else { value }
+        }
+        5125 => u32::from_le_bytes(raw.try_into().expect("4 bytes")) as f64,
+        _ => f32::from_le_bytes(raw.try_into().expect("4 bytes")) as f64,
+    }
+}
+
+fn assemble_triangles(indices: &[usize], mode: usize) -> Vec<[usize; 3]> {
+    match mode {
+        MODE_TRIANGLE_STRIP => (0..indices.len().saturating_sub(2))
+            .map(|i| {
+                // Every other strip triangle is reversed to keep a consistent winding
+                if i % 2 == 0 {
+                    [indices[i], indices[i + 1], indices[i + 2]]
+                } else {
+                    [indices[i + 1], indices[i], indices[i + 2]]
+                }
+            })
+            .collect(),
+        MODE_TRIANGLE_FAN => (1..indices.len().saturating_sub(1))
+            .map(|i| [indices[0], indices[i], indices[i + 1]])
+            .collect(),
+        _ => indices
+            .chunks_exact(3)
+            .map(|triangle| [triangle[0], triangle[1], triangle[2]])
+            .collect(),
+    }
+}
+
+/// `matrix` when present, otherwise `translation * rotation * scale`.
+fn local_transform(node: &JsonValue) -> Matrix4 {
+    if node.get("matrix").is_some() {
+        let m = numbers(
+            node,
+            "matrix",
+            [
+                1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0,
+            ],
+        );
+        return Matrix4::from_cols(
+            Vector4::new(m[0], m[1], m[2], m[3]),
+            Vector4::new(m[4], m[5], m[6], m[7]),
+            Vector4::new(m[8], m[9], m[10], m[11]),
+            Vector4::new(m[12], m[13], m[14], m[15]),
+        );
+    }
+
+    let [tx, ty, tz] = numbers(node, "translation", [0.0; 3]);
+    let [x, y, z, w] = numbers(node, "rotation", [0.0, 0.0, 0.0, 1.0]);
+    let [sx, sy, sz] = numbers(node, "scale", [1.0; 3]);
+    Matrix4::from_cols(
+        Vector4::new(
+            1.0 - 2.0 * (y * y + z * z),
+            2.0 * (x * y + w * z),
+            2.0 * (x * z - w * y),
+            0.0,
+        ) * sx,
+        Vector4::new(
+            2.0 * (x * y - w * z),
+            1.0 - 2.0 * (x * x + z * z),
+            2.0 * (y * z + w * x),
+            0.0,
+        ) * sy,
+        Vector4::new(
+            2.0 * (x * z + w * y),
+            2.0 * (y * z - w * x),
+            1.0 - 2.0 * (x * x + y * y),
+            0.0,
+        ) * sz,
+        Vector4::new(tx, ty, tz, 1.0),
+    )
+}
+
+fn column3(column: Vector4) -> Vector3 {
+    Vector3::new(column.x, column.y, column.z)
+}
+
+fn transform_point(m: &Matrix4, [x, y, z]: [f32; 3]) -> [f32; 3] {
+    let p = m.x * x + m.y * y + m.z * z + m.w;
+    [p.x, p.y, p.z]
+}
+
+fn determinant(m: &Matrix4) -> f32 {
+    column3(m.x).dot(column3(m.y).cross(column3(m.z)))
+}
+
+/// Transforms a normal by the inverse transpose of the upper 3x3, built from cofactors.
+fn transform_normal(m: &Matrix4, [x, y, z]: [f32; 3]) -> [f32; 3] {
+    let (a, b, c) = (column3(m.x), column3(m.y), column3(m.z));
+    let n = b.cross(c) * x + c.cross(a) * y + a.cross(b) * z;
+    let n = if determinant(m) < 0.0 { -n } else { n };
+    let length = n.magnitude();
+    if length <= f32::EPSILON {
+        return [0.0; 3];
+    }
+    [n.x / length, n.y / length, n.z / length]
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::parse;
+
+    fn base64(bytes: &[u8]) -> String {
+        const ALPHABET: &[u8; 64] =
+            b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+        let mut text = String::new();
+        for chunk in bytes.chunks(3) {
+            let word = chunk.iter().enumerate().fold(0u32, |word, (i, &byte)| {
+                word | u32::from(byte) << (16 - 8 * i)
+            });
+            for i in 0..4 {
+                if i <= chunk.len() {
+                    text.push(ALPHABET[(word >> (18 - 6 * i) & 0x3F) as usize] as char);
+                } else {
+                    text.push('=');
+                }
+            }
+        }
+        text
+    }
+
+    fn floats(values: &[f32]) -> Vec<u8> {
+        values
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect()
+    }
+
+    /// A unit right triangle: positions, UVs and u16 indices in one buffer.
+    fn triangle_buffer() -> Vec<u8> {
+        let mut buffer = floats(&[0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0]);
+        buffer.extend(floats(&[0.0, 0.0, 1.0, 0.0, 0.0, 1.0]));
+        buffer.extend([0u16, 1, 2].iter().flat_map(|index| index.to_le_bytes()));
+        buffer
+    }
+
+    fn triangle_gltf(buffer_uri: &str, node: &str) -> String {
+        format!(
+            r#"{{
+  "asset": {{"version": "2.0"}},
+  "scene": 0,
+  "scenes": [{{"nodes": [0]}}],
+  "nodes": [{node}],
+  "meshes": [{{"name": "Tri", "primitives": [{{
+    "attributes": {{"POSITION": 0, "TEXCOORD_0": 1}}, "indices": 2, "material": 0
+  }}]}}],
+  "materials": [{{"name": "Painted", "pbrMetallicRoughness": {{
+    "baseColorFactor": [0.5, 0.25, 1.0, 0.75], "baseColorTexture": {{"index": 0}}
+  }}}}],
+  "textures": [{{"source": 0, "sampler": 0}}],
+  "images": [{{"uri": "albedo%20map.png"}}],
+  "samplers": [{{"wrapS": 33071, "wrapT": 33071}}],
+  "buffers": [{{"uri": "{buffer_uri}", "byteLength": 66}}],
+  "bufferViews": [
+    {{"buffer": 0, "byteOffset": 0, "byteLength": 36}},
+    {{"buffer": 0, "byteOffset": 36, "byteLength": 24}},
+    {{"buffer": 0, "byteOffset": 60, "byteLength": 6}}
+  ],
+  "accessors": [
+    {{"bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3"}},
+    {{"bufferView": 1, "componentType": 5126, "count": 3, "type": "VEC2"}},
+    {{"bufferView": 2, "componentType": 5123, "count": 3, "type": "SCALAR"}}
+  ]
+}}"#
+        )
+    }
+
+    fn embedded_uri() -> String {
+        format!(
+            "data:application/octet-stream;base64,{}",
+            base64(&triangle_buffer())
+        )
+    }
+
+    #[test]
+    fn reads_embedded_buffers_materials_and_node_transforms() {
+        let text = triangle_gltf(
+            &embedded_uri(),
+            r#"{"mesh": 0, "translation": [10, 0, 0], "scale": [2, 2, 2]}"#,
+        );
+        let scene = parse(text.as_bytes(), Path::new("")).expect("glTF should parse");
+
+        assert_eq!(scene.objects.len(), 1);
+        assert_eq!(scene.objects[0].name, "Tri");
+        let mesh = &scene.objects[0].meshes[0];
+        assert_eq!(
+            mesh.positions,
+            vec![10.0, 0.0, 0.0, 12.0, 0.0, 0.0, 10.0, 2.0, 0.0]
+        );
+        assert_eq!(mesh.indices, vec![0, 1, 2]);
+        // V is flipped into the bottom-left UV convention
+        assert_eq!(mesh.texcoords, vec![0.0, 1.0, 1.0, 1.0, 0.0, 0.0]);
+        // Missing normals are generated from the triangle
+        assert_eq!(mesh.normals[..3], [0.0, 0.0, 1.0]);
+        assert_eq!(mesh.material_id, Some(0));
+
+        let material = &scene.materials[0];
+        assert_eq!(material.name, "Painted");
+        assert_eq!(material.diffuse_color, Some([0.5, 0.25, 1.0]));
+        assert_eq!(material.dissolve, Some(0.75));
+        let texture = material
+            .diffuse_texture
+            .as_ref()
+            .expect("base color texture");
+        assert_eq!(texture.path, "albedo map.png");
+        assert!(texture.clamp);
+    }
+
+    #[test]
+    fn mirrored_nodes_keep_counter_clockwise_winding() {
+        let text = triangle_gltf(&embedded_uri(), r#"{"mesh": 0, "scale": [-1, 1, 1]}"#);
+        let scene = parse(text.as_bytes(), Path::new("")).expect("glTF should parse");
+        let mesh = &scene.objects[0].meshes[0];
+        assert_eq!(mesh.indices, vec![0, 2, 1]);
+        assert_eq!(mesh.normals[..3], [0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn reads_glb_containers_with_hierarchies_and_strips() {
+        // A quad as a 4-vertex triangle strip with normals and normalized u8 colors
+        let mut buffer = floats(&[0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 0.0]);
+        buffer.extend(floats(&[0.0, 0.0, 1.0].repeat(4)));
+        buffer.extend([
+            255u8, 0, 0, 255, 0, 255, 0, 255, 0, 0, 255, 255, 255, 255, 255, 255,
+        ]);
+        let json = r#"{
+  "asset": {"version": "2.0"},
+  "nodes": [
+    {"name": "Parent", "rotation": [0, 0, 0.7071068, 0.7071068], "children": [1]},
+    {"name": "Child", "mesh": 0, "translation": [5, 0, 0]}
+  ],
+  "meshes": [{"primitives": [
+    {"attributes": {"POSITION": 0, "NORMAL": 1, "COLOR_0": 2}, "mode": 5},
+    {"attributes": {"POSITION": 0}, "mode": 1}
+  ]}],
+  "buffers": [{"byteLength": 112}],
+  "bufferViews": [{"buffer": 0, "byteLength": 112}],
+  "accessors": [
+    {"bufferView": 0, "componentType": 5126, "count": 4, "type": "VEC3"},
+    {"bufferView": 0, "byteOffset": 48, "componentType": 5126, "count": 4, "type": "VEC3"},
+    {"bufferView": 0, "byteOffset": 96, "componentType": 5121, "normalized": true,
+     "count": 4, "type": "VEC4"}
+  ]
+}"#;
+        let mut json = json.as_bytes().to_vec();
+        json.resize(json.len().div_ceil(4) * 4, b' ');
+
+        let mut glb = b"glTF".to_vec();
+        glb.extend(2u32.to_le_bytes());
+        glb.extend(((12 + 8 + json.len() + 8 + buffer.len()) as u32).to_le_bytes());
+        glb.extend((json.len() as u32).to_le_bytes());
+        glb.extend(0x4E4F_534Au32.to_le_bytes());
+        glb.extend(&json);
+        glb.extend((buffer.len() as u32).to_le_bytes());
+        glb.extend(0x004E_4942u32.to_le_bytes());
+        glb.extend(&buffer);
+
+        let scene = parse(&glb, Path::new("")).expect("GLB should parse");
+        assert_eq!(scene.objects.len(), 1);
+        assert_eq!(scene.objects[0].name, "Child");
+        // The line primitive is skipped
+        assert_eq!(scene.objects[0].meshes.len(), 1);
+
+        let mesh = &scene.objects[0].meshes[0];
+        assert_eq!(mesh.indices, vec![0, 1, 2, 2, 1, 3]);
+        // Child translation (5, 0, 0) rotated 90 degrees about Z by the parent
+        let first: Vec<f32> = mesh.positions[..3].iter().map(|v| v.round()).collect();
+        assert_eq!(first, vec![0.0, 5.0, 0.0]);
+        let second: Vec<f32> = mesh.positions[3..6].iter().map(|v| v.round()).collect();
+        assert_eq!(second, vec![0.0, 6.0, 0.0]);
+        assert_eq!(mesh.colors[..6], [1.0, 0.0, 0.0, 0.0, 1.0, 0.0]);
+        assert!((mesh.normals[2] - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn rejects_invalid_documents() {
+        let node = r#"{"mesh": 0}"#;
+        assert!(parse(b"not json", Path::new("")).is_err());
+
+        let old = triangle_gltf(&embedded_uri(), node).replace("\"2.0\"", "\"1.0\"");
+        assert!(parse(old.as_bytes(), Path::new("")).is_err());
+
+        let missing_file = triangle_gltf("missing.bin", node);
+        assert!(parse(missing_file.as_bytes(), Path::new("/nonexistent")).is_err());
+
+        let short = triangle_gltf(&embedded_uri(), node).replace("\"count\": 3", "\"count\": 30");
+        assert!(parse(short.as_bytes(), Path::new("")).is_err());
+
+        let sparse = triangle_gltf(&embedded_uri(), node).replace(
+            r#""type": "SCALAR"}"#,
+            r#""type": "SCALAR", "sparse": {"count": 1}}"#,
+        );
+        assert!(parse(sparse.as_bytes(), Path::new("")).is_err());
+
+        let cycle = triangle_gltf(&embedded_uri(), r#"{"mesh": 0, "children": [0]}"#);
+        assert!(parse(cycle.as_bytes(), Path::new("")).is_err());
+    }
+
+    #[test]
+    fn rejects_accessor_sizes_that_overflow() {
+        let node = r#"{"mesh": 0}"#;
+        let huge = "18446744073709551615";
+        let valid = triangle_gltf(&embedded_uri(), node);
+        let edits = [
+            // count * components
+            (
+                r#""count": 3, "type": "VEC3""#,
+                format!(r#""count": {huge}, "type": "VEC3""#),
+            ),
+            // view offset + length
+            (r#""byteOffset": 36,"#, format!(r#""byteOffset": {huge},"#)),
+            // stride * (count - 1) and accessor offset
+            (
+                r#""count": 3, "type": "VEC2""#,
+                format!(r#""count": {huge}, "type": "VEC2""#),
+            ),
+            (
+                r#""bufferView": 2, "componentType""#,
+                format!(r#""bufferView": 2, "byteOffset": {huge}, "componentType""#),
+            ),
+            // strides shorter than an element would let `count` grow unchecked
+            (
+                r#""byteLength": 24}"#,
+                r#""byteLength": 24, "byteStride": 0}"#.to_string(),
+            ),
+            // zero-filled accessor without a buffer view
+            (
+                r#""bufferView": 1, "componentType": 5126, "count": 3"#,
+                r#""componentType": 5126, "count": 100000000"#.to_string(),
+            ),
+        ];
+        for (from, to) in edits {
+            let text = valid.replace(from, &to);
+            assert_ne!(text, valid, "fixture should contain {}", from);
+            assert!(parse(text.as_bytes(), Path::new("")).is_err(), "{}", to);
+        }
+    }
+}
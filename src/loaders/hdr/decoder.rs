@@ -0,0 +1,327 @@
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+use super::HdrImage;
+
+/// Scanlines this long (or longer) may use the per-channel "new-style" RLE.
+const MIN_NEW_RLE_WIDTH: u32 = 8;
+const MAX_NEW_RLE_WIDTH: u32 = 0x7FFF;
+/// Upper bound on the pixels a byte of scanline data can decode to: an unchained 4-byte repeat
+/// marker covers at most 255 pixels, and new-style runs fewer still. Only files made mostly of
+/// chained markers go past it.
+const MAX_PIXELS_PER_BYTE: usize = 64;
+
+/// Loads a Radiance RGBE (`.hdr`) file into an `HdrImage`.
+pub fn open<P: AsRef<Path>>(path: P) -> Result<HdrImage, String> {
+    let mut file = fs::File::open(&path).map_err(|error| {
+        format!(
+            "Failed to open HDR '{}': {}",
+            path.as_ref().display(),
+            error
+        )
+    })?;
+    from_reader(&mut file)
+}
+
+/// Reads and decodes a Radiance RGBE image from `source`.
+pub fn from_reader<R: Read>(source: &mut R) -> Result<HdrImage, String> {
+    let mut bytes = Vec::new();
+    source
+        .read_to_end(&mut bytes)
+        .map_err(|error| format!("Failed to read HDR data: {}", error))?;
+    decode(&bytes)
+}
+
+/// Decodes a Radiance RGBE file with flat, old-style RLE or new-style RLE scanlines.
+pub fn decode(bytes: &[u8]) -> Result<HdrImage, String> {
+    let mut reader = Reader { bytes, position: 0 };
+
+    let signature = reader.line()?;
+    if !signature.starts_with(b"#?") {
+        return Err("Not a Radiance HDR file (missing '#?' signature)".to_string());
+    }
+    loop {
+        let line = reader.line()?;
+        if line.is_empty() {
+            break;
+        }
+        if let Some(format) = line.strip_prefix(b"FORMAT=")
+            && format != b"32-bit_rle_rgbe"
+        {
+            return Err(format!(
+                "Unsupported HDR pixel format '{}'",
+                String::from_utf8_lossy(format)
+            ));
+        }
+    }
+
+    let resolution = String::from_utf8_lossy(reader.line()?).into_owned();
+    let (width, height, bottom_up) = parse_resolution(&resolution)?;
+    let remaining = bytes.len() - reader.position;
+    (width as usize)
+        .checked_mul(height as usize)
+        .filter(|&pixels| pixels.div_ceil(MAX_PIXELS_PER_BYTE) <= remaining)
+        .ok_or_else(|| {
+            format!(
+                "HDR image {}x{} is too large for its {} bytes of data",
+                width, height, remaining
+            )
+        })?;
+
+    let mut image = HdrImage::new(width, height);
+    let mut scanline = vec![[0u8; 4]; width as usize];
+    for row in 0..height {
+        reader.scanline(&mut scanline)?;
+        let y = if bottom_up { height - row - 1 } else { row };
+        for (x, &rgbe) in scanline.iter().enumerate() {
+            image.set_pixel(x as u32, y, rgbe_to_rgb(rgbe));
+        }
+    }
+
+    Ok(image)
+}
+
+/// Parses the resolution string; only the standard `-Y h +X w` and its vertical flip
+/// `+Y h +X w` orientations are supported.
+fn parse_resolution(line: &str) -> Result<(u32, u32, bool), String> {
+    let invalid = || format!("Unsupported HDR resolution line '{}'", line);
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    let [y_axis, height, "+X", width] = fields[..] else {
+        return Err(invalid());
+    };
+    let bottom_up = match y_axis {
+        "-Y" => false,
+        "+Y" => true,
+        _ => return Err(invalid()),
+    };
+    let width: u32 = width.parse().map_err(|_| invalid())?;
+    let height: u32 = height.parse().map_err(|_| invalid())?;
+    if width == 0 || height == 0 {
+        return Err(invalid());
+    }
+    Ok((width, height, bottom_up))
+}
+
+/// Converts a shared-exponent RGBE pixel to linear floats.
+fn rgbe_to_rgb([r, g, b, e]: [u8; 4]) -> [f32; 3] {
+    if e == 0 {
+        return [0.0; 3];
+    }
+    let scale = 2f32.powi(e as i32 - (128 + 8));
+    [r as f32 * scale, g as f32 * scale, b as f32 * scale]
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn byte(&mut self) -> Result<u8, String> {
+        let byte = *self
+            .bytes
+            .get(self.position)
+            .ok_or_else(|| "HDR data ended before all scanlines were decoded".to_string())?;
+        self.position += 1;
+        Ok(byte)
+    }
+
+    fn rgbe(&mut self) -> Result<[u8; 4], String> {
+        Ok([self.byte()?, self.byte()?, self.byte()?, self.byte()?])
+    }
+
+    /// Returns the next header line without its terminating newline.
+    fn line(&mut self) -> Result<&'a [u8], String> {
+        let rest = &self.bytes[self.position..];
+        let end = rest
+            .iter()
+            .position(|&byte| byte == b'\n')
+            .ok_or_else(|| "HDR header ended unexpectedly".to_string())?;
+        self.position += end + 1;
+        Ok(&rest[..end])
+    }
+
+    fn scanline(&mut self, scanline: &mut [[u8; 4]]) -> Result<(), String> {
+        let width = scanline.len() as u32;
+        if !(MIN_NEW_RLE_WIDTH..=MAX_NEW_RLE_WIDTH).contains(&width) {
+            return self.old_scanline(scanline, 0);
+        }
+
+        let first = self.rgbe()?;
+        if first[0] != 2 || first[1] != 2 || first[2] & 0x80 != 0 {
+            scanline[0] = first;
+            return self.old_scanline(scanline, 1);
+        }
+        let encoded_width = u32::from(first[2]) << 8 | u32::from(first[3]);
+        if encoded_width != width {
+            return Err(format!(
+                "HDR scanline length {} does not match image width {}",
+                encoded_width, width
+            ));
+        }
+
+        // New-style RLE: each channel is stored separately as runs and literal spans
+        for channel in 0..4 {
+            let mut x = 0;
+            while x < scanline.len() {
+                let count = self.byte()?;
+                let (length, is_run) = if count > 128 {
+                    ((count - 128) as usize, true)
+                } else {
+                    (count as usize, false)
+                };
+                if length == 0 || x + length > scanline.len() {
+                    return Err(format!(
+                        "Invalid HDR run of {} bytes at x = {} (width {})",
+                        length, x, width
+                    ));
+                }
+                if is_run {
+                    let value = self.byte()?;
+                    for pixel in &mut scanline[x..x + length] {
+                        pixel[channel] = value;
+                    }
+                } else {
+                    for pixel in &mut scanline[x..x + length] {
+                        pixel[channel] = self.byte()?;
+                    }
+                }
+                x += length;
+            }
+        }
+        Ok(())
+    }
+
+    /// Flat RGBE pixels where `(1, 1, 1, n)` repeats the previous pixel; consecutive repeat
+    /// markers contribute successively higher bytes of the count.
+    fn old_scanline(&mut self, scanline: &mut [[u8; 4]], mut x: usize) -> Result<(), String> {
+        let mut shift = 0;
+        while x < scanline.len() {
+            let pixel = self.rgbe()?;
+            if pixel[..3] == [1, 1, 1] {
+                if x == 0 {
+                    return Err("HDR scanline starts with a repeat marker".to_string());
+                }
+                let count = (pixel[3] as usize)
+                    .checked_shl(shift)
+                    .filter(|count| count >> shift == pixel[3] as usize)
+                    .ok_or_else(|| {
+                        format!(
+                            "HDR repeat markers chained past {} bits at x = {}",
+                            shift, x
+                        )
+                    })?;
+                if count > scanline.len() - x {
+                    return Err(format!(
+                        "HDR repeat of {} pixels overruns the scanline at x = {}",
+                        count, x
+                    ));
+                }
+                let previous = scanline[x - 1];
+                scanline[x..x + count].fill(previous);
+                x += count;
+                shift += 8;
+            } else {
+                scanline[x] = pixel;
+                x += 1;
+                shift = 0;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::decode;
+
+    fn hdr(resolution: &str, pixels: &[u8]) -> Vec<u8> {
+        let mut bytes = format!(
+            "#?RADIANCE\n# comment\nFORMAT=32-bit_rle_rgbe\nEXPOSURE=1.0\n\n{resolution}\n"
+        )
+        .into_bytes();
+        bytes.extend_from_slice(pixels);
+        bytes
+    }
+
+    #[test]
+    fn decodes_flat_and_old_style_rle_scanlines() {
+        let first_row = [
+            128, 64, 32, 129, // (1, 0.5, 0.25)
+            1, 1, 1, 2, // repeat the previous pixel twice
+            0, 0, 0, 0, // black
+        ];
+        let second_row = [
+            128, 128, 128, 136, // 128
+            1, 1, 1, 1, // repeat once
+            0, 0, 0, 0, 0, 0, 0, 0,
+        ];
+        let image = decode(&hdr("+Y 2 +X 4", &[&first_row[..], &second_row].concat()))
+            .expect("flat HDR should decode");
+        // "+Y" stores the bottom row first
+        assert_eq!(image.get_pixel(0, 1), [1.0, 0.5, 0.25]);
+        assert_eq!(image.get_pixel(2, 1), [1.0, 0.5, 0.25]);
+        assert_eq!(image.get_pixel(3, 1), [0.0; 3]);
+        assert_eq!(image.get_pixel(0, 0), [128.0; 3]);
+        assert_eq!(image.get_pixel(1, 0), [128.0; 3]);
+        assert_eq!(image.get_pixel(2, 0), [0.0; 3]);
+
+        // A second consecutive marker shifts its count up a byte: 1 + 256 pixels overrun
+        let overrun = [128, 128, 128, 136, 1, 1, 1, 1, 1, 1, 1, 1];
+        let error = decode(&hdr("-Y 1 +X 4", &overrun)).expect_err("repeat should overrun");
+        assert!(error.contains("overruns"));
+
+        // Zero-length repeats keep shifting the count until it no longer fits in a usize
+        let mut chained = vec![128, 128, 128, 136];
+        for _ in 0..usize::BITS / 8 + 1 {
+            chained.extend_from_slice(&[1, 1, 1, 0]);
+        }
+        chained.extend_from_slice(&[1, 1, 1, 1]);
+        let error = decode(&hdr("-Y 1 +X 4", &chained)).expect_err("chain should be rejected");
+        assert!(error.contains("chained"), "{}", error);
+    }
+
+    #[test]
+    fn decodes_new_style_rle_scanlines() {
+        let width = 10u8;
+        let mut row = vec![2, 2, 0, width];
+        // red: run of 10 x 128
+        row.extend_from_slice(&[128 + 10, 128]);
+        // green: literal 0..5 then run of 5 x 64
+        row.extend_from_slice(&[5, 0, 1, 2, 3, 4, 128 + 5, 64]);
+        // blue: run of 10 x 0
+        row.extend_from_slice(&[128 + 10, 0]);
+        // exponent: run of 10 x 129
+        row.extend_from_slice(&[128 + 10, 129]);
+
+        let image = decode(&hdr("-Y 2 +X 10", &[row.clone(), row].concat()))
+            .expect("RLE HDR should decode");
+        assert_eq!((image.get_width(), image.get_height()), (10, 2));
+        for y in 0..2 {
+            assert_eq!(image.get_pixel(0, y), [1.0, 0.0, 0.0]);
+            assert_eq!(image.get_pixel(4, y), [1.0, 4.0 / 128.0, 0.0]);
+            assert_eq!(image.get_pixel(9, y), [1.0, 0.5, 0.0]);
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_files() {
+        assert!(decode(b"P6\n1 1\n255\n\x00\x00\x00").is_err());
+        assert!(decode(&hdr("-Y 1 +X 1", &[1, 2, 3])).is_err());
+        assert!(decode(&hdr("+X 1 -Y 1", &[1, 2, 3, 4])).is_err());
+        assert!(decode(&hdr("-Y 1 +X 1", &[1, 1, 1, 4])).is_err());
+
+        // Huge resolutions over a few bytes of data: must fail without allocating the image
+        assert!(decode(&hdr("-Y 100000 +X 100000", &[128, 128, 128, 136])).is_err());
+        assert!(decode(&hdr("-Y 1 +X 4294967295", &[128, 128, 128, 136])).is_err());
+
+        let xyze = b"#?RADIANCE\nFORMAT=32-bit_rle_xyze\n\n-Y 1 +X 1\n\x80\x80\x80\x80";
+        assert!(decode(xyze).is_err());
+
+        let wrong_length = [2, 2, 0, 9, 128 + 8, 0, 128 + 8, 0, 128 + 8, 0, 128 + 8, 0];
+        assert!(decode(&hdr("-Y 1 +X 8", &wrong_length)).is_err());
+        let overlong_run = [2, 2, 0, 8, 128 + 9, 0];
+        assert!(decode(&hdr("-Y 1 +X 8", &overlong_run)).is_err());
+    }
+}
@@ -0,0 +1,39 @@
+/// A floating-point RGB image in linear radiance units.
+///
+/// Like the BMP `Image`, rows are stored bottom-up so `data` can be handed to OpenGL as is,
+/// while `get_pixel`/`set_pixel` take `y` counted from the top.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HdrImage {
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<[f32; 3]>,
+}
+
+impl HdrImage {
+    /// Creates a black `HdrImage` of `width` x `height` pixels.
+    pub fn new(width: u32, height: u32) -> HdrImage {
+        HdrImage {
+            width,
+            height,
+            data: vec![[0.0; 3]; width as usize * height as usize],
+        }
+    }
+
+    pub fn get_width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn get_height(&self) -> u32 {
+        self.height
+    }
+
+    /// Returns the RGB value at `x`, `y` (counted from the top-left corner).
+    pub fn get_pixel(&self, x: u32, y: u32) -> [f32; 3] {
+        self.data[((self.height - y - 1) * self.width + x) as usize]
+    }
+
+    /// Sets the RGB value at `x`, `y` (counted from the top-left corner).
+    pub fn set_pixel(&mut self, x: u32, y: u32, value: [f32; 3]) {
+        self.data[((self.height - y - 1) * self.width + x) as usize] = value;
+    }
+}
@@ -0,0 +1,30 @@
+mod decoder;
+mod image;
+
+use std::path::Path;
+
+use crate::loaders::registry::{TextureData, TextureDecoder};
+
+pub use decoder::{decode, from_reader, open};
+pub use image::HdrImage;
+
+/// Registry entry for Radiance HDR textures.
+pub struct HdrDecoder;
+
+impl TextureDecoder for HdrDecoder {
+    fn name(&self) -> &'static str {
+        "HDR"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["hdr"]
+    }
+
+    fn matches_magic(&self, header: &[u8]) -> bool {
+        header.starts_with(b"#?RADIANCE") || header.starts_with(b"#?RGBE")
+    }
+
+    fn decode(&self, path: &Path) -> Result<TextureData, String> {
+        open(path).map(TextureData::Hdr)
+    }
+}
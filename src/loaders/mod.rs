@@ -1,2 +1,17 @@
 pub mod bmp;
+pub mod dds;
+pub mod gltf;
+pub mod hdr;
+pub mod netpbm;
 pub mod obj;
+pub mod ply;
+pub mod png;
+pub mod qoi;
+pub mod registry;
+pub mod stl;
+pub mod tga;
+
+pub use registry::{
+    MODEL_LOADERS, ModelLoader, TEXTURE_DECODERS, TextureData, TextureDecoder, is_texture_path,
+    model_extensions, model_loader_for, texture_decoder_for, texture_extensions,
+};
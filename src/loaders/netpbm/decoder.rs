@@ -0,0 +1,438 @@
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+use crate::loaders::bmp::image::{Image, Pixel};
+
+/// Header values shared by all Netpbm variants.
+struct NetpbmHeader {
+    width: u32,
+    height: u32,
+    /// Samples per pixel: 1 (gray), 2 (gray + alpha), 3 (RGB) or 4 (RGBA).
+    depth: usize,
+    maxval: u32,
+    /// `P1`..`P3` store samples as decimal text.
+    ascii: bool,
+    /// `P1`/`P4` store bits where 1 means black.
+    bitmap: bool,
+}
+
+/// Loads a PBM, PGM, PPM or PAM file into an `Image`.
+pub fn open<P: AsRef<Path>>(path: P) -> Result<Image, String> {
+    let mut file = fs::File::open(&path).map_err(|error| {
+        format!(
+            "Failed to open Netpbm image '{}': {}",
+            path.as_ref().display(),
+            error
+        )
+    })?;
+    from_reader(&mut file)
+}
+
+/// Reads and decodes a Netpbm image from `source`.
+pub fn from_reader<R: Read>(source: &mut R) -> Result<Image, String> {
+    let mut bytes = Vec::new();
+    source
+        .read_to_end(&mut bytes)
+        .map_err(|error| format!("Failed to read Netpbm data: {}", error))?;
+    decode(&bytes)
+}
+
+/// Decodes the ASCII (`P1`–`P3`) and binary (`P4`–`P6`) PBM/PGM/PPM variants and PAM (`P7`).
+///
+/// Samples are scaled from `0..=maxval` to 8 bits; maxvals above 255 use two big-endian bytes
+/// per binary sample. PAM images with an alpha channel keep it.
+pub fn decode(bytes: &[u8]) -> Result<Image, String> {
+    let mut cursor = Cursor { bytes, position: 0 };
+    let magic = cursor
+        .bytes
+        .get(..2)
+        .ok_or_else(|| "Netpbm data is too short for a magic number".to_string())?;
+    cursor.position = 2;
+
+    let header = match magic {
+        b"P1" | b"P4" => NetpbmHeader {
+            width: cursor.header_number("width")?,
+            height: cursor.header_number("height")?,
+            depth: 1,
+            maxval: 1,
+            ascii: magic == b"P1",
+            bitmap: true,
+        },
+        b"P2" | b"P3" | b"P5" | b"P6" => NetpbmHeader {
+            width: cursor.header_number("width")?,
+            height: cursor.header_number("height")?,
+            depth: if matches!(magic, b"P3" | b"P6") { 3 } else { 1 },
+            maxval: cursor.header_number("maxval")?,
+            ascii: matches!(magic, b"P2" | b"P3"),
+            bitmap: false,
+        },
+        b"P7" => read_pam_header(&mut cursor)?,
+        _ => {
+            return Err(format!(
+                "Unsupported Netpbm magic number '{}'",
+                String::from_utf8_lossy(magic)
+            ));
+        }
+    };
+
+    if header.width == 0 || header.height == 0 {
+        return Err(format!(
+            "Netpbm image has invalid dimensions {}x{}",
+            header.width, header.height
+        ));
+    }
+    if header.maxval == 0 || header.maxval > 65535 {
+        return Err(format!(
+            "Netpbm maxval must be between 1 and 65535, was {}",
+            header.maxval
+        ));
+    }
+
+    // Binary rasters start after exactly one whitespace byte following the header
+    if !header.ascii {
+        cursor.position += 1;
+    }
+
+    let width = header.width as usize;
+    let sample_count = width
+        .checked_mul(header.height as usize)
+        .and_then(|pixels| pixels.checked_mul(header.depth))
+        .ok_or_else(|| {
+            format!(
+                "Netpbm dimensions {}x{}x{} are too large",
+                header.width, header.height, header.depth
+            )
+        })?;
+    let samples = if header.ascii {
+        read_ascii_samples(&mut cursor, &header, sample_count)?
+    } else if header.bitmap {
+        read_packed_bits(&cursor, width, header.height as usize)?
+    } else {
+        read_binary_samples(&cursor, &header, sample_count)?
+    };
+
+    let scale = |value: u32| -> Result<u8, String> {
+        if value > header.maxval {
+            return Err(format!(
+                "Netpbm sample {} exceeds maxval {}",
+                value, header.maxval
+            ));
+        }
+        Ok(((value * 255 + header.maxval / 2) / header.maxval) as u8)
+    };
+
+    let mut image = Image::new(header.width, header.height);
+    for (index, pixel_samples) in samples.chunks_exact(header.depth).enumerate() {
+        let pixel = if header.bitmap {
+            let value = if pixel_samples[0] == 1 { 0 } else { 255 };
+            Pixel::new(value, value, value)
+        } else {
+            match *pixel_samples {
+                [gray] => {
+                    let gray = scale(gray)?;
+                    Pixel::new(gray, gray, gray)
+                }
+                [gray, alpha] => {
+                    let gray = scale(gray)?;
+                    Pixel::with_alpha(gray, gray, gray, scale(alpha)?)
+                }
+                [r, g, b] => Pixel::new(scale(r)?, scale(g)?, scale(b)?),
+                [r, g, b, a] => Pixel::with_alpha(scale(r)?, scale(g)?, scale(b)?, scale(a)?),
+                _ => unreachable!("depth is validated"),
+            }
+        };
+        image.set_pixel((index % width) as u32, (index / width) as u32, pixel);
+    }
+
+    Ok(image)
+}
+
+struct Cursor<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Cursor<'a> {
+    /// Skips whitespace and `#` comments (which run to the end of the line).
+    fn skip_whitespace_and_comments(&mut self) {
+        while let Some(&byte) = self.bytes.get(self.position) {
+            if byte == b'#' {
+                while self
+                    .bytes
+                    .get(self.position)
+                    .is_some_and(|&byte| byte != b'\n' && byte != b'\r')
+                {
+                    self.position += 1;
+                }
+            } else if byte.is_ascii_whitespace() {
+                self.position += 1;
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn token(&mut self) -> Option<&'a [u8]> {
+        self.skip_whitespace_and_comments();
+        let start = self.position;
+        while self
+            .bytes
+            .get(self.position)
+            .is_some_and(|byte| !byte.is_ascii_whitespace() && *byte != b'#')
+        {
+            self.position += 1;
+        }
+        (self.position > start).then(|| &self.bytes[start..self.position])
+    }
+
+    fn header_number(&mut self, name: &str) -> Result<u32, String> {
+        let token = self
+            .token()
+            .ok_or_else(|| format!("Netpbm header is missing the {}", name))?;
+        std::str::from_utf8(token)
+            .ok()
+            .and_then(|text| text.parse::<u32>().ok())
+            .ok_or_else(|| {
+                format!(
+                    "Netpbm header {} is not a number: '{}'",
+                    name,
+                    String::from_utf8_lossy(token)
+                )
+            })
+    }
+}
+
+fn read_pam_header(cursor: &mut Cursor) -> Result<NetpbmHeader, String> {
+    let (mut width, mut height, mut depth, mut maxval) = (None, None, None, None);
+    let mut tuple_type = String::new();
+
+    loop {
+        let keyword = cursor
+            .token()
+            .ok_or_else(|| "PAM header is missing ENDHDR".to_string())?;
+        match keyword {
+            b"ENDHDR" => break,
+            b"WIDTH" => width = Some(cursor.header_number("WIDTH")?),
+            b"HEIGHT" => height = Some(cursor.header_number("HEIGHT")?),
+            b"DEPTH" => depth = Some(cursor.header_number("DEPTH")?),
+            b"MAXVAL" => maxval = Some(cursor.header_number("MAXVAL")?),
+            b"TUPLTYPE" => {
+                let value = cursor
+                    .token()
+                    .ok_or_else(|| "PAM TUPLTYPE has no value".to_string())?;
+                tuple_type = String::from_utf8_lossy(value).into_owned();
+            }
+            other => {
+                return Err(format!(
+                    "Unknown PAM header field '{}'",
+                    String::from_utf8_lossy(other)
+                ));
+            }
+        }
+    }
+    // ENDHDR is followed by a single newline, which `decode` skips like other binary formats
+
+    let missing = |name: &str| format!("PAM header is missing {}", name);
+    let depth = depth.ok_or_else(|| missing("DEPTH"))? as usize;
+    let expected_depth = match tuple_type.as_str() {
+        "BLACKANDWHITE" | "GRAYSCALE" => Some(1),
+        "BLACKANDWHITE_ALPHA" | "GRAYSCALE_ALPHA" => Some(2),
+        "RGB" => Some(3),
+        "RGB_ALPHA" => Some(4),
+        _ => None,
+    };
+    if !(1..=4).contains(&depth) || expected_depth.is_some_and(|expected| expected != depth) {
+        return Err(format!(
+            "Unsupported PAM depth {} for tuple type '{}'",
+            depth, tuple_type
+        ));
+    }
+
+    Ok(NetpbmHeader {
+        width: width.ok_or_else(|| missing("WIDTH"))?,
+        height: height.ok_or_else(|| missing("HEIGHT"))?,
+        depth,
+        maxval: maxval.ok_or_else(|| missing("MAXVAL"))?,
+        ascii: false,
+        bitmap: false,
+    })
+}
+
+fn read_ascii_samples(
+    cursor: &mut Cursor,
+    header: &NetpbmHeader,
+    sample_count: usize,
+) -> Result<Vec<u32>, String> {
+    // Every ASCII sample takes at least one byte, which bounds what can really be read.
+    let remaining = cursor.bytes.len().saturating_sub(cursor.position);
+    let mut samples = Vec::with_capacity(sample_count.min(remaining));
+    while samples.len() < sample_count {
+        if header.bitmap {
+            // PBM bits may be written without separating whitespace
+            cursor.skip_whitespace_and_comments();
+            match cursor.bytes.get(cursor.position) {
+                Some(b'0') => samples.push(0),
+                Some(b'1') => samples.push(1),
+                Some(&other) => {
+                    return Err(format!("Invalid PBM bit '{}'", other as char));
+                }
+                None => break,
+            }
+            cursor.position += 1;
+        } else {
+            let Some(token) = cursor.token() else {
+                break;
+            };
+            let value = std::str::from_utf8(token)
+                .ok()
+                .and_then(|text| text.parse::<u32>().ok())
+                .ok_or_else(|| {
+                    format!("Invalid Netpbm sample '{}'", String::from_utf8_lossy(token))
+                })?;
+            samples.push(value);
+        }
+    }
+
+    if samples.len() < sample_count {
+        return Err(format!(
+            "Netpbm data ended after {} of {} samples",
+            samples.len(),
+            sample_count
+        ));
+    }
+    Ok(samples)
+}
+
+fn read_packed_bits(cursor: &Cursor, width: usize, height: usize) -> Result<Vec<u32>, String> {
+    let row_bytes = width.div_ceil(8);
+    let data = row_bytes
+        .checked_mul(height)
+        .and_then(|size| cursor.position.checked_add(size))
+        .and_then(|end| cursor.bytes.get(cursor.position..end))
+        .ok_or_else(|| "PBM raster data is truncated".to_string())?;
+
+    Ok(data
+        .chunks_exact(row_bytes)
+        .flat_map(|row| (0..width).map(move |x| ((row[x / 8] >> (7 - x % 8)) & 1) as u32))
+        .collect())
+}
+
+fn read_binary_samples(
+    cursor: &Cursor,
+    header: &NetpbmHeader,
+    sample_count: usize,
+) -> Result<Vec<u32>, String> {
+    let bytes_per_sample = if header.maxval > 255 { 2 } else { 1 };
+    let data = sample_count
+        .checked_mul(bytes_per_sample)
+        .and_then(|size| cursor.position.checked_add(size))
+        .and_then(|end| cursor.bytes.get(cursor.position..end))
+        .ok_or_else(|| "Netpbm raster data is truncated".to_string())?;
+
+    Ok(data
+        .chunks_exact(bytes_per_sample)
+        .map(|sample| match *sample {
+            [value] => value as u32,
+            [hi, lo] => u16::from_be_bytes([hi, lo]) as u32,
+            _ => unreachable!("samples are 1 or 2 bytes"),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::decode;
+    use crate::loaders::bmp::image::Pixel;
+
+    #[test]
+    fn decodes_ascii_variants_with_comments() {
+        let pbm = decode(b"P1\n# a comment\n3 2\n1 0 1\n010").expect("P1 should decode");
+        assert_eq!(pbm.get_pixel(0, 0), Pixel::new(0, 0, 0));
+        assert_eq!(pbm.get_pixel(1, 0), Pixel::new(255, 255, 255));
+        assert_eq!(pbm.get_pixel(1, 1), Pixel::new(0, 0, 0));
+
+        let pgm = decode(b"P2 2 1 # inline comment\n 4\n0 2").expect("P2 should decode");
+        assert_eq!(pgm.get_pixel(0, 0), Pixel::new(0, 0, 0));
+        assert_eq!(pgm.get_pixel(1, 0), Pixel::new(128, 128, 128));
+
+        let ppm = decode(b"P3\n1 2\n255\n255 0 0\n0 0 255\n").expect("P3 should decode");
+        assert_eq!(ppm.get_pixel(0, 0), Pixel::new(255, 0, 0));
+        assert_eq!(ppm.get_pixel(0, 1), Pixel::new(0, 0, 255));
+    }
+
+    #[test]
+    fn decodes_binary_variants() {
+        // 10 pixels wide: two bytes per row, the last 6 bits are padding
+        let mut pbm = b"P4\n10 1\n".to_vec();
+        pbm.extend_from_slice(&[0b1000_0000, 0b0100_0000]);
+        let image = decode(&pbm).expect("P4 should decode");
+        assert_eq!(image.get_pixel(0, 0), Pixel::new(0, 0, 0));
+        assert_eq!(image.get_pixel(1, 0), Pixel::new(255, 255, 255));
+        assert_eq!(image.get_pixel(9, 0), Pixel::new(0, 0, 0));
+
+        let mut pgm = b"P5 2 1 255\n".to_vec();
+        pgm.extend_from_slice(&[10, 200]);
+        let image = decode(&pgm).expect("P5 should decode");
+        assert_eq!(image.get_pixel(1, 0), Pixel::new(200, 200, 200));
+
+        // Raster bytes that look like whitespace must not be skipped
+        let mut ppm = b"P6\n1 1\n255\n".to_vec();
+        ppm.extend_from_slice(&[b' ', b'\n', 7]);
+        let image = decode(&ppm).expect("P6 should decode");
+        assert_eq!(image.get_pixel(0, 0), Pixel::new(32, 10, 7));
+    }
+
+    #[test]
+    fn decodes_16_bit_maxval() {
+        let mut ppm = b"P6\n1 1\n65535\n".to_vec();
+        ppm.extend_from_slice(&[0xFF, 0xFF, 0x80, 0x00, 0x00, 0x00]);
+        let image = decode(&ppm).expect("16-bit P6 should decode");
+        assert_eq!(image.get_pixel(0, 0), Pixel::new(255, 128, 0));
+
+        let pgm = decode(b"P2 1 1 1000 500").expect("16-bit P2 should decode");
+        assert_eq!(pgm.get_pixel(0, 0), Pixel::new(128, 128, 128));
+    }
+
+    #[test]
+    fn decodes_pam_with_alpha() {
+        let mut pam =
+            b"P7\nWIDTH 2\nHEIGHT 1\nDEPTH 4\nMAXVAL 255\nTUPLTYPE RGB_ALPHA\nENDHDR\n".to_vec();
+        pam.extend_from_slice(&[255, 0, 0, 128, 0, 255, 0, 255]);
+        let image = decode(&pam).expect("PAM should decode");
+        assert_eq!(image.get_pixel(0, 0), Pixel::with_alpha(255, 0, 0, 128));
+        assert_eq!(image.get_pixel(1, 0), Pixel::new(0, 255, 0));
+        assert!(image.has_alpha());
+
+        let mut gray_alpha = b"P7\nWIDTH 1\nHEIGHT 1\nDEPTH 2\nMAXVAL 65535\nENDHDR\n".to_vec();
+        gray_alpha.extend_from_slice(&[0xFF, 0xFF, 0x00, 0x00]);
+        let image = decode(&gray_alpha).expect("PAM without TUPLTYPE should decode");
+        assert_eq!(image.get_pixel(0, 0), Pixel::with_alpha(255, 255, 255, 0));
+    }
+
+    #[test]
+    fn rejects_malformed_images() {
+        for bytes in [
+            &b"P9 1 1 255\n\0"[..],
+            b"P2 2 1 255\n1",
+            b"P2 1 1 3\n4",
+            b"P5 1 1 0\n\0",
+            b"P6 2 2 255\n\0\0\0",
+            b"P1 1 1\n2",
+            b"P7\nWIDTH 1\nHEIGHT 1\nDEPTH 3\nMAXVAL 255\nTUPLTYPE GRAYSCALE\nENDHDR\n\0\0\0",
+            b"P7\nWIDTH 1\nHEIGHT 1\nMAXVAL 255\nENDHDR\n\0",
+            b"P",
+            // Huge headers over a few bytes of data: must fail without reserving the raster
+            b"P2 100000 100000 255\n1 2 3",
+            b"P1 100000 100000\n0101",
+            b"P5 4294967295 4294967295 65535\n\0\0",
+            b"P4 4294967295 4294967295\n\0",
+        ] {
+            assert!(
+                decode(bytes).is_err(),
+                "'{}' should fail",
+                String::from_utf8_lossy(bytes)
+            );
+        }
+    }
+}
@@ -0,0 +1,31 @@
+mod decoder;
+
+use std::path::Path;
+
+use crate::loaders::registry::{TextureData, TextureDecoder};
+
+pub use decoder::{decode, from_reader, open};
+
+/// Registry entry for Netpbm textures.
+pub struct NetpbmDecoder;
+
+impl TextureDecoder for NetpbmDecoder {
+    fn name(&self) -> &'static str {
+        "Netpbm"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["pam", "pbm", "pgm", "ppm"]
+    }
+
+    fn matches_magic(&self, header: &[u8]) -> bool {
+        matches!(
+            header,
+            [b'P', b'1'..=b'7', b' ' | b'\t' | b'\n' | b'\r', ..]
+        )
+    }
+
+    fn decode(&self, path: &Path) -> Result<TextureData, String> {
+        open(path).map(|image| TextureData::Levels(vec![image]))
+    }
+}
@@ -1,8 +1,46 @@
 mod index;
+mod normals;
 mod parse_mtl;
 mod parse_obj;
+mod texture_map;
 mod triangulate;
 mod types;
+mod write_mtl;
+mod write_obj;
+
+use std::path::Path;
+
+use crate::loaders::registry::ModelLoader;
 
 pub use parse_obj::load;
 pub use types::*;
+pub use write_mtl::encode_mtl;
+pub use write_obj::{encode, save};
+
+pub(crate) use index::FaceVertex;
+pub(crate) use normals::generate_vertex_normals;
+pub(crate) use triangulate::{TriangulationOutcome, triangulate_face};
+
+/// Registry entry for OBJ models (with their MTL materials).
+pub struct ObjLoader;
+
+impl ModelLoader for ObjLoader {
+    fn name(&self) -> &'static str {
+        "OBJ"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["obj"]
+    }
+
+    fn load(&self, path: &Path) -> Result<ObjSceneData, String> {
+        load(
+            path,
+            &ObjLoadOptions {
+                triangulate: true,
+                single_index: true,
+                crease_angle_degrees: None,
+            },
+        )
+    }
+}
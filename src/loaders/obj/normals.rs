@@ -0,0 +1,285 @@
+use std::collections::HashMap;
+
+use super::index::FaceVertex;
+
+/// Fills in normals for triangle corners that have no `vn` reference.
+///
+/// Faces in smoothing group `0` (`s off`) get their flat face normal. Faces in any other group
+/// get an area-weighted average of every face in the same group that shares the corner's
+/// position. When `crease_angle_degrees` is set, neighbors whose face normal deviates from the
+/// current face by more than that angle are left out, which keeps sharp edges sharp.
+///
+/// Generated normals are appended to `normals` and the corners are rewritten to reference them,
+/// so identical normals are shared and vertex welding keeps working afterwards.
+pub fn generate_missing_normals(
+    faces: &mut [&mut [FaceVertex]],
+    smoothing_groups: &[u32],
+    positions: &[[f32; 3]],
+    normals: &mut Vec<[f32; 3]>,
+    crease_angle_degrees: Option<f32>,
+) {
+    let needs_normals: Vec<bool> = faces
+        .iter()
+        .map(|face| face.iter().any(|corner| corner.2.is_none()))
+        .collect();
+    if !needs_normals.iter().any(|&needed| needed) {
+        return;
+    }
+
+    let weighted_normals: Vec<[f32; 3]> = faces
+        .iter()
+        .map(|face| weighted_face_normal(face, positions))
+        .collect();
+    let unit_normals: Vec<[f32; 3]> = weighted_normals.iter().map(|&n| normalize(n)).collect();
+
+    let mut shared_corners: HashMap<(u32, usize), Vec<usize>> = HashMap::new();
+    for (face_index, face) in faces.iter().enumerate() {
+        let group = smoothing_groups[face_index];
+        if group == 0 || !needs_normals[face_index] {
+            continue;
+        }
+        for corner in face.iter() {
+            let faces_at_corner = shared_corners.entry((group, corner.0)).or_default();
+            if !faces_at_corner.contains(&face_index) {
+                faces_at_corner.push(face_index);
+            }
+        }
+    }
+
+    let cos_threshold = crease_angle_degrees.map(|degrees| degrees.to_radians().cos());
+    let mut generated: HashMap<[u32; 3], usize> = HashMap::new();
+
+    for (face_index, face) in faces.iter_mut().enumerate() {
+        if !needs_normals[face_index] {
+            continue;
+        }
+
+        let group = smoothing_groups[face_index];
+        let face_normal = unit_normals[face_index];
+
+        for corner in face.iter_mut() {
+            if corner.2.is_some() {
+                continue;
+            }
+
+            let normal = if group == 0 {
+                face_normal
+            } else {
+                let mut sum = [0.0, 0.0, 0.0];
+                for &neighbor in &shared_corners[&(group, corner.0)] {
+                    if let Some(cos_threshold) = cos_threshold
+                        && neighbor != face_index
+                        && dot(face_normal, unit_normals[neighbor]) < cos_threshold
+                    {
+                        continue;
+                    }
+                    let weighted = weighted_normals[neighbor];
+                    sum[0] += weighted[0];
+                    sum[1] += weighted[1];
+                    sum[2] += weighted[2];
+                }
+                normalize(sum)
+            };
+
+            let key = normal.map(f32::to_bits);
+            let normal_index = *generated.entry(key).or_insert_with(|| {
+                normals.push(normal);
+                normals.len() - 1
+            });
+            corner.2 = Some(normal_index);
+        }
+    }
+}
+
+/// Smooth normals for an already indexed triangle mesh: every vertex gets the area-weighted
+/// average of the faces that use it. Unreferenced vertices keep a zero normal.
+pub fn generate_vertex_normals(positions: &[[f32; 3]], triangles: &[[usize; 3]]) -> Vec<[f32; 3]> {
+    let mut corners: Vec<[FaceVertex; 3]> = triangles
+        .iter()
+        .map(|triangle| triangle.map(|index| (index, None, None)))
+        .collect();
+    let mut generated = Vec::new();
+    {
+        let mut faces: Vec<&mut [FaceVertex]> =
+            corners.iter_mut().map(|corners| &mut corners[..]).collect();
+        let smoothing_groups = vec![1; faces.len()];
+        generate_missing_normals(
+            &mut faces,
+            &smoothing_groups,
+            positions,
+            &mut generated,
+            None,
+        );
+    }
+
+    let mut normals = vec![[0.0; 3]; positions.len()];
+    for &(position, _, normal) in corners.iter().flatten() {
+        if let Some(normal) = normal {
+            normals[position] = generated[normal];
+        }
+    }
+    normals
+}
+
+/// Cross product of two triangle edges; its length is twice the triangle area.
+fn weighted_face_normal(face: &[FaceVertex], positions: &[[f32; 3]]) -> [f32; 3] {
+    let a = positions[face[0].0];
+    let b = positions[face[1].0];
+    let c = positions[face[2].0];
+    let ab = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+    let ac = [c[0] - a[0], c[1] - a[1], c[2] - a[2]];
+
+    [
+        ab[1] * ac[2] - ab[2] * ac[1],
+        ab[2] * ac[0] - ab[0] * ac[2],
+        ab[0] * ac[1] - ab[1] * ac[0],
+    ]
+}
+
+fn normalize(v: [f32; 3]) -> [f32; 3] {
+    let length = dot(v, v).sqrt();
+    if length <= f32::EPSILON {
+        [0.0, 0.0, 0.0]
+    } else {
+        [v[0] / length, v[1] / length, v[2] / length]
+    }
+}
+
+fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FaceVertex, generate_missing_normals};
+
+    // Two triangles meeting at a 90 degree edge along the X axis (positions 0 and 1).
+    fn folded_positions() -> Vec<[f32; 3]> {
+        vec![
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [0.0, 0.0, 1.0],
+        ]
+    }
+
+    fn folded_faces() -> Vec<Vec<FaceVertex>> {
+        vec![
+            vec![(0, None, None), (1, None, None), (2, None, None)],
+            vec![(1, None, None), (0, None, None), (3, None, None)],
+        ]
+    }
+
+    fn run(
+        faces: &mut [Vec<FaceVertex>],
+        groups: &[u32],
+        crease_angle_degrees: Option<f32>,
+    ) -> Vec<[f32; 3]> {
+        let positions = folded_positions();
+        let mut normals = Vec::new();
+        let mut face_refs: Vec<&mut [FaceVertex]> =
+            faces.iter_mut().map(|face| face.as_mut_slice()).collect();
+        generate_missing_normals(
+            &mut face_refs,
+            groups,
+            &positions,
+            &mut normals,
+            crease_angle_degrees,
+        );
+        normals
+    }
+
+    fn assert_close(actual: [f32; 3], expected: [f32; 3]) {
+        for axis in 0..3 {
+            assert!(
+                (actual[axis] - expected[axis]).abs() < 1e-5,
+                "expected {:?}, got {:?}",
+                expected,
+                actual
+            );
+        }
+    }
+
+    #[test]
+    fn flat_normals_without_smoothing_group() {
+        let mut faces = folded_faces();
+        let normals = run(&mut faces, &[0, 0], None);
+
+        assert_eq!(normals.len(), 2);
+        for corner in &faces[0] {
+            assert_close(
+                normals[corner.2.expect("normal generated")],
+                [0.0, 0.0, 1.0],
+            );
+        }
+        for corner in &faces[1] {
+            assert_close(
+                normals[corner.2.expect("normal generated")],
+                [0.0, 1.0, 0.0],
+            );
+        }
+    }
+
+    #[test]
+    fn smooth_normals_average_shared_corners() {
+        let mut faces = folded_faces();
+        let normals = run(&mut faces, &[1, 1], None);
+
+        let half = std::f32::consts::FRAC_1_SQRT_2;
+        let shared = normals[faces[0][0].2.expect("normal generated")];
+        assert_close(shared, [0.0, half, half]);
+        assert_eq!(faces[0][0].2, faces[1][1].2);
+        assert_eq!(faces[0][1].2, faces[1][0].2);
+        assert_close(
+            normals[faces[0][2].2.expect("normal generated")],
+            [0.0, 0.0, 1.0],
+        );
+    }
+
+    #[test]
+    fn different_smoothing_groups_do_not_blend() {
+        let mut faces = folded_faces();
+        let normals = run(&mut faces, &[1, 2], None);
+
+        assert_close(
+            normals[faces[0][0].2.expect("normal generated")],
+            [0.0, 0.0, 1.0],
+        );
+        assert_close(
+            normals[faces[1][1].2.expect("normal generated")],
+            [0.0, 1.0, 0.0],
+        );
+    }
+
+    #[test]
+    fn crease_angle_splits_sharp_edges() {
+        let mut faces = folded_faces();
+        let normals = run(&mut faces, &[1, 1], Some(60.0));
+        assert_close(
+            normals[faces[0][0].2.expect("normal generated")],
+            [0.0, 0.0, 1.0],
+        );
+
+        let mut faces = folded_faces();
+        let normals = run(&mut faces, &[1, 1], Some(120.0));
+        let half = std::f32::consts::FRAC_1_SQRT_2;
+        assert_close(
+            normals[faces[0][0].2.expect("normal generated")],
+            [0.0, half, half],
+        );
+    }
+
+    #[test]
+    fn existing_normals_are_kept() {
+        let positions = folded_positions();
+        let mut normals = vec![[1.0, 0.0, 0.0]];
+        let mut face = vec![(0, None, Some(0)), (1, None, None), (2, None, Some(0))];
+        let mut face_refs: Vec<&mut [FaceVertex]> = vec![face.as_mut_slice()];
+        generate_missing_normals(&mut face_refs, &[0], &positions, &mut normals, None);
+
+        assert_eq!(face[0].2, Some(0));
+        assert_eq!(face[2].2, Some(0));
+        assert_eq!(face[1].2, Some(1));
+        assert_close(normals[1], [0.0, 0.0, 1.0]);
+    }
+}
@@ -3,6 +3,7 @@ use std::io::{BufRead, BufReader};
 use std::path::Path;
 
 use super::index::directive_value;
+use super::texture_map::parse_texture_map;
 use super::types::ObjMaterialData;
 
 pub fn load_mtl(path: &Path) -> Result<Vec<ObjMaterialData>, String> {
@@ -40,23 +41,76 @@ pub fn load_mtl(path: &Path) -> Result<Vec<ObjMaterialData>, String> {
                     ..Default::default()
                 });
             }
+            "Ka" | "Kd" | "Ks" | "Ke" => {
+                let key = parts[0];
+                let color = parse_color(&parts, line_number)?;
+                if let Some(ref mut mat) = current_material {
+                    let slot = match key {
+                        "Ka" => &mut mat.ambient_color,
+                        "Kd" => &mut mat.diffuse_color,
+                        "Ks" => &mut mat.specular_color,
+                        _ => &mut mat.emissive_color,
+                    };
+                    *slot = Some(color);
+                }
+            }
+            "Ns" => {
+                let shininess = parse_scalar(&parts, line_number, 0.0, f32::MAX)?;
+                if let Some(ref mut mat) = current_material {
+                    mat.shininess = Some(shininess);
+                }
+            }
+            "Ni" => {
+                let optical_density = parse_scalar(&parts, line_number, 0.0, 10.0)?;
+                if let Some(ref mut mat) = current_material {
+                    mat.optical_density = Some(optical_density);
+                }
+            }
+            "d" => {
+                let dissolve = parse_scalar(&parts, line_number, 0.0, 1.0)?;
+                if let Some(ref mut mat) = current_material {
+                    mat.dissolve = Some(dissolve);
+                }
+            }
+            "Tr" => {
+                let transparency = parse_scalar(&parts, line_number, 0.0, 1.0)?;
+                if let Some(ref mut mat) = current_material {
+                    mat.dissolve = Some(1.0 - transparency);
+                }
+            }
+            "illum" => {
+                let values = directive_values(&parts);
+                let raw = single_value(&values, parts[0], line_number)?;
+                let illumination_model = raw
+                    .parse::<u8>()
+                    .ok()
+                    .filter(|model| *model <= 10)
+                    .ok_or_else(|| {
+                        format!(
+                            "MTL line {}: illumination model must be an integer from 0 to 10, was '{}'",
+                            line_number, raw
+                        )
+                    })?;
+                if let Some(ref mut mat) = current_material {
+                    mat.illumination_model = Some(illumination_model);
+                }
+            }
             "map_Kd" => {
-                let diffuse_texture = directive_value(line, "map_Kd", line_number)?;
+                let diffuse_texture = parse_texture_map(line, line_number)?;
                 if let Some(ref mut mat) = current_material {
-                    mat.diffuse_texture = Some(diffuse_texture.to_string());
+                    mat.diffuse_texture = Some(diffuse_texture);
                 }
             }
             "map_Ks" => {
-                let specular_texture = directive_value(line, "map_Ks", line_number)?;
+                let specular_texture = parse_texture_map(line, line_number)?;
                 if let Some(ref mut mat) = current_material {
-                    mat.specular_texture = Some(specular_texture.to_string());
+                    mat.specular_texture = Some(specular_texture);
                 }
             }
             "map_Bump" | "bump" => {
-                let key = parts[0];
-                let normal_texture = directive_value(line, key, line_number)?;
+                let normal_texture = parse_texture_map(line, line_number)?;
                 if let Some(ref mut mat) = current_material {
-                    mat.normal_texture = Some(normal_texture.to_string());
+                    mat.normal_texture = Some(normal_texture);
                 }
             }
             _ => {}
@@ -69,3 +123,208 @@ pub fn load_mtl(path: &Path) -> Result<Vec<ObjMaterialData>, String> {
 
     Ok(materials)
 }
+
+fn directive_values<'a>(parts: &[&'a str]) -> Vec<&'a str> {
+    parts
+        .iter()
+        .skip(1)
+        .take_while(|token| !token.starts_with('#'))
+        .copied()
+        .collect()
+}
+
+fn single_value<'a>(
+    values: &[&'a str],
+    directive: &str,
+    line_number: usize,
+) -> Result<&'a str, String> {
+    match values {
+        [value] => Ok(value),
+        [] => Err(format!(
+            "Line {}: directive '{}' is missing a required value",
+            line_number, directive
+        )),
+        _ => Err(format!(
+            "MTL line {}: directive '{}' expects a single value, found {}",
+            line_number,
+            directive,
+            values.len()
+        )),
+    }
+}
+
+fn parse_mtl_f32(raw: &str, line_number: usize, directive: &str) -> Result<f32, String> {
+    raw.parse::<f32>()
+        .ok()
+        .filter(|value| value.is_finite())
+        .ok_or_else(|| {
+            format!(
+                "MTL line {}: invalid {} value '{}'",
+                line_number, directive, raw
+            )
+        })
+}
+
+fn parse_color(parts: &[&str], line_number: usize) -> Result<[f32; 3], String> {
+    let directive = parts[0];
+    let values = directive_values(parts);
+
+    if matches!(values.first(), Some(&"spectral") | Some(&"xyz")) {
+        return Err(format!(
+            "MTL line {}: '{} {}' colors are not supported, use RGB values",
+            line_number, directive, values[0]
+        ));
+    }
+
+    let color = match values.as_slice() {
+        [gray] => {
+            let gray = parse_mtl_f32(gray, line_number, directive)?;
+            [gray, gray, gray]
+        }
+        [r, g, b] => [
+            parse_mtl_f32(r, line_number, directive)?,
+            parse_mtl_f32(g, line_number, directive)?,
+            parse_mtl_f32(b, line_number, directive)?,
+        ],
+        [] => {
+            return Err(format!(
+                "Line {}: directive '{}' is missing a required value",
+                line_number, directive
+            ));
+        }
+        _ => {
+            return Err(format!(
+                "MTL line {}: color '{}' requires 1 or 3 components, found {}",
+                line_number,
+                directive,
+                values.len()
+            ));
+        }
+    };
+
+    if color.iter().any(|component| *component < 0.0) {
+        return Err(format!(
+            "MTL line {}: color '{}' components must not be negative",
+            line_number, directive
+        ));
+    }
+
+    Ok(color)
+}
+
+fn parse_scalar(parts: &[&str], line_number: usize, min: f32, max: f32) -> Result<f32, String> {
+    let directive = parts[0];
+    let mut values = directive_values(parts);
+    // `d -halo factor` only changes how dissolve is applied; the factor itself still counts.
+    if directive == "d" && values.first() == Some(&"-halo") {
+        values.remove(0);
+    }
+
+    let raw = single_value(&values, directive, line_number)?;
+    let value = parse_mtl_f32(raw, line_number, directive)?;
+    if value < min || value > max {
+        let expected = if max == f32::MAX {
+            format!("at least {}", min)
+        } else {
+            format!("in [{}, {}]", min, max)
+        };
+        return Err(format!(
+            "MTL line {}: {} value {} must be {}",
+            line_number, directive, value, expected
+        ));
+    }
+
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::process;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use super::load_mtl;
+
+    fn unique_temp_dir(prefix: &str) -> PathBuf {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("system clock should be after epoch")
+            .as_nanos();
+        let dir = env::temp_dir().join(format!("{}_{}_{}", prefix, process::id(), nanos));
+        fs::create_dir_all(&dir).expect("failed to create temporary test directory");
+        dir
+    }
+
+    fn load_fixture(prefix: &str, mtl_data: &str) -> Result<Vec<super::ObjMaterialData>, String> {
+        let dir = unique_temp_dir(prefix);
+        let mtl_path = dir.join("fixture.mtl");
+        fs::write(&mtl_path, mtl_data).expect("failed to write MTL fixture");
+        let result = load_mtl(&mtl_path);
+        fs::remove_dir_all(&dir).expect("failed to cleanup temp directory");
+        result
+    }
+
+    #[test]
+    fn parses_scalar_and_color_properties() {
+        let materials = load_fixture(
+            "scop_mtl_properties",
+            "\
+newmtl Painted
+Ka 0.1 0.2 0.3
+Kd 0.64 0.5 0.25 # base color
+Ks 0.5
+Ke 2.0 1.5 0.0
+Ns 96.5
+Ni 1.45
+d 0.75
+illum 2
+newmtl Glass
+Tr 0.9
+",
+        )
+        .expect("MTL with material properties should parse");
+
+        assert_eq!(materials.len(), 2);
+        let painted = &materials[0];
+        assert_eq!(painted.ambient_color, Some([0.1, 0.2, 0.3]));
+        assert_eq!(painted.diffuse_color, Some([0.64, 0.5, 0.25]));
+        assert_eq!(painted.specular_color, Some([0.5, 0.5, 0.5]));
+        assert_eq!(painted.emissive_color, Some([2.0, 1.5, 0.0]));
+        assert_eq!(painted.shininess, Some(96.5));
+        assert_eq!(painted.optical_density, Some(1.45));
+        assert_eq!(painted.dissolve, Some(0.75));
+        assert_eq!(painted.illumination_model, Some(2));
+
+        let glass = &materials[1];
+        assert_eq!(glass.diffuse_color, None);
+        let dissolve = glass.dissolve.expect("Tr should set dissolve");
+        assert!((dissolve - 0.1).abs() < 1e-6);
+    }
+
+    #[test]
+    fn reports_invalid_values_with_line_numbers() {
+        let cases = [
+            ("newmtl A\nKd 0.5 0.5\n", "MTL line 2"),
+            ("newmtl A\nKd red green blue\n", "MTL line 2"),
+            ("newmtl A\n\nKs -1 0 0\n", "MTL line 3"),
+            ("newmtl A\nd 1.5\n", "MTL line 2"),
+            ("newmtl A\nNs -4\n", "MTL line 2"),
+            ("newmtl A\nillum 11\n", "MTL line 2"),
+            ("newmtl A\nKd spectral file.rfl\n", "MTL line 2"),
+            ("newmtl A\nNi\n", "Line 2"),
+        ];
+
+        for (mtl_data, expected_prefix) in cases {
+            let error = load_fixture("scop_mtl_invalid", mtl_data)
+                .expect_err("invalid MTL value should fail");
+            assert!(
+                error.starts_with(expected_prefix),
+                "unexpected error for {:?}: {}",
+                mtl_data,
+                error
+            );
+        }
+    }
+}
@@ -4,13 +4,56 @@ use std::io::{BufRead, BufReader};
 use std::path::Path;
 
 use super::index::{FaceVertex, parse_f32_component, parse_face_vertex};
+use super::normals::generate_missing_normals;
 use super::parse_mtl::load_mtl;
 use super::triangulate::{TriangulationOutcome, triangulate_face};
 use super::types::{ObjLoadOptions, ObjMeshData, ObjObjectData, ObjSceneData};
 
-type MaterialFaces = HashMap<Option<String>, Vec<Vec<FaceVertex>>>;
+struct PendingFace {
+    corners: Vec<FaceVertex>,
+    smoothing_group: u32,
+}
+
+struct MaterialRun {
+    material: Option<String>,
+    faces: Vec<PendingFace>,
+}
+
+#[derive(Default)]
+struct PendingObject {
+    name: String,
+    groups: Vec<String>,
+    runs: Vec<MaterialRun>,
+}
+
+impl PendingObject {
+    fn push_face(&mut self, material: &Option<String>, groups: &[String], face: PendingFace) {
+        for group in groups {
+            if !self.groups.contains(group) {
+                self.groups.push(group.clone());
+            }
+        }
+
+        match self.runs.last_mut() {
+            Some(run) if run.material == *material => run.faces.push(face),
+            _ => self.runs.push(MaterialRun {
+                material: material.clone(),
+                faces: vec![face],
+            }),
+        }
+    }
+}
 
 pub fn load(path: &Path, options: &ObjLoadOptions) -> Result<ObjSceneData, String> {
+    if let Some(crease_angle) = options.crease_angle_degrees
+        && !(0.0..=180.0).contains(&crease_angle)
+    {
+        return Err(format!(
+            "OBJ load options: crease angle must be between 0 and 180 degrees, was {}",
+            crease_angle
+        ));
+    }
+
     let file = File::open(path)
         .map_err(|e| format!("Failed to open OBJ file '{}': {}", path.display(), e))?;
     let reader = BufReader::new(file);
@@ -18,13 +61,14 @@ pub fn load(path: &Path, options: &ObjLoadOptions) -> Result<ObjSceneData, Strin
     let mut positions: Vec<[f32; 3]> = Vec::new();
     let mut normals: Vec<[f32; 3]> = Vec::new();
     let mut texcoords: Vec<[f32; 2]> = Vec::new();
+    let mut vertex_colors: Vec<Option<[f32; 3]>> = Vec::new();
 
     let mut current_material: Option<String> = None;
-    let mut material_faces: MaterialFaces = HashMap::new();
+    let mut current_groups: Vec<String> = Vec::new();
+    let mut current_smoothing_group = 0u32;
+    let mut pending_objects: Vec<PendingObject> = vec![PendingObject::default()];
     let mut mtl_files: Vec<String> = Vec::new();
 
-    let _ = options.single_index;
-
     for (line_number, line_result) in reader.lines().enumerate() {
         let line_number = line_number + 1;
         let line =
@@ -52,6 +96,23 @@ pub fn load(path: &Path, options: &ObjLoadOptions) -> Result<ObjSceneData, Strin
                 let y = parse_f32_component(parts[2], line_number, "vertex y")?;
                 let z = parse_f32_component(parts[3], line_number, "vertex z")?;
                 positions.push([x, y, z]);
+
+                // `v x y z r g b`: vertex color extension written by many exporters
+                let extra: Vec<&str> = parts[4..]
+                    .iter()
+                    .take_while(|token| !token.starts_with('#'))
+                    .copied()
+                    .collect();
+                let color = if extra.len() >= 3 {
+                    Some([
+                        parse_f32_component(extra[0], line_number, "vertex red")?,
+                        parse_f32_component(extra[1], line_number, "vertex green")?,
+                        parse_f32_component(extra[2], line_number, "vertex blue")?,
+                    ])
+                } else {
+                    None
+                };
+                vertex_colors.push(color);
             }
             "vn" => {
                 if parts.len() < 4 {
@@ -106,24 +167,17 @@ pub fn load(path: &Path, options: &ObjLoadOptions) -> Result<ObjSceneData, Strin
                     ));
                 }
 
+                let mut triangles = Vec::new();
                 if options.triangulate && face.len() > 3 {
                     match triangulate_face(&face, &positions) {
-                        TriangulationOutcome::Robust(triangles) => {
-                            for [a, b, c] in triangles {
-                                let tri = vec![face[a], face[b], face[c]];
-                                material_faces
-                                    .entry(current_material.clone())
-                                    .or_default()
-                                    .push(tri);
+                        TriangulationOutcome::Robust(local_triangles) => {
+                            for [a, b, c] in local_triangles {
+                                triangles.push(vec![face[a], face[b], face[c]]);
                             }
                         }
                         TriangulationOutcome::FallbackFan => {
                             for i in 1..face.len() - 1 {
-                                let tri = vec![face[0], face[i], face[i + 1]];
-                                material_faces
-                                    .entry(current_material.clone())
-                                    .or_default()
-                                    .push(tri);
+                                triangles.push(vec![face[0], face[i], face[i + 1]]);
                             }
                         }
                     }
@@ -134,12 +188,57 @@ pub fn load(path: &Path, options: &ObjLoadOptions) -> Result<ObjSceneData, Strin
                             line_number
                         ));
                     }
-                    material_faces
-                        .entry(current_material.clone())
-                        .or_default()
-                        .push(face);
+                    triangles.push(face);
+                }
+
+                let object = pending_objects
+                    .last_mut()
+                    .expect("pending objects always has a current object");
+                for corners in triangles {
+                    let face = PendingFace {
+                        corners,
+                        smoothing_group: current_smoothing_group,
+                    };
+                    object.push_face(&current_material, &current_groups, face);
                 }
             }
+            "o" => {
+                let object_name =
+                    collect_directive_values(parts.as_slice(), "o", line_number)?.join(" ");
+                match pending_objects.last_mut() {
+                    Some(object) if object.runs.is_empty() => {
+                        *object = PendingObject {
+                            name: object_name,
+                            ..Default::default()
+                        };
+                    }
+                    _ => pending_objects.push(PendingObject {
+                        name: object_name,
+                        ..Default::default()
+                    }),
+                }
+            }
+            "s" => {
+                let value = collect_directive_values(parts.as_slice(), "s", line_number)?[0];
+                current_smoothing_group = if value.eq_ignore_ascii_case("off") {
+                    0
+                } else {
+                    value.parse::<u32>().map_err(|error| {
+                        format!(
+                            "OBJ line {}: invalid smoothing group '{}': {}",
+                            line_number, value, error
+                        )
+                    })?
+                };
+            }
+            "g" => {
+                current_groups = parts
+                    .iter()
+                    .skip(1)
+                    .take_while(|token| !token.starts_with('#'))
+                    .map(|token| token.to_string())
+                    .collect();
+            }
             "usemtl" => {
                 let material_name =
                     collect_directive_values(parts.as_slice(), "usemtl", line_number)?
@@ -178,55 +277,122 @@ pub fn load(path: &Path, options: &ObjLoadOptions) -> Result<ObjSceneData, Strin
 
     let mut objects = Vec::new();
 
-    for (mat_name, mat_faces) in material_faces {
-        let material_id = mat_name
-            .as_ref()
-            .and_then(|name| material_map.get(name).copied());
+    for mut pending in pending_objects {
+        if pending.runs.is_empty() {
+            continue;
+        }
 
-        let mut mesh = ObjMeshData {
-            material_id,
-            ..Default::default()
-        };
+        let (mut faces, smoothing_groups): (Vec<&mut [FaceVertex]>, Vec<u32>) = pending
+            .runs
+            .iter_mut()
+            .flat_map(|run| run.faces.iter_mut())
+            .map(|face| (face.corners.as_mut_slice(), face.smoothing_group))
+            .unzip();
+        generate_missing_normals(
+            &mut faces,
+            &smoothing_groups,
+            &positions,
+            &mut normals,
+            options.crease_angle_degrees,
+        );
+
+        let mut meshes = Vec::with_capacity(pending.runs.len());
+        for run in &pending.runs {
+            let material_id = run
+                .material
+                .as_ref()
+                .and_then(|name| material_map.get(name).copied());
+
+            meshes.push(assemble_mesh(
+                &run.faces,
+                &positions,
+                &vertex_colors,
+                &normals,
+                &texcoords,
+                material_id,
+                options.single_index,
+            )?);
+        }
 
-        let mut next_index = 0u32;
-        let mut vertex_texcoords: Vec<Option<[f32; 2]>> = Vec::new();
+        objects.push(ObjObjectData {
+            name: pending.name,
+            groups: pending.groups,
+            meshes,
+        });
+    }
 
-        for face in mat_faces {
-            if face.len() != 3 {
-                return Err(
-                    "Internal OBJ loader error: non-triangulated face reached mesh assembly"
-                        .to_string(),
-                );
+    Ok(ObjSceneData { objects, materials })
+}
+
+fn assemble_mesh(
+    faces: &[PendingFace],
+    positions: &[[f32; 3]],
+    vertex_colors: &[Option<[f32; 3]>],
+    normals: &[[f32; 3]],
+    texcoords: &[[f32; 2]],
+    material_id: Option<usize>,
+    single_index: bool,
+) -> Result<ObjMeshData, String> {
+    let mut mesh = ObjMeshData {
+        material_id,
+        ..Default::default()
+    };
+
+    // With `single_index`, every distinct (position, texcoord, normal) triple becomes one shared
+    // vertex; otherwise each triangle corner gets its own vertex.
+    let mut welded: HashMap<FaceVertex, u32> = HashMap::new();
+    let mut vertex_texcoords: Vec<Option<[f32; 2]>> = Vec::new();
+    let mut colors: Vec<Option<[f32; 3]>> = Vec::new();
+
+    for face in faces {
+        if face.corners.len() != 3 {
+            return Err(
+                "Internal OBJ loader error: non-triangulated face reached mesh assembly"
+                    .to_string(),
+            );
+        }
+
+        for &corner in &face.corners {
+            if single_index && let Some(&index) = welded.get(&corner) {
+                mesh.indices.push(index);
+                continue;
             }
 
-            for &(pos_idx, tex_idx, norm_idx) in &face {
-                let position = positions[pos_idx];
-                mesh.positions.extend_from_slice(&position);
+            let index = u32::try_from(vertex_texcoords.len()).map_err(|_| {
+                "OBJ mesh exceeds the maximum number of vertices addressable by u32 indices"
+                    .to_string()
+            })?;
 
-                let normal = norm_idx
-                    .map(|normal_idx| normals[normal_idx])
-                    .unwrap_or([0.0, 0.0, 0.0]);
-                mesh.normals.extend_from_slice(&normal);
+            let (pos_idx, tex_idx, norm_idx) = corner;
+            mesh.positions.extend_from_slice(&positions[pos_idx]);
 
-                let texcoord = tex_idx.map(|texcoord_idx| texcoords[texcoord_idx]);
-                vertex_texcoords.push(texcoord);
+            let normal = norm_idx
+                .map(|normal_idx| normals[normal_idx])
+                .unwrap_or([0.0, 0.0, 0.0]);
+            mesh.normals.extend_from_slice(&normal);
 
-                mesh.indices.push(next_index);
-                next_index += 1;
-            }
-        }
+            vertex_texcoords.push(tex_idx.map(|texcoord_idx| texcoords[texcoord_idx]));
+            colors.push(vertex_colors[pos_idx]);
 
-        if vertex_texcoords.iter().all(|uv| uv.is_some()) {
-            for uv in vertex_texcoords {
-                let uv = uv.expect("checked above");
-                mesh.texcoords.extend_from_slice(&uv);
+            if single_index {
+                welded.insert(corner, index);
             }
+            mesh.indices.push(index);
         }
+    }
 
-        objects.push(ObjObjectData { mesh });
+    if vertex_texcoords.iter().all(|uv| uv.is_some()) {
+        for uv in vertex_texcoords {
+            let uv = uv.expect("checked above");
+            mesh.texcoords.extend_from_slice(&uv);
+        }
+    }
+    // Like texcoords, colors are kept only when every vertex of the mesh has one
+    if colors.iter().all(|color| color.is_some()) {
+        mesh.colors = colors.into_iter().flatten().flatten().collect();
     }
 
-    Ok(ObjSceneData { objects, materials })
+    Ok(mesh)
 }
 
 fn collect_directive_values<'a>(
@@ -297,12 +463,13 @@ f 1 2 3 # triangle
             &ObjLoadOptions {
                 triangulate: true,
                 single_index: false,
+                crease_angle_degrees: None,
             },
         )
         .expect("OBJ with inline face comment should parse");
 
         assert_eq!(scene.objects.len(), 1);
-        assert_eq!(scene.objects[0].mesh.indices, vec![0, 1, 2]);
+        assert_eq!(scene.objects[0].meshes[0].indices, vec![0, 1, 2]);
 
         fs::remove_dir_all(&dir).expect("failed to cleanup temp directory");
     }
@@ -340,6 +507,7 @@ f 1 2 3
             &ObjLoadOptions {
                 triangulate: true,
                 single_index: false,
+                crease_angle_degrees: None,
             },
         )
         .expect("OBJ with multiple mtllib files should parse");
@@ -354,7 +522,7 @@ f 1 2 3
             .position(|mat| mat.name == "MatB")
             .expect("MatB should be loaded");
         assert_eq!(scene.objects.len(), 1);
-        assert_eq!(scene.objects[0].mesh.material_id, Some(mat_b_index));
+        assert_eq!(scene.objects[0].meshes[0].material_id, Some(mat_b_index));
 
         fs::remove_dir_all(&dir).expect("failed to cleanup temp directory");
     }
@@ -378,12 +546,13 @@ f 1 2 3 4 5
             &ObjLoadOptions {
                 triangulate: true,
                 single_index: false,
+                crease_angle_degrees: None,
             },
         )
         .expect("concave face OBJ should parse and triangulate");
 
         assert_eq!(scene.objects.len(), 1);
-        assert_eq!(scene.objects[0].mesh.indices.len(), 9);
+        assert_eq!(scene.objects[0].meshes[0].indices.len(), 9);
 
         fs::remove_dir_all(&dir).expect("failed to cleanup temp directory");
     }
@@ -406,12 +575,310 @@ f 1 2 3 4
             &ObjLoadOptions {
                 triangulate: true,
                 single_index: false,
+                crease_angle_degrees: None,
             },
         )
         .expect("non-coplanar face OBJ should parse and triangulate");
 
         assert_eq!(scene.objects.len(), 1);
-        assert_eq!(scene.objects[0].mesh.indices.len(), 6);
+        assert_eq!(scene.objects[0].meshes[0].indices.len(), 6);
+
+        fs::remove_dir_all(&dir).expect("failed to cleanup temp directory");
+    }
+
+    fn expand_corners(mesh: &crate::loaders::obj::ObjMeshData) -> Vec<Vec<f32>> {
+        mesh.indices
+            .iter()
+            .map(|&index| {
+                let i = index as usize;
+                let mut corner = Vec::new();
+                corner.extend_from_slice(&mesh.positions[i * 3..i * 3 + 3]);
+                corner.extend_from_slice(&mesh.normals[i * 3..i * 3 + 3]);
+                if !mesh.texcoords.is_empty() {
+                    corner.extend_from_slice(&mesh.texcoords[i * 2..i * 2 + 2]);
+                }
+                corner
+            })
+            .collect()
+    }
+
+    #[test]
+    fn single_index_welds_shared_vertices_and_matches_flat_expansion() {
+        let dir = unique_temp_dir("scop_obj_single_index");
+        let obj_path = dir.join("quad_strip.obj");
+        let obj_data = "\
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+v 2 0 0
+v 2 1 0
+vt 0 0
+vt 1 0
+vt 1 1
+vt 0 1
+vn 0 0 1
+f 1/1/1 2/2/1 3/3/1 4/4/1
+f 2/1/1 5/2/1 6/3/1 3/4/1
+";
+        fs::write(&obj_path, obj_data).expect("failed to write OBJ fixture");
+
+        let flat = load(
+            &obj_path,
+            &ObjLoadOptions {
+                triangulate: true,
+                single_index: false,
+                crease_angle_degrees: None,
+            },
+        )
+        .expect("flat OBJ load should succeed");
+        let welded = load(
+            &obj_path,
+            &ObjLoadOptions {
+                triangulate: true,
+                single_index: true,
+                crease_angle_degrees: None,
+            },
+        )
+        .expect("single-index OBJ load should succeed");
+
+        let flat_mesh = &flat.objects[0].meshes[0];
+        let welded_mesh = &welded.objects[0].meshes[0];
+
+        assert_eq!(flat_mesh.positions.len(), 12 * 3);
+        assert_eq!(flat_mesh.indices, (0..12).collect::<Vec<u32>>());
+
+        // Vertices 2 and 3 are shared between the quads but use different UVs there,
+        // so only corners within each quad weld: 4 + 4 unique triples.
+        assert_eq!(welded_mesh.positions.len(), 8 * 3);
+        assert_eq!(welded_mesh.normals.len(), 8 * 3);
+        assert_eq!(welded_mesh.texcoords.len(), 8 * 2);
+        assert_eq!(welded_mesh.indices.len(), 12);
+        assert!(welded_mesh.indices.iter().all(|&i| i < 8));
+
+        assert_eq!(expand_corners(welded_mesh), expand_corners(flat_mesh));
+
+        fs::remove_dir_all(&dir).expect("failed to cleanup temp directory");
+    }
+
+    #[test]
+    fn single_index_welds_position_only_faces() {
+        let dir = unique_temp_dir("scop_obj_single_index_positions");
+        let obj_path = dir.join("tetra.obj");
+        let obj_data = "\
+v 0 0 0
+v 1 0 0
+v 0 1 0
+v 0 0 1
+s 1
+f 1 3 2
+f 1 2 4
+f 2 3 4
+f 3 1 4
+";
+        fs::write(&obj_path, obj_data).expect("failed to write OBJ fixture");
+
+        let options = |single_index| ObjLoadOptions {
+            triangulate: true,
+            single_index,
+            crease_angle_degrees: None,
+        };
+        let flat = load(&obj_path, &options(false)).expect("flat OBJ load should succeed");
+        let welded = load(&obj_path, &options(true)).expect("welded OBJ load should succeed");
+
+        let welded_mesh = &welded.objects[0].meshes[0];
+        assert_eq!(welded_mesh.positions.len(), 4 * 3);
+        assert_eq!(
+            welded_mesh.indices,
+            vec![0, 1, 2, 0, 2, 3, 2, 1, 3, 1, 0, 3]
+        );
+        assert!(welded_mesh.texcoords.is_empty());
+        assert_eq!(
+            expand_corners(welded_mesh),
+            expand_corners(&flat.objects[0].meshes[0])
+        );
+
+        fs::remove_dir_all(&dir).expect("failed to cleanup temp directory");
+    }
+
+    #[test]
+    fn splits_objects_and_material_runs_in_file_order() {
+        let dir = unique_temp_dir("scop_obj_objects_groups");
+        let obj_path = dir.join("objects.obj");
+        fs::write(
+            dir.join("objects.mtl"),
+            "\
+newmtl Red
+newmtl Blue
+",
+        )
+        .expect("failed to write MTL fixture");
+        let obj_data = "\
+mtllib objects.mtl
+v 0 0 0
+v 1 0 0
+v 0 1 0
+v 1 1 0
+o Body
+g hull frame
+usemtl Red
+f 1 2 3
+usemtl Blue
+f 2 4 3
+usemtl Red
+f 1 2 4
+o Lid # top part
+g cap
+usemtl Red
+f 1 2 3
+g cap rim
+f 2 4 3
+";
+        fs::write(&obj_path, obj_data).expect("failed to write OBJ fixture");
+
+        let scene = load(
+            &obj_path,
+            &ObjLoadOptions {
+                triangulate: true,
+                single_index: false,
+                crease_angle_degrees: None,
+            },
+        )
+        .expect("OBJ with objects and groups should parse");
+
+        let red = Some(0);
+        let blue = Some(1);
+
+        assert_eq!(scene.objects.len(), 2);
+
+        let body = &scene.objects[0];
+        assert_eq!(body.name, "Body");
+        assert_eq!(body.groups, vec!["hull", "frame"]);
+        let body_materials: Vec<Option<usize>> =
+            body.meshes.iter().map(|mesh| mesh.material_id).collect();
+        assert_eq!(body_materials, vec![red, blue, red]);
+
+        let lid = &scene.objects[1];
+        assert_eq!(lid.name, "Lid");
+        assert_eq!(lid.groups, vec!["cap", "rim"]);
+        assert_eq!(lid.meshes.len(), 1);
+        assert_eq!(lid.meshes[0].material_id, red);
+        assert_eq!(lid.meshes[0].indices.len(), 6);
+
+        fs::remove_dir_all(&dir).expect("failed to cleanup temp directory");
+    }
+
+    #[test]
+    fn faces_before_any_object_directive_form_an_unnamed_object() {
+        let dir = unique_temp_dir("scop_obj_unnamed_object");
+        let obj_path = dir.join("unnamed.obj");
+        let obj_data = "\
+v 0 0 0
+v 1 0 0
+v 0 1 0
+f 1 2 3
+o Named
+f 3 2 1
+o Empty
+";
+        fs::write(&obj_path, obj_data).expect("failed to write OBJ fixture");
+
+        let scene = load(
+            &obj_path,
+            &ObjLoadOptions {
+                triangulate: true,
+                single_index: false,
+                crease_angle_degrees: None,
+            },
+        )
+        .expect("OBJ with an implicit object should parse");
+
+        let names: Vec<&str> = scene.objects.iter().map(|obj| obj.name.as_str()).collect();
+        assert_eq!(names, vec!["", "Named"]);
+        assert!(scene.objects.iter().all(|obj| obj.groups.is_empty()));
+        assert!(
+            scene
+                .objects
+                .iter()
+                .all(|obj| obj.meshes.len() == 1 && obj.meshes[0].material_id.is_none())
+        );
+
+        fs::remove_dir_all(&dir).expect("failed to cleanup temp directory");
+    }
+
+    #[test]
+    fn generates_normals_per_smoothing_group() {
+        let dir = unique_temp_dir("scop_obj_generated_normals");
+        let obj_path = dir.join("folded.obj");
+        let obj_data = "\
+v 0 0 0
+v 1 0 0
+v 0 1 0
+v 0 0 1
+s off
+f 1 2 3
+s 1
+f 2 1 4
+f 1 2 3
+";
+        fs::write(&obj_path, obj_data).expect("failed to write OBJ fixture");
+
+        let scene = load(
+            &obj_path,
+            &ObjLoadOptions {
+                triangulate: true,
+                single_index: true,
+                crease_angle_degrees: None,
+            },
+        )
+        .expect("OBJ without vn lines should parse");
+
+        let mesh = &scene.objects[0].meshes[0];
+        let normal_at = |corner: usize| {
+            let i = mesh.indices[corner] as usize;
+            [
+                mesh.normals[i * 3],
+                mesh.normals[i * 3 + 1],
+                mesh.normals[i * 3 + 2],
+            ]
+        };
+
+        // Flat face: every corner carries the face normal.
+        for corner in 0..3 {
+            assert_eq!(normal_at(corner), [0.0, 0.0, 1.0]);
+        }
+        // Smoothed faces share the averaged normal along their common edge.
+        let half = std::f32::consts::FRAC_1_SQRT_2;
+        let shared = normal_at(3);
+        assert!((shared[1] - half).abs() < 1e-5 && (shared[2] - half).abs() < 1e-5);
+        assert_eq!(mesh.indices[3], mesh.indices[7]);
+
+        fs::remove_dir_all(&dir).expect("failed to cleanup temp directory");
+    }
+
+    #[test]
+    fn rejects_invalid_smoothing_group_and_crease_angle() {
+        let dir = unique_temp_dir("scop_obj_invalid_smoothing");
+        let obj_path = dir.join("invalid.obj");
+        fs::write(&obj_path, "v 0 0 0\ns smooth\n").expect("failed to write OBJ fixture");
+
+        let error = load(&obj_path, &ObjLoadOptions::default())
+            .expect_err("invalid smoothing group should fail");
+        assert!(error.contains("OBJ line 2"), "unexpected error: {}", error);
+
+        let error = load(
+            &obj_path,
+            &ObjLoadOptions {
+                crease_angle_degrees: Some(200.0),
+                ..Default::default()
+            },
+        )
+        .expect_err("out of range crease angle should fail");
+        assert!(
+            error.contains("crease angle"),
+            "unexpected error: {}",
+            error
+        );
 
         fs::remove_dir_all(&dir).expect("failed to cleanup temp directory");
     }
@@ -0,0 +1,262 @@
+use super::types::ObjTextureMap;
+
+/// Parses a `map_*` / `bump` directive line: leading texture options followed by the file
+/// name, e.g. `map_Kd -s 2 2 1 -clamp on wood.bmp`. The file name is taken verbatim from the
+/// line, so runs of spaces inside it survive, and a trailing `# comment` is dropped.
+pub fn parse_texture_map(line: &str, line_number: usize) -> Result<ObjTextureMap, String> {
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    let directive = parts[0];
+    let tokens = &parts[1..];
+    let mut map = ObjTextureMap::default();
+    let mut cursor = 0;
+
+    while cursor < tokens.len() && tokens[cursor].starts_with('-') {
+        let option = tokens[cursor];
+        cursor += 1;
+
+        match option {
+            "-s" => map.scale = parse_uvw(tokens, &mut cursor, map.scale, option, line_number)?,
+            "-o" => map.offset = parse_uvw(tokens, &mut cursor, map.offset, option, line_number)?,
+            "-t" => {
+                map.turbulence =
+                    parse_uvw(tokens, &mut cursor, map.turbulence, option, line_number)?
+            }
+            "-clamp" => map.clamp = parse_on_off(tokens, &mut cursor, option, line_number)?,
+            "-blendu" => map.blend_u = parse_on_off(tokens, &mut cursor, option, line_number)?,
+            "-blendv" => map.blend_v = parse_on_off(tokens, &mut cursor, option, line_number)?,
+            "-cc" => map.color_correction = parse_on_off(tokens, &mut cursor, option, line_number)?,
+            "-bm" => map.bump_multiplier = parse_float(tokens, &mut cursor, option, line_number)?,
+            "-boost" => map.boost = Some(parse_float(tokens, &mut cursor, option, line_number)?),
+            "-mm" => {
+                map.value_base = parse_float(tokens, &mut cursor, option, line_number)?;
+                map.value_gain = parse_float(tokens, &mut cursor, option, line_number)?;
+            }
+            "-texres" => {
+                let raw = next_token(tokens, &mut cursor, option, line_number)?;
+                let resolution = raw.parse::<u32>().map_err(|_| {
+                    format!(
+                        "MTL line {}: texture option '{}' expects a positive integer, found '{}'",
+                        line_number, option, raw
+                    )
+                })?;
+                map.resolution = Some(resolution);
+            }
+            "-imfchan" => {
+                let raw = next_token(tokens, &mut cursor, option, line_number)?;
+                let channel = match raw {
+                    "r" | "g" | "b" | "m" | "l" | "z" => raw.chars().next(),
+                    _ => None,
+                }
+                .ok_or_else(|| {
+                    format!(
+                        "MTL line {}: texture option '{}' expects one of r, g, b, m, l, z, found '{}'",
+                        line_number, option, raw
+                    )
+                })?;
+                map.channel = Some(channel);
+            }
+            // Only meaningful for reflection maps; accepted so the file name is found.
+            "-type" => {
+                next_token(tokens, &mut cursor, option, line_number)?;
+            }
+            _ => {
+                return Err(format!(
+                    "MTL line {}: unsupported texture option '{}' in '{}'",
+                    line_number, option, directive
+                ));
+            }
+        }
+    }
+
+    // Skip the directive and option tokens in the line itself to keep the name's spacing
+    let mut rest = line.trim_start();
+    for token in &parts[..=cursor] {
+        rest = rest[token.len()..].trim_start();
+    }
+    let path = strip_comment(rest);
+    if path.is_empty() {
+        return Err(format!(
+            "Line {}: directive '{}' is missing a required value",
+            line_number, directive
+        ));
+    }
+    map.path = path.to_string();
+
+    Ok(map)
+}
+
+/// Cuts a trailing comment, which starts at a `#` beginning a token; other `#`s belong to the
+/// file name.
+fn strip_comment(value: &str) -> &str {
+    let end = value
+        .char_indices()
+        .find(|&(index, c)| {
+            c == '#' && (index == 0 || value[..index].ends_with(char::is_whitespace))
+        })
+        .map_or(value.len(), |(index, _)| index);
+    value[..end].trim_end()
+}
+
+fn next_token<'a>(
+    tokens: &[&'a str],
+    cursor: &mut usize,
+    option: &str,
+    line_number: usize,
+) -> Result<&'a str, String> {
+    let token = tokens.get(*cursor).copied().ok_or_else(|| {
+        format!(
+            "MTL line {}: texture option '{}' is missing a value",
+            line_number, option
+        )
+    })?;
+    *cursor += 1;
+    Ok(token)
+}
+
+fn parse_float(
+    tokens: &[&str],
+    cursor: &mut usize,
+    option: &str,
+    line_number: usize,
+) -> Result<f32, String> {
+    let raw = next_token(tokens, cursor, option, line_number)?;
+    raw.parse::<f32>()
+        .ok()
+        .filter(|value| value.is_finite())
+        .ok_or_else(|| {
+            format!(
+                "MTL line {}: texture option '{}' expects a number, found '{}'",
+                line_number, option, raw
+            )
+        })
+}
+
+fn parse_on_off(
+    tokens: &[&str],
+    cursor: &mut usize,
+    option: &str,
+    line_number: usize,
+) -> Result<bool, String> {
+    match next_token(tokens, cursor, option, line_number)? {
+        "on" => Ok(true),
+        "off" => Ok(false),
+        other => Err(format!(
+            "MTL line {}: texture option '{}' expects 'on' or 'off', found '{}'",
+            line_number, option, other
+        )),
+    }
+}
+
+/// Reads `u [v [w]]`; omitted components keep their defaults, as in the MTL specification.
+fn parse_uvw(
+    tokens: &[&str],
+    cursor: &mut usize,
+    defaults: [f32; 3],
+    option: &str,
+    line_number: usize,
+) -> Result<[f32; 3], String> {
+    let mut values = defaults;
+    values[0] = parse_float(tokens, cursor, option, line_number)?;
+
+    for value in values.iter_mut().skip(1) {
+        // Stop before the file name: only plain numbers are consumed, and at least one token
+        // must remain for the path.
+        match tokens.get(*cursor) {
+            Some(raw) if *cursor + 1 < tokens.len() => match raw.parse::<f32>() {
+                Ok(parsed) if parsed.is_finite() => {
+                    *value = parsed;
+                    *cursor += 1;
+                }
+                _ => break,
+            },
+            _ => break,
+        }
+    }
+
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_texture_map;
+
+    fn parse(line: &str) -> Result<crate::loaders::obj::ObjTextureMap, String> {
+        parse_texture_map(line, 7)
+    }
+
+    #[test]
+    fn plain_file_name_uses_defaults() {
+        let map = parse("map_Kd textures/wood floor.bmp").expect("plain map should parse");
+        assert_eq!(map.path, "textures/wood floor.bmp");
+        assert_eq!(map.scale, [1.0, 1.0, 1.0]);
+        assert_eq!(map.offset, [0.0, 0.0, 0.0]);
+        assert!(!map.clamp);
+        assert!(map.blend_u && map.blend_v);
+        assert_eq!(map.bump_multiplier, 1.0);
+        assert_eq!((map.value_base, map.value_gain), (0.0, 1.0));
+    }
+
+    #[test]
+    fn file_name_keeps_its_spacing_and_drops_trailing_comments() {
+        let map = parse("map_Kd -clamp on  textures/old  oak#2.bmp   # walls")
+            .expect("spaced file name should parse");
+        assert_eq!(map.path, "textures/old  oak#2.bmp");
+        assert!(map.clamp);
+
+        assert!(parse("map_Kd -clamp on # no file").is_err());
+    }
+
+    #[test]
+    fn parses_standard_options_before_file_name() {
+        let map = parse(
+            "map_Kd -s 2 2 1 -o 0.5 0.25 -clamp on -blendu off -blendv off -mm 0.1 0.8 -boost 1.5 -texres 512 -cc on wood.bmp",
+        )
+        .expect("map with options should parse");
+
+        assert_eq!(map.path, "wood.bmp");
+        assert_eq!(map.scale, [2.0, 2.0, 1.0]);
+        assert_eq!(map.offset, [0.5, 0.25, 0.0]);
+        assert!(map.clamp);
+        assert!(!map.blend_u && !map.blend_v);
+        assert_eq!((map.value_base, map.value_gain), (0.1, 0.8));
+        assert_eq!(map.boost, Some(1.5));
+        assert_eq!(map.resolution, Some(512));
+        assert!(map.color_correction);
+    }
+
+    #[test]
+    fn parses_bump_options() {
+        let map = parse("bump -bm 0.3 -imfchan l -s 4 normal.bmp").expect("bump map should parse");
+        assert_eq!(map.path, "normal.bmp");
+        assert_eq!(map.bump_multiplier, 0.3);
+        assert_eq!(map.channel, Some('l'));
+        assert_eq!(map.scale, [4.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn numeric_file_name_is_not_consumed_as_option_value() {
+        let map = parse("map_Kd -s 2 2 1").expect("numeric file name should parse");
+        assert_eq!(map.scale, [2.0, 2.0, 1.0]);
+        assert_eq!(map.path, "1");
+    }
+
+    #[test]
+    fn rejects_malformed_options() {
+        for line in [
+            "map_Kd -clamp maybe wood.bmp",
+            "map_Kd -bm wood.bmp",
+            "map_Kd -unknown 1 wood.bmp",
+            "map_Kd -s",
+            "map_Kd -texres -1 wood.bmp",
+            "map_Kd",
+        ] {
+            let error = parse(line).expect_err("malformed map should fail");
+            assert!(
+                error.contains("line 7") || error.contains("Line 7"),
+                "unexpected error for '{}': {}",
+                line,
+                error
+            );
+        }
+    }
+}
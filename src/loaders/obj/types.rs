@@ -1,32 +1,108 @@
-#[derive(Default, Clone)]
+#[derive(Debug, Default, Clone)]
 pub struct ObjLoadOptions {
     pub triangulate: bool,
     pub single_index: bool,
+    /// Maximum angle between neighboring faces that still get smoothed together when normals
+    /// are generated. `None` smooths everything within a smoothing group.
+    pub crease_angle_degrees: Option<f32>,
 }
 
-#[derive(Default, Clone)]
+#[derive(Debug, Clone)]
+pub struct ObjWriteOptions {
+    /// Decimal places written for every float; trailing zeros are dropped.
+    pub precision: usize,
+}
+
+impl Default for ObjWriteOptions {
+    fn default() -> Self {
+        Self { precision: 6 }
+    }
+}
+
+#[derive(Debug, Default, Clone)]
 pub struct ObjMeshData {
     pub positions: Vec<f32>,
     pub normals: Vec<f32>,
     pub texcoords: Vec<f32>,
+    /// Per-vertex RGB in `[0, 1]`; empty when the source has no vertex colors.
+    pub colors: Vec<f32>,
     pub indices: Vec<u32>,
     pub material_id: Option<usize>,
 }
 
-#[derive(Default, Clone)]
+#[derive(Debug, Default, Clone)]
 pub struct ObjObjectData {
-    pub mesh: ObjMeshData,
+    pub name: String,
+    pub groups: Vec<String>,
+    pub meshes: Vec<ObjMeshData>,
 }
 
-#[derive(Default, Clone)]
+#[derive(Debug, Default, Clone)]
 pub struct ObjMaterialData {
     pub name: String,
-    pub diffuse_texture: Option<String>,
-    pub specular_texture: Option<String>,
-    pub normal_texture: Option<String>,
+    pub ambient_color: Option<[f32; 3]>,
+    pub diffuse_color: Option<[f32; 3]>,
+    pub specular_color: Option<[f32; 3]>,
+    pub emissive_color: Option<[f32; 3]>,
+    pub shininess: Option<f32>,
+    pub optical_density: Option<f32>,
+    /// Opacity in `[0, 1]`, from `d` or derived from `Tr` as `1 - Tr`.
+    pub dissolve: Option<f32>,
+    pub illumination_model: Option<u8>,
+    pub diffuse_texture: Option<ObjTextureMap>,
+    pub specular_texture: Option<ObjTextureMap>,
+    pub normal_texture: Option<ObjTextureMap>,
+}
+
+/// A texture reference from an MTL `map_*` / `bump` directive together with its options.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjTextureMap {
+    pub path: String,
+    /// `-s u v w`: UV scale.
+    pub scale: [f32; 3],
+    /// `-o u v w`: UV offset.
+    pub offset: [f32; 3],
+    /// `-t u v w`: turbulence.
+    pub turbulence: [f32; 3],
+    /// `-clamp on|off`: clamp UVs to `[0, 1]` instead of repeating.
+    pub clamp: bool,
+    pub blend_u: bool,
+    pub blend_v: bool,
+    /// `-cc on|off`: color correction.
+    pub color_correction: bool,
+    /// `-bm mult`: bump multiplier.
+    pub bump_multiplier: f32,
+    /// `-mm base gain`: value range remapping.
+    pub value_base: f32,
+    pub value_gain: f32,
+    pub boost: Option<f32>,
+    pub resolution: Option<u32>,
+    /// `-imfchan r|g|b|m|l|z`: channel used for scalar or bump textures.
+    pub channel: Option<char>,
+}
+
+impl Default for ObjTextureMap {
+    fn default() -> Self {
+        Self {
+            path: String::new(),
+            scale: [1.0, 1.0, 1.0],
+            offset: [0.0, 0.0, 0.0],
+            turbulence: [0.0, 0.0, 0.0],
+            clamp: false,
+            blend_u: true,
+            blend_v: true,
+            color_correction: false,
+            bump_multiplier: 1.0,
+            value_base: 0.0,
+            value_gain: 1.0,
+            boost: None,
+            resolution: None,
+            channel: None,
+        }
+    }
 }
 
-#[derive(Default, Clone)]
+#[derive(Debug, Default, Clone)]
 pub struct ObjSceneData {
     pub objects: Vec<ObjObjectData>,
     pub materials: Vec<ObjMaterialData>,
@@ -0,0 +1,157 @@
+use std::fmt::Write;
+
+use super::types::{ObjMaterialData, ObjTextureMap, ObjWriteOptions};
+
+/// Writes the materials as MTL, under the names returned by `material_names`.
+pub fn encode_mtl(materials: &[ObjMaterialData], options: &ObjWriteOptions) -> String {
+    let names = material_names(materials);
+    let mut mtl = String::new();
+
+    for (material, name) in materials.iter().zip(&names) {
+        if !mtl.is_empty() {
+            mtl.push('\n');
+        }
+        writeln!(mtl, "newmtl {}", name).expect("writing to a String cannot fail");
+
+        let colors = [
+            ("Ka", material.ambient_color),
+            ("Kd", material.diffuse_color),
+            ("Ks", material.specular_color),
+            ("Ke", material.emissive_color),
+        ];
+        for (directive, color) in colors {
+            if let Some(color) = color {
+                writeln!(mtl, "{} {}", directive, format_floats(&color, options))
+                    .expect("writing to a String cannot fail");
+            }
+        }
+
+        let scalars = [
+            ("Ns", material.shininess),
+            ("Ni", material.optical_density),
+            ("d", material.dissolve),
+        ];
+        for (directive, value) in scalars {
+            if let Some(value) = value {
+                writeln!(mtl, "{} {}", directive, format_float(value, options))
+                    .expect("writing to a String cannot fail");
+            }
+        }
+        if let Some(illumination_model) = material.illumination_model {
+            writeln!(mtl, "illum {}", illumination_model).expect("writing to a String cannot fail");
+        }
+
+        let maps = [
+            ("map_Kd", &material.diffuse_texture),
+            ("map_Ks", &material.specular_texture),
+            ("map_Bump", &material.normal_texture),
+        ];
+        for (directive, map) in maps {
+            if let Some(map) = map.as_ref().filter(|map| !map.path.is_empty()) {
+                writeln!(mtl, "{} {}", directive, encode_texture_map(map, options))
+                    .expect("writing to a String cannot fail");
+            }
+        }
+    }
+
+    mtl
+}
+
+/// Unique names usable in both `newmtl` and `usemtl`: the OBJ loader reads only the first
+/// token of `usemtl`, so whitespace becomes `_`, and unnamed or repeated names get the
+/// material index appended.
+pub(super) fn material_names(materials: &[ObjMaterialData]) -> Vec<String> {
+    let mut names: Vec<String> = Vec::with_capacity(materials.len());
+    for (index, material) in materials.iter().enumerate() {
+        let mut name = sanitize_name(&material.name);
+        if name.is_empty() {
+            name = format!("material_{}", index);
+        }
+        if names.contains(&name) {
+            name = format!("{}_{}", name, index);
+        }
+        names.push(name);
+    }
+    names
+}
+
+pub(super) fn sanitize_name(name: &str) -> String {
+    name.split_whitespace().collect::<Vec<_>>().join("_")
+}
+
+/// Texture options followed by the path; options equal to their MTL default are left out.
+fn encode_texture_map(map: &ObjTextureMap, options: &ObjWriteOptions) -> String {
+    let defaults = ObjTextureMap::default();
+    let mut parts = Vec::new();
+
+    let vectors = [
+        ("-s", map.scale, defaults.scale),
+        ("-o", map.offset, defaults.offset),
+        ("-t", map.turbulence, defaults.turbulence),
+    ];
+    for (option, value, default) in vectors {
+        if value != default {
+            parts.push(format!("{} {}", option, format_floats(&value, options)));
+        }
+    }
+
+    let switches = [
+        ("-clamp", map.clamp, defaults.clamp),
+        ("-blendu", map.blend_u, defaults.blend_u),
+        ("-blendv", map.blend_v, defaults.blend_v),
+        ("-cc", map.color_correction, defaults.color_correction),
+    ];
+    for (option, value, default) in switches {
+        if value != default {
+            parts.push(format!("{} {}", option, if value { "on" } else { "off" }));
+        }
+    }
+
+    if map.bump_multiplier != defaults.bump_multiplier {
+        parts.push(format!(
+            "-bm {}",
+            format_float(map.bump_multiplier, options)
+        ));
+    }
+    if map.value_base != defaults.value_base || map.value_gain != defaults.value_gain {
+        parts.push(format!(
+            "-mm {} {}",
+            format_float(map.value_base, options),
+            format_float(map.value_gain, options)
+        ));
+    }
+    if let Some(boost) = map.boost {
+        parts.push(format!("-boost {}", format_float(boost, options)));
+    }
+    if let Some(resolution) = map.resolution {
+        parts.push(format!("-texres {}", resolution));
+    }
+    if let Some(channel) = map.channel {
+        parts.push(format!("-imfchan {}", channel));
+    }
+
+    parts.push(map.path.clone());
+    parts.join(" ")
+}
+
+/// Formats `value` with `options.precision` decimals, then drops trailing zeros so `1.500000`
+/// is written as `1.5` and `-0.000000` as `0`.
+pub(super) fn format_float(value: f32, options: &ObjWriteOptions) -> String {
+    let mut text = format!("{:.*}", options.precision, value);
+    if text.contains('.') {
+        let trimmed = text.trim_end_matches('0').trim_end_matches('.').len();
+        text.truncate(trimmed);
+    }
+    if text == "-0" {
+        text.remove(0);
+    }
+    text
+}
+
+pub(super) fn format_floats(values: &[f32], options: &ObjWriteOptions) -> String {
+    values
+        .iter()
+        .map(|value| format_float(*value, options))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -0,0 +1,475 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+use super::types::{ObjMeshData, ObjSceneData, ObjWriteOptions};
+use super::write_mtl::{encode_mtl, format_floats, material_names, sanitize_name};
+
+/// One `v` / `vt` / `vn` list. Entries that format to the same text are written once and
+/// shared by every face that uses them.
+#[derive(Default)]
+struct SharedPool {
+    lines: Vec<String>,
+    indices: HashMap<String, usize>,
+}
+
+impl SharedPool {
+    /// Returns the 1-based OBJ index of `line`, adding it when it is new.
+    fn index_of(&mut self, line: String) -> usize {
+        if let Some(&index) = self.indices.get(&line) {
+            return index;
+        }
+        self.lines.push(line.clone());
+        let index = self.lines.len();
+        self.indices.insert(line, index);
+        index
+    }
+}
+
+/// Writes the scene as OBJ text with shared `v` / `vt` / `vn` indices, one `o` per object
+/// and one `usemtl` per material change. `mtl_file_name` adds the `mtllib` line; the
+/// materials themselves are written by `encode_mtl`.
+pub fn encode(
+    scene: &ObjSceneData,
+    mtl_file_name: Option<&str>,
+    options: &ObjWriteOptions,
+) -> Result<String, String> {
+    let names = material_names(&scene.materials);
+    // `usemtl` with a name missing from the MTL leaves the following meshes without material.
+    let mut no_material = String::from("none");
+    while names.contains(&no_material) {
+        no_material.push('_');
+    }
+
+    let mut positions = SharedPool::default();
+    let mut texcoords = SharedPool::default();
+    let mut normals = SharedPool::default();
+    let mut body = String::new();
+    let mut current_material: Option<&str> = None;
+    let mut has_groups = false;
+
+    for (object_index, object) in scene.objects.iter().enumerate() {
+        if object.meshes.iter().all(|mesh| mesh.indices.is_empty()) {
+            continue;
+        }
+
+        let name = object.name.trim();
+        if !name.is_empty() {
+            writeln!(body, "o {}", name).expect("writing to a String cannot fail");
+        } else if !body.is_empty() {
+            // Only faces before the first `o` can form an unnamed object.
+            writeln!(body, "o object_{}", object_index).expect("writing to a String cannot fail");
+        }
+
+        let groups: Vec<String> = object
+            .groups
+            .iter()
+            .map(|group| sanitize_name(group))
+            .filter(|group| !group.is_empty())
+            .collect();
+        if !groups.is_empty() {
+            writeln!(body, "g {}", groups.join(" ")).expect("writing to a String cannot fail");
+        } else if has_groups {
+            body.push_str("g\n");
+        }
+        has_groups = !groups.is_empty();
+
+        for mesh in object.meshes.iter().filter(|mesh| !mesh.indices.is_empty()) {
+            validate_mesh(mesh, scene.materials.len(), &object.name)?;
+
+            let material = match mesh.material_id {
+                Some(material_id) => Some(names[material_id].as_str()),
+                None => current_material.map(|_| no_material.as_str()),
+            };
+            if let Some(material) = material
+                && current_material != Some(material)
+            {
+                writeln!(body, "usemtl {}", material).expect("writing to a String cannot fail");
+                current_material = Some(material);
+            }
+
+            // Only vertices referenced by a face are written.
+            let mut corners: Vec<Option<String>> = vec![None; mesh.positions.len() / 3];
+            for triangle in mesh.indices.chunks_exact(3) {
+                body.push('f');
+                for &index in triangle {
+                    let reference = corners[index as usize].get_or_insert_with(|| {
+                        corner_reference(
+                            mesh,
+                            index as usize,
+                            &mut positions,
+                            &mut texcoords,
+                            &mut normals,
+                            options,
+                        )
+                    });
+                    body.push(' ');
+                    body.push_str(reference);
+                }
+                body.push('\n');
+            }
+        }
+    }
+
+    let mut obj = String::new();
+    if let Some(mtl_file_name) = mtl_file_name {
+        writeln!(obj, "mtllib {}", mtl_file_name).expect("writing to a String cannot fail");
+    }
+    for (directive, pool) in [("v", &positions), ("vt", &texcoords), ("vn", &normals)] {
+        for line in &pool.lines {
+            writeln!(obj, "{} {}", directive, line).expect("writing to a String cannot fail");
+        }
+    }
+    obj.push_str(&body);
+    Ok(obj)
+}
+
+/// Writes the scene to `path` and, when it has materials, an MTL file next to it with the
+/// same stem. Texture paths are written unchanged, so relative paths stay relative to the
+/// directory the model was loaded from.
+pub fn save(scene: &ObjSceneData, path: &Path, options: &ObjWriteOptions) -> Result<(), String> {
+    let mtl_path = path.with_extension("mtl");
+    let mtl_file_name = if scene.materials.is_empty() {
+        None
+    } else {
+        let file_name = mtl_path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| format!("Invalid MTL file name for '{}'", path.display()))?;
+        if file_name.contains(char::is_whitespace) {
+            return Err(format!(
+                "MTL file name '{}' cannot be referenced from OBJ because it contains whitespace",
+                file_name
+            ));
+        }
+        Some(file_name)
+    };
+
+    let obj = encode(scene, mtl_file_name, options)?;
+    fs::write(path, obj)
+        .map_err(|error| format!("Failed to write OBJ '{}': {}", path.display(), error))?;
+
+    if mtl_file_name.is_some() {
+        let mtl = encode_mtl(&scene.materials, options);
+        fs::write(&mtl_path, mtl)
+            .map_err(|error| format!("Failed to write MTL '{}': {}", mtl_path.display(), error))?;
+    }
+    Ok(())
+}
+
+/// The `v/vt/vn` reference for one mesh vertex. Texture coordinates are left out when the
+/// mesh has none, and so are zero normals, which the loader regenerates.
+fn corner_reference(
+    mesh: &ObjMeshData,
+    vertex: usize,
+    positions: &mut SharedPool,
+    texcoords: &mut SharedPool,
+    normals: &mut SharedPool,
+    options: &ObjWriteOptions,
+) -> String {
+    let mut position = format_floats(&mesh.positions[vertex * 3..vertex * 3 + 3], options);
+    if !mesh.colors.is_empty() {
+        // `v x y z r g b`: the common vertex color extension.
+        position.push(' ');
+        position.push_str(&format_floats(
+            &mesh.colors[vertex * 3..vertex * 3 + 3],
+            options,
+        ));
+    }
+    let position = positions.index_of(position);
+
+    let texcoord = (!mesh.texcoords.is_empty()).then(|| {
+        texcoords.index_of(format_floats(
+            &mesh.texcoords[vertex * 2..vertex * 2 + 2],
+            options,
+        ))
+    });
+
+    let normal = mesh
+        .normals
+        .get(vertex * 3..vertex * 3 + 3)
+        .filter(|normal| normal.iter().any(|component| *component != 0.0))
+        .map(|normal| normals.index_of(format_floats(normal, options)));
+
+    match (texcoord, normal) {
+        (Some(texcoord), Some(normal)) => format!("{}/{}/{}", position, texcoord, normal),
+        (None, Some(normal)) => format!("{}//{}", position, normal),
+        (Some(texcoord), None) => format!("{}/{}", position, texcoord),
+        (None, None) => position.to_string(),
+    }
+}
+
+fn validate_mesh(
+    mesh: &ObjMeshData,
+    material_count: usize,
+    object_name: &str,
+) -> Result<(), String> {
+    let malformed = |reason: &str| {
+        Err(format!(
+            "Cannot write OBJ object '{}': {}",
+            object_name, reason
+        ))
+    };
+
+    if !mesh.positions.len().is_multiple_of(3) {
+        return malformed("positions array length is not a multiple of 3");
+    }
+    let vertex_count = mesh.positions.len() / 3;
+    if !mesh.normals.is_empty() && mesh.normals.len() != mesh.positions.len() {
+        return malformed("normals array length must match positions length");
+    }
+    if !mesh.texcoords.is_empty() && mesh.texcoords.len() != vertex_count * 2 {
+        return malformed("texcoords array length must be vertex_count * 2");
+    }
+    if !mesh.colors.is_empty() && mesh.colors.len() != mesh.positions.len() {
+        return malformed("colors array length must match positions length");
+    }
+    if !mesh.indices.len().is_multiple_of(3) {
+        return malformed("indices do not form whole triangles");
+    }
+    if let Some(index) = mesh
+        .indices
+        .iter()
+        .find(|index| **index as usize >= vertex_count)
+    {
+        return malformed(&format!(
+            "index {} is out of range for {} vertices",
+            index, vertex_count
+        ));
+    }
+    if let Some(material_id) = mesh.material_id
+        && material_id >= material_count
+    {
+        return malformed(&format!("unknown material id {}", material_id));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::process;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use super::{encode, save};
+    use crate::loaders::obj::{
+        ObjLoadOptions, ObjMeshData, ObjObjectData, ObjSceneData, ObjWriteOptions, load,
+    };
+
+    fn unique_temp_dir(prefix: &str) -> PathBuf {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("system clock should be after epoch")
+            .as_nanos();
+        let dir = env::temp_dir().join(format!("{}_{}_{}", prefix, process::id(), nanos));
+        fs::create_dir_all(&dir).expect("failed to create temporary test directory");
+        dir
+    }
+
+    fn load_welded(path: &std::path::Path) -> ObjSceneData {
+        load(
+            path,
+            &ObjLoadOptions {
+                triangulate: true,
+                single_index: true,
+                crease_angle_degrees: None,
+            },
+        )
+        .expect("OBJ should load")
+    }
+
+    /// Every triangle corner as `position, texcoord, normal`, independent of vertex order.
+    fn expand_corners(mesh: &ObjMeshData) -> Vec<Vec<f32>> {
+        mesh.indices
+            .iter()
+            .map(|&index| {
+                let i = index as usize;
+                let mut corner = mesh.positions[i * 3..i * 3 + 3].to_vec();
+                corner.extend_from_slice(&mesh.texcoords[i * 2..i * 2 + 2]);
+                corner.extend_from_slice(&mesh.normals[i * 3..i * 3 + 3]);
+                corner
+            })
+            .collect()
+    }
+
+    #[test]
+    fn round_trips_objects_materials_and_shared_vertices() {
+        let dir = unique_temp_dir("scop_obj_write_round_trip");
+        fs::write(
+            dir.join("source.mtl"),
+            "\
+newmtl Wood
+Kd 0.5 0.25 0.125
+Ns 32
+d 0.75
+illum 2
+map_Kd -s 2 2 1 -clamp on wood.bmp
+
+newmtl Metal
+Ks 1 1 1
+map_Bump -bm 0.5 metal_normal.bmp
+",
+        )
+        .expect("failed to write MTL fixture");
+        let source = dir.join("source.obj");
+        fs::write(
+            &source,
+            "\
+mtllib source.mtl
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+v 0 0 1
+vt 0 0
+vt 1 0
+vt 1 1
+vt 0 1
+vn 0 0 1
+o Crate
+g box lid
+usemtl Wood
+f 1/1/1 2/2/1 3/3/1 4/4/1
+usemtl Metal
+f 1/1/1 2/2/1 5/3/1
+o Bolt
+usemtl Metal
+f 2/1/1 3/2/1 5/4/1
+",
+        )
+        .expect("failed to write OBJ fixture");
+
+        let original = load_welded(&source);
+        let exported = dir.join("exported.obj");
+        save(&original, &exported, &ObjWriteOptions::default()).expect("scene should save");
+        let reloaded = load_welded(&exported);
+
+        // The quad was triangulated on load; its four corners stay shared in the output.
+        let obj = fs::read_to_string(&exported).expect("failed to read exported OBJ");
+        assert!(obj.starts_with("mtllib exported.mtl\n"));
+        assert_eq!(obj.lines().filter(|line| line.starts_with("v ")).count(), 5);
+        assert_eq!(
+            obj.lines().filter(|line| line.starts_with("vn ")).count(),
+            1
+        );
+        assert_eq!(obj.lines().filter(|line| line.starts_with("f ")).count(), 4);
+
+        assert_eq!(reloaded.objects.len(), 2);
+        for (before, after) in original.objects.iter().zip(&reloaded.objects) {
+            assert_eq!(before.name, after.name);
+            assert_eq!(before.groups, after.groups);
+            assert_eq!(before.meshes.len(), after.meshes.len());
+            for (mesh_before, mesh_after) in before.meshes.iter().zip(&after.meshes) {
+                assert_eq!(expand_corners(mesh_before), expand_corners(mesh_after));
+                let name = |scene: &ObjSceneData, mesh: &ObjMeshData| {
+                    scene.materials[mesh.material_id.expect("material")]
+                        .name
+                        .clone()
+                };
+                assert_eq!(name(&original, mesh_before), name(&reloaded, mesh_after));
+            }
+        }
+        assert_eq!(reloaded.objects[0].groups, vec!["box", "lid"]);
+
+        let wood = &reloaded.materials[0];
+        assert_eq!(wood.name, "Wood");
+        assert_eq!(wood.diffuse_color, Some([0.5, 0.25, 0.125]));
+        assert_eq!(wood.shininess, Some(32.0));
+        assert_eq!(wood.dissolve, Some(0.75));
+        assert_eq!(wood.illumination_model, Some(2));
+        assert_eq!(wood.diffuse_texture, original.materials[0].diffuse_texture);
+        assert_eq!(
+            reloaded.materials[1].normal_texture,
+            original.materials[1].normal_texture
+        );
+
+        fs::remove_dir_all(&dir).expect("failed to cleanup temp directory");
+    }
+
+    #[test]
+    fn precision_limits_decimals_and_vertices_keep_their_colors() {
+        let mesh = ObjMeshData {
+            positions: vec![0.123456, -0.00001, 2.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0],
+            colors: vec![1.0, 0.5, 0.0, 0.25, 0.5, 0.75, 0.0, 0.0, 1.0],
+            indices: vec![0, 1, 2],
+            ..Default::default()
+        };
+        let scene = ObjSceneData {
+            objects: vec![ObjObjectData {
+                name: "Tri".to_string(),
+                meshes: vec![mesh],
+                ..Default::default()
+            }],
+            materials: Vec::new(),
+        };
+
+        let obj = encode(&scene, None, &ObjWriteOptions { precision: 3 }).expect("encode");
+        assert_eq!(
+            obj,
+            "\
+v 0.123 0 2 1 0.5 0
+v 1 0 0 0.25 0.5 0.75
+v 0 1 0 0 0 1
+o Tri
+f 1 2 3
+"
+        );
+
+        // The OBJ loader reads the colors back
+        let dir = unique_temp_dir("scop_obj_write_colors");
+        let path = dir.join("colors.obj");
+        save(&scene, &path, &ObjWriteOptions::default()).expect("scene should save");
+        let reloaded = load_welded(&path);
+        let mesh = &reloaded.objects[0].meshes[0];
+        let corner_colors: Vec<&[f32]> = mesh
+            .indices
+            .iter()
+            .map(|&index| &mesh.colors[index as usize * 3..index as usize * 3 + 3])
+            .collect();
+        assert_eq!(
+            corner_colors,
+            vec![&[1.0, 0.5, 0.0][..], &[0.25, 0.5, 0.75], &[0.0, 0.0, 1.0]]
+        );
+
+        fs::remove_dir_all(&dir).expect("failed to cleanup temp directory");
+    }
+
+    #[test]
+    fn meshes_without_material_after_a_material_stay_unassigned() {
+        let triangle = |material_id| ObjMeshData {
+            positions: vec![0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0],
+            indices: vec![0, 1, 2],
+            material_id,
+            ..Default::default()
+        };
+        let dir = unique_temp_dir("scop_obj_write_no_material");
+        let scene = ObjSceneData {
+            objects: vec![ObjObjectData {
+                meshes: vec![triangle(Some(0)), triangle(None)],
+                ..Default::default()
+            }],
+            materials: vec![Default::default()],
+        };
+        let path = dir.join("unassigned.obj");
+        save(&scene, &path, &ObjWriteOptions::default()).expect("scene should save");
+
+        let reloaded = load_welded(&path);
+        let materials: Vec<_> = reloaded.objects[0]
+            .meshes
+            .iter()
+            .map(|mesh| mesh.material_id)
+            .collect();
+        assert_eq!(materials, vec![Some(0), None]);
+        assert_eq!(reloaded.materials[0].name, "material_0");
+
+        let mut broken = scene.clone();
+        broken.objects[0].meshes[1].indices = vec![0, 1, 3];
+        let error = encode(&broken, None, &ObjWriteOptions::default()).unwrap_err();
+        assert!(error.contains("index 3 is out of range"), "{}", error);
+
+        fs::remove_dir_all(&dir).expect("failed to cleanup temp directory");
+    }
+}
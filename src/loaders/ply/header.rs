@@ -0,0 +1,196 @@
+/// Body encoding declared by the `format` line.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScalarType {
+    Int8,
+    UInt8,
+    Int16,
+    UInt16,
+    Int32,
+    UInt32,
+    Float32,
+    Float64,
+}
+
+impl ScalarType {
+    fn parse(name: &str) -> Option<ScalarType> {
+        Some(match name {
+            "char" | "int8" => ScalarType::Int8,
+            "uchar" | "uint8" => ScalarType::UInt8,
+            "short" | "int16" => ScalarType::Int16,
+            "ushort" | "uint16" => ScalarType::UInt16,
+            "int" | "int32" => ScalarType::Int32,
+            "uint" | "uint32" => ScalarType::UInt32,
+            "float" | "float32" => ScalarType::Float32,
+            "double" | "float64" => ScalarType::Float64,
+            _ => return None,
+        })
+    }
+
+    pub fn size(self) -> usize {
+        match self {
+            ScalarType::Int8 | ScalarType::UInt8 => 1,
+            ScalarType::Int16 | ScalarType::UInt16 => 2,
+            ScalarType::Int32 | ScalarType::UInt32 | ScalarType::Float32 => 4,
+            ScalarType::Float64 => 8,
+        }
+    }
+
+    /// Largest value of an integer type, used to normalize colors; `None` for floats.
+    pub fn integer_max(self) -> Option<f64> {
+        match self {
+            ScalarType::Int8 => Some(i8::MAX as f64),
+            ScalarType::UInt8 => Some(u8::MAX as f64),
+            ScalarType::Int16 => Some(i16::MAX as f64),
+            ScalarType::UInt16 => Some(u16::MAX as f64),
+            ScalarType::Int32 => Some(i32::MAX as f64),
+            ScalarType::UInt32 => Some(u32::MAX as f64),
+            ScalarType::Float32 | ScalarType::Float64 => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PropertyKind {
+    Scalar(ScalarType),
+    /// `property list <count type> <item type> name`
+    List {
+        count: ScalarType,
+        item: ScalarType,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Property {
+    pub name: String,
+    pub kind: PropertyKind,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Element {
+    pub name: String,
+    pub count: usize,
+    pub properties: Vec<Property>,
+}
+
+impl Element {
+    pub fn property_index(&self, names: &[&str]) -> Option<usize> {
+        self.properties
+            .iter()
+            .position(|property| names.contains(&property.name.as_str()))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Header {
+    pub format: Format,
+    pub elements: Vec<Element>,
+}
+
+/// Parses the header and returns it with the offset of the first body byte.
+pub fn parse_header(bytes: &[u8]) -> Result<(Header, usize), String> {
+    let mut position = 0;
+    let mut next_line = |line_number: usize| -> Result<String, String> {
+        let rest = &bytes[position..];
+        let end = rest
+            .iter()
+            .position(|&byte| byte == b'\n')
+            .ok_or_else(|| format!("PLY header line {}: missing 'end_header'", line_number))?;
+        position += end + 1;
+        Ok(String::from_utf8_lossy(&rest[..end]).trim().to_string())
+    };
+
+    if next_line(1)? != "ply" {
+        return Err("Not a PLY file (missing 'ply' magic line)".to_string());
+    }
+
+    let mut format = None;
+    let mut elements: Vec<Element> = Vec::new();
+    let mut line_number = 1;
+    loop {
+        line_number += 1;
+        let line = next_line(line_number)?;
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        match parts.as_slice() {
+            [] | ["comment", ..] | ["obj_info", ..] => {}
+            ["format", encoding, _version] => {
+                format = Some(match *encoding {
+                    "ascii" => Format::Ascii,
+                    "binary_little_endian" => Format::BinaryLittleEndian,
+                    "binary_big_endian" => Format::BinaryBigEndian,
+                    _ => {
+                        return Err(format!(
+                            "PLY header line {}: unknown format '{}'",
+                            line_number, encoding
+                        ));
+                    }
+                });
+            }
+            ["element", name, count] => {
+                let count = count.parse().map_err(|_| {
+                    format!(
+                        "PLY header line {}: invalid element count '{}'",
+                        line_number, count
+                    )
+                })?;
+                elements.push(Element {
+                    name: name.to_string(),
+                    count,
+                    properties: Vec::new(),
+                });
+            }
+            ["property", declaration @ ..] => {
+                let element = elements.last_mut().ok_or_else(|| {
+                    format!(
+                        "PLY header line {}: property before any element",
+                        line_number
+                    )
+                })?;
+                let scalar = |name: &str| {
+                    ScalarType::parse(name).ok_or_else(|| {
+                        format!(
+                            "PLY header line {}: unknown property type '{}'",
+                            line_number, name
+                        )
+                    })
+                };
+                let (kind, name) = match declaration {
+                    ["list", count, item, name] => (
+                        PropertyKind::List {
+                            count: scalar(count)?,
+                            item: scalar(item)?,
+                        },
+                        name,
+                    ),
+                    [scalar_type, name] => (PropertyKind::Scalar(scalar(scalar_type)?), name),
+                    _ => {
+                        return Err(format!(
+                            "PLY header line {}: malformed property '{}'",
+                            line_number, line
+                        ));
+                    }
+                };
+                element.properties.push(Property {
+                    name: name.to_string(),
+                    kind,
+                });
+            }
+            ["end_header"] => break,
+            _ => {
+                return Err(format!(
+                    "PLY header line {}: unexpected '{}'",
+                    line_number, line
+                ));
+            }
+        }
+    }
+
+    let format = format.ok_or_else(|| "PLY header is missing its 'format' line".to_string())?;
+    Ok((Header { format, elements }, position))
+}
@@ -0,0 +1,30 @@
+mod header;
+mod parse_ply;
+
+use std::path::Path;
+
+use crate::loaders::obj::ObjSceneData;
+use crate::loaders::registry::ModelLoader;
+
+pub use parse_ply::{load, parse};
+
+/// Registry entry for PLY models.
+pub struct PlyLoader;
+
+impl ModelLoader for PlyLoader {
+    fn name(&self) -> &'static str {
+        "PLY"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["ply"]
+    }
+
+    fn matches_magic(&self, header: &[u8]) -> bool {
+        header.starts_with(b"ply\n") || header.starts_with(b"ply\r\n")
+    }
+
+    fn load(&self, path: &Path) -> Result<ObjSceneData, String> {
+        load(path)
+    }
+}
@@ -0,0 +1,519 @@
+use std::fs;
+use std::path::Path;
+
+use super::header::{Element, Format, PropertyKind, ScalarType, parse_header};
+use crate::loaders::obj::{
+    FaceVertex, ObjMeshData, ObjObjectData, ObjSceneData, TriangulationOutcome,
+    generate_vertex_normals, triangulate_face,
+};
+
+/// Loads an ASCII or binary PLY file into the same scene data the OBJ loader produces: one
+/// unnamed object holding a single triangulated mesh.
+pub fn load(path: &Path) -> Result<ObjSceneData, String> {
+    let bytes = fs::read(path)
+        .map_err(|e| format!("Failed to open PLY file '{}': {}", path.display(), e))?;
+    parse(&bytes).map_err(|e| format!("{} (while loading {})", e, path.display()))
+}
+
+/// Parses PLY bytes. `vertex` and `face` elements are used; any other element is read and
+/// skipped so the elements after it stay in sync.
+pub fn parse(bytes: &[u8]) -> Result<ObjSceneData, String> {
+    let (header, body_start) = parse_header(bytes)?;
+    let mut reader = BodyReader::new(header.format, &bytes[body_start..])?;
+
+    let mut vertices = VertexData::default();
+    let mut faces: Vec<Vec<usize>> = Vec::new();
+    for element in &header.elements {
+        match element.name.as_str() {
+            "vertex" => vertices = read_vertices(&mut reader, element)?,
+            "face" => faces = read_faces(&mut reader, element)?,
+            // Instances without properties take no bytes, whatever the header count says
+            _ if element.properties.is_empty() => {}
+            _ => {
+                for _ in 0..element.count {
+                    read_instance(&mut reader, element)?;
+                }
+            }
+        }
+    }
+
+    let mesh = build_mesh(vertices, &faces)?;
+    Ok(ObjSceneData {
+        objects: vec![ObjObjectData {
+            name: String::new(),
+            groups: Vec::new(),
+            meshes: vec![mesh],
+        }],
+        materials: Vec::new(),
+    })
+}
+
+#[derive(Default)]
+struct VertexData {
+    positions: Vec<[f32; 3]>,
+    normals: Vec<[f32; 3]>,
+    texcoords: Vec<[f32; 2]>,
+    colors: Vec<[f32; 3]>,
+}
+
+fn read_vertices(reader: &mut BodyReader, element: &Element) -> Result<VertexData, String> {
+    let find_all = |names: &[&[&str]]| -> Option<Vec<usize>> {
+        names
+            .iter()
+            .map(|aliases| element.property_index(aliases))
+            .collect()
+    };
+    let position = find_all(&[&["x"], &["y"], &["z"]])
+        .ok_or_else(|| "PLY vertex element needs x, y and z properties".to_string())?;
+    let normal = find_all(&[&["nx"], &["ny"], &["nz"]]);
+    let texcoord = find_all(&[&["u", "s", "texture_u"], &["v", "t", "texture_v"]]);
+    let color = find_all(&[
+        &["red", "diffuse_red"],
+        &["green", "diffuse_green"],
+        &["blue", "diffuse_blue"],
+    ]);
+    // Integer colors are normalized by their type's range; float colors are already in [0, 1]
+    let color_scale: Vec<f64> = color
+        .iter()
+        .flatten()
+        .map(|&index| match element.properties[index].kind {
+            PropertyKind::Scalar(scalar) => scalar.integer_max().unwrap_or(1.0),
+            PropertyKind::List { .. } => 1.0,
+        })
+        .collect();
+
+    let mut vertices = VertexData::default();
+    for _ in 0..element.count {
+        let values = read_instance(reader, element)?;
+        let scalar = |index: usize| -> Result<f64, String> {
+            match values[index].as_slice() {
+                [value] => Ok(*value),
+                _ => Err(format!(
+                    "PLY vertex property '{}' must be a scalar",
+                    element.properties[index].name
+                )),
+            }
+        };
+        let vector3 = |indices: &[usize]| -> Result<[f32; 3], String> {
+            Ok([
+                scalar(indices[0])? as f32,
+                scalar(indices[1])? as f32,
+                scalar(indices[2])? as f32,
+            ])
+        };
+
+        vertices.positions.push(vector3(&position)?);
+        if let Some(normal) = &normal {
+            vertices.normals.push(vector3(normal)?);
+        }
+        if let Some(texcoord) = &texcoord {
+            vertices
+                .texcoords
+                .push([scalar(texcoord[0])? as f32, scalar(texcoord[1])? as f32]);
+        }
+        if let Some(color) = &color {
+            let channel = |i: usize| -> Result<f32, String> {
+                Ok((scalar(color[i])? / color_scale[i]) as f32)
+            };
+            vertices
+                .colors
+                .push([channel(0)?, channel(1)?, channel(2)?]);
+        }
+    }
+    Ok(vertices)
+}
+
+fn read_faces(reader: &mut BodyReader, element: &Element) -> Result<Vec<Vec<usize>>, String> {
+    let indices = element
+        .property_index(&["vertex_indices", "vertex_index"])
+        .ok_or_else(|| "PLY face element needs a vertex_indices list".to_string())?;
+
+    // `element.count` comes straight from the header, so nothing is reserved up front.
+    let mut faces = Vec::new();
+    for face_number in 0..element.count {
+        let values = read_instance(reader, element)?;
+        let face = values[indices]
+            .iter()
+            .map(|&index| {
+                if index >= 0.0 && index.fract() == 0.0 {
+                    Ok(index as usize)
+                } else {
+                    Err(format!(
+                        "PLY face {} has invalid vertex index {}",
+                        face_number, index
+                    ))
+                }
+            })
+            .collect::<Result<Vec<usize>, String>>()?;
+        faces.push(face);
+    }
+    Ok(faces)
+}
+
+/// Reads one element instance; every property yields its values (one for scalars).
+fn read_instance(reader: &mut BodyReader, element: &Element) -> Result<Vec<Vec<f64>>, String> {
+    element
+        .properties
+        .iter()
+        .map(|property| match property.kind {
+            PropertyKind::Scalar(scalar) => Ok(vec![reader.read(scalar, &element.name)?]),
+            PropertyKind::List { count, item } => {
+                let length = reader.read(count, &element.name)?;
+                if length < 0.0 || length.fract() != 0.0 {
+                    return Err(format!(
+                        "PLY list '{}' has invalid length {}",
+                        property.name, length
+                    ));
+                }
+                (0..length as usize)
+                    .map(|_| reader.read(item, &element.name))
+                    .collect()
+            }
+        })
+        .collect()
+}
+
+fn build_mesh(vertices: VertexData, faces: &[Vec<usize>]) -> Result<ObjMeshData, String> {
+    let VertexData {
+        positions,
+        mut normals,
+        texcoords,
+        colors,
+    } = vertices;
+
+    let mut triangles: Vec<[usize; 3]> = Vec::new();
+    for (face_number, face) in faces.iter().enumerate() {
+        if face.len() < 3 {
+            return Err(format!(
+                "PLY face {} requires at least 3 vertices",
+                face_number
+            ));
+        }
+        if let Some(&index) = face.iter().find(|&&index| index >= positions.len()) {
+            return Err(format!(
+                "PLY face {} references vertex {} but only {} exist",
+                face_number,
+                index,
+                positions.len()
+            ));
+        }
+
+        let corners: Vec<FaceVertex> = face.iter().map(|&index| (index, None, None)).collect();
+        let local_triangles = if corners.len() == 3 {
+            vec![[0, 1, 2]]
+        } else {
+            match triangulate_face(&corners, &positions) {
+                TriangulationOutcome::Robust(local_triangles) => local_triangles,
+                TriangulationOutcome::FallbackFan => {
+                    (1..corners.len() - 1).map(|i| [0, i, i + 1]).collect()
+                }
+            }
+        };
+        triangles.extend(
+            local_triangles
+                .into_iter()
+                .map(|[a, b, c]| [face[a], face[b], face[c]]),
+        );
+    }
+
+    // Vertices are already shared, so generated normals are smoothed across every face
+    if normals.is_empty() && !triangles.is_empty() {
+        normals = generate_vertex_normals(&positions, &triangles);
+    }
+
+    let index = |&index: &usize| {
+        u32::try_from(index).map_err(|_| {
+            "PLY mesh exceeds the maximum number of vertices addressable by u32 indices".to_string()
+        })
+    };
+    Ok(ObjMeshData {
+        positions: positions.into_iter().flatten().collect(),
+        normals: normals.into_iter().flatten().collect(),
+        texcoords: texcoords.into_iter().flatten().collect(),
+        colors: colors.into_iter().flatten().collect(),
+        indices: triangles
+            .iter()
+            .flatten()
+            .map(index)
+            .collect::<Result<_, _>>()?,
+        material_id: None,
+    })
+}
+
+enum BodyReader<'a> {
+    Ascii(std::str::SplitAsciiWhitespace<'a>),
+    Binary {
+        bytes: &'a [u8],
+        position: usize,
+        big_endian: bool,
+    },
+}
+
+impl<'a> BodyReader<'a> {
+    fn new(format: Format, body: &'a [u8]) -> Result<Self, String> {
+        Ok(match format {
+            Format::Ascii => BodyReader::Ascii(
+                std::str::from_utf8(body)
+                    .map_err(|_| "ASCII PLY body is not valid UTF-8".to_string())?
+                    .split_ascii_whitespace(),
+            ),
+            Format::BinaryLittleEndian | Format::BinaryBigEndian => BodyReader::Binary {
+                bytes: body,
+                position: 0,
+                big_endian: format == Format::BinaryBigEndian,
+            },
+        })
+    }
+
+    fn read(&mut self, scalar: ScalarType, element: &str) -> Result<f64, String> {
+        let ended = || format!("PLY body ended while reading element '{}'", element);
+        match self {
+            BodyReader::Ascii(tokens) => {
+                let token = tokens.next().ok_or_else(ended)?;
+                token
+                    .parse::<f64>()
+                    .map_err(|_| format!("PLY element '{}': invalid number '{}'", element, token))
+            }
+            BodyReader::Binary {
+                bytes,
+                position,
+                big_endian,
+            } => {
+                let size = scalar.size();
+                let raw = bytes.get(*position..*position + size).ok_or_else(ended)?;
+                *position += size;
+                let mut buffer = [0u8; 8];
+                buffer[..size].copy_from_slice(raw);
+                if *big_endian {
+                    buffer[..size].reverse();
+                }
+                Ok(match scalar {
+                    ScalarType::Int8 => buffer[0] as i8 as f64,
+                    ScalarType::UInt8 => buffer[0] as f64,
+                    ScalarType::Int16 => i16::from_le_bytes([buffer[0], buffer[1]]) as f64,
+                    ScalarType::UInt16 => u16::from_le_bytes([buffer[0], buffer[1]]) as f64,
+                    ScalarType::Int32 => {
+                        i32::from_le_bytes(buffer[..4].try_into().expect("4 bytes")) as f64
+                    }
+                    ScalarType::UInt32 => {
+                        u32::from_le_bytes(buffer[..4].try_into().expect("4 bytes")) as f64
+                    }
+                    ScalarType::Float32 => {
+                        f32::from_le_bytes(buffer[..4].try_into().expect("4 bytes")) as f64
+                    }
+                    ScalarType::Float64 => f64::from_le_bytes(buffer),
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+
+    const HEADER_BODY: &str = "\
+element vertex 5
+property float x
+property float y
+property float z
+property uchar red
+property uchar green
+property uchar blue
+element edge 1
+property int vertex1
+property int vertex2
+element face 2
+property list uchar int vertex_indices
+end_header
+";
+
+    fn header(format: &str) -> Vec<u8> {
+        format!(
+            "ply\nformat {} 1.0\ncomment scanned\n{}",
+            format, HEADER_BODY
+        )
+        .into_bytes()
+    }
+
+    const VERTICES: [([f32; 3], [u8; 3]); 5] = [
+        ([0.0, 0.0, 0.0], [255, 0, 0]),
+        ([2.0, 0.0, 0.0], [0, 255, 0]),
+        ([2.0, 2.0, 0.0], [0, 0, 255]),
+        ([1.5, 0.5, 0.0], [255, 255, 255]),
+        ([0.0, 2.0, 0.0], [0, 0, 0]),
+    ];
+
+    fn binary(big_endian: bool) -> Vec<u8> {
+        let mut bytes = header(if big_endian {
+            "binary_big_endian"
+        } else {
+            "binary_little_endian"
+        });
+        let int = |value: i32| {
+            if big_endian {
+                value.to_be_bytes()
+            } else {
+                value.to_le_bytes()
+            }
+        };
+        for (position, color) in VERTICES {
+            for value in position {
+                bytes.extend_from_slice(&if big_endian {
+                    value.to_be_bytes()
+                } else {
+                    value.to_le_bytes()
+                });
+            }
+            bytes.extend_from_slice(&color);
+        }
+        bytes.extend_from_slice(&int(0));
+        bytes.extend_from_slice(&int(1));
+        // A concave quad and a triangle
+        bytes.push(4);
+        for index in [0, 1, 2, 3] {
+            bytes.extend_from_slice(&int(index));
+        }
+        bytes.push(3);
+        for index in [0, 3, 4] {
+            bytes.extend_from_slice(&int(index));
+        }
+        bytes
+    }
+
+    fn ascii() -> Vec<u8> {
+        let mut bytes = header("ascii");
+        for (position, color) in VERTICES {
+            bytes.extend_from_slice(
+                format!(
+                    "{} {} {} {} {} {}\n",
+                    position[0], position[1], position[2], color[0], color[1], color[2]
+                )
+                .as_bytes(),
+            );
+        }
+        bytes.extend_from_slice(b"0 1\n4 0 1 2 3\n3 0 3 4\n");
+        bytes
+    }
+
+    #[test]
+    fn every_encoding_yields_the_same_mesh() {
+        let reference = parse(&ascii()).expect("ASCII PLY should parse");
+        let mesh = &reference.objects[0].meshes[0];
+        assert_eq!(mesh.positions.len(), 5 * 3);
+        assert_eq!(mesh.colors[..6], [1.0, 0.0, 0.0, 0.0, 1.0, 0.0]);
+        assert_eq!(mesh.colors[9..12], [1.0, 1.0, 1.0]);
+        // The concave quad is ear-clipped into two triangles, plus the triangle face
+        assert_eq!(mesh.indices.len(), 9);
+        assert!(mesh.texcoords.is_empty());
+        // Generated normals face +Z for this counter-clockwise, planar mesh
+        assert!(
+            mesh.normals
+                .chunks(3)
+                .all(|normal| (normal[2] - 1.0).abs() < 1e-6)
+        );
+
+        for big_endian in [false, true] {
+            let scene = parse(&binary(big_endian)).expect("binary PLY should parse");
+            let binary_mesh = &scene.objects[0].meshes[0];
+            assert_eq!(binary_mesh.positions, mesh.positions);
+            assert_eq!(binary_mesh.colors, mesh.colors);
+            assert_eq!(binary_mesh.indices, mesh.indices);
+        }
+    }
+
+    #[test]
+    fn concave_faces_use_ear_clipping() {
+        let scene = parse(&ascii()).expect("ASCII PLY should parse");
+        let indices = &scene.objects[0].meshes[0].indices;
+        // A fan from vertex 0 would emit (0, 2, 3), which covers the reflex vertex 3 wrongly
+        assert!(
+            !indices[..6]
+                .chunks(3)
+                .any(|triangle| triangle.contains(&0) && triangle.contains(&2))
+        );
+    }
+
+    #[test]
+    fn reads_normals_texcoords_and_float_colors() {
+        let text = "\
+ply
+format ascii 1.0
+element vertex 3
+property double x
+property double y
+property double z
+property float nx
+property float ny
+property float nz
+property float s
+property float t
+property float red
+property float green
+property float blue
+property uchar alpha
+element face 1
+property list uint8 uint32 vertex_index
+end_header
+0 0 0 0 0 -1 0 0 0.5 0.25 1 255
+1 0 0 0 0 -1 1 0 0.5 0.25 1 255
+0 1 0 0 0 -1 0 1 0.5 0.25 1 255
+3 0 1 2
+";
+        let scene = parse(text.as_bytes()).expect("PLY should parse");
+        let mesh = &scene.objects[0].meshes[0];
+        assert_eq!(mesh.normals[..3], [0.0, 0.0, -1.0]);
+        assert_eq!(mesh.texcoords, vec![0.0, 0.0, 1.0, 0.0, 0.0, 1.0]);
+        assert_eq!(mesh.colors[..3], [0.5, 0.25, 1.0]);
+        assert_eq!(mesh.indices, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn rejects_malformed_files() {
+        assert!(parse(b"obj\n").is_err());
+        assert!(parse(b"ply\nformat ascii 1.0\nelement vertex 1\n").is_err());
+        assert!(parse(b"ply\nformat utf16 1.0\nend_header\n").is_err());
+        assert!(parse(b"ply\nformat ascii 1.0\nproperty float x\nend_header\n").is_err());
+
+        let mut truncated = binary(false);
+        truncated.pop();
+        assert!(parse(&truncated).is_err());
+
+        let mut out_of_range = ascii();
+        out_of_range.truncate(out_of_range.len() - "3 0 3 4\n".len());
+        out_of_range.extend_from_slice(b"3 0 3 9\n");
+        assert!(parse(&out_of_range).is_err());
+
+        let mut degenerate = ascii();
+        degenerate.truncate(degenerate.len() - "3 0 3 4\n".len());
+        degenerate.extend_from_slice(b"2 0 3\n");
+        assert!(parse(&degenerate).is_err());
+
+        // Header counts are not trusted for allocation before the body is read
+        for count in ["18446744073709551615", "4000000000"] {
+            let huge = format!(
+                "ply\nformat binary_little_endian 1.0\nelement vertex 0\nproperty float x\n\
+                 property float y\nproperty float z\nelement face {}\n\
+                 property list uchar int vertex_indices\nend_header\n",
+                count
+            );
+            assert!(parse(huge.as_bytes()).is_err());
+        }
+    }
+
+    #[test]
+    fn skips_elements_without_properties_at_once() {
+        let text = String::from_utf8(ascii()).expect("ASCII fixture");
+        let with_empty_element = text.replacen(
+            "end_header\n",
+            "element junk 18446744073709551615\nend_header\n",
+            1,
+        );
+        let expected = parse(&ascii()).expect("ASCII fixture should parse");
+        let scene = parse(with_empty_element.as_bytes()).expect("empty element should be skipped");
+        assert_eq!(
+            scene.objects[0].meshes[0].indices,
+            expected.objects[0].meshes[0].indices
+        );
+    }
+}
@@ -0,0 +1,764 @@
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+use super::inflate::zlib_decompress;
+use crate::loaders::bmp::image::{Image, Pixel};
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n'];
+
+const GRAYSCALE: u8 = 0;
+const TRUECOLOR: u8 = 2;
+const INDEXED: u8 = 3;
+const GRAYSCALE_ALPHA: u8 = 4;
+const TRUECOLOR_ALPHA: u8 = 6;
+
+/// Pass origins and steps `(x0, y0, dx, dy)` of the Adam7 interlacing scheme.
+const ADAM7_PASSES: [(usize, usize, usize, usize); 7] = [
+    (0, 0, 8, 8),
+    (4, 0, 8, 8),
+    (0, 4, 4, 8),
+    (2, 0, 4, 4),
+    (0, 2, 2, 4),
+    (1, 0, 2, 2),
+    (0, 1, 1, 2),
+];
+
+const CRC_TABLE: [u32; 256] = crc_table();
+
+struct PngHeader {
+    width: u32,
+    height: u32,
+    bit_depth: u8,
+    color_type: u8,
+    interlaced: bool,
+}
+
+impl PngHeader {
+    fn channels(&self) -> usize {
+        match self.color_type {
+            TRUECOLOR => 3,
+            GRAYSCALE_ALPHA => 2,
+            TRUECOLOR_ALPHA => 4,
+            _ => 1,
+        }
+    }
+
+    fn bits_per_pixel(&self) -> usize {
+        self.channels() * self.bit_depth as usize
+    }
+
+    /// Bytes of one unfiltered row of `width` pixels, without the filter type byte.
+    fn row_bytes(&self, width: usize) -> usize {
+        (width * self.bits_per_pixel()).div_ceil(8)
+    }
+}
+
+/// Transparency from a tRNS chunk.
+enum Transparency {
+    None,
+    /// Alpha per palette entry; entries past the end are opaque.
+    Palette(Vec<u8>),
+    /// Sample value(s) at the image bit depth that are fully transparent.
+    Key([u16; 3]),
+}
+
+/// Loads a PNG file into an `Image`.
+pub fn open<P: AsRef<Path>>(path: P) -> Result<Image, String> {
+    let mut file = fs::File::open(&path).map_err(|error| {
+        format!(
+            "Failed to open PNG '{}': {}",
+            path.as_ref().display(),
+            error
+        )
+    })?;
+    from_reader(&mut file)
+}
+
+/// Reads and decodes a PNG image from `source`.
+pub fn from_reader<R: Read>(source: &mut R) -> Result<Image, String> {
+    let mut bytes = Vec::new();
+    source
+        .read_to_end(&mut bytes)
+        .map_err(|error| format!("Failed to read PNG data: {}", error))?;
+    decode(&bytes)
+}
+
+/// Decodes a PNG image of any color type and bit depth, interlaced or not.
+///
+/// Every chunk CRC is verified. Samples are reduced to 8 bits (16-bit samples keep their high
+/// byte, low bit depths are scaled up), and tRNS transparency becomes the pixel alpha.
+pub fn decode(bytes: &[u8]) -> Result<Image, String> {
+    if bytes.get(..8) != Some(&SIGNATURE[..]) {
+        return Err("Not a PNG file (signature mismatch)".to_string());
+    }
+
+    let mut header = None;
+    let mut palette: Vec<Pixel> = Vec::new();
+    let mut transparency = Transparency::None;
+    let mut compressed = Vec::new();
+    let mut offset = 8;
+    let mut seen_end = false;
+
+    while offset < bytes.len() {
+        let (chunk_type, data, next) = read_chunk(bytes, offset)?;
+        offset = next;
+
+        match &chunk_type {
+            b"IHDR" => header = Some(read_header(data)?),
+            b"PLTE" => {
+                if data.len() % 3 != 0 || data.len() > 256 * 3 {
+                    return Err(format!("Invalid PNG PLTE chunk length {}", data.len()));
+                }
+                palette = data
+                    .chunks_exact(3)
+                    .map(|rgb| Pixel::new(rgb[0], rgb[1], rgb[2]))
+                    .collect();
+            }
+            b"tRNS" => {
+                let header = header
+                    .as_ref()
+                    .ok_or_else(|| "PNG tRNS chunk before IHDR".to_string())?;
+                transparency = read_transparency(header, data)?;
+            }
+            b"IDAT" => compressed.extend_from_slice(data),
+            b"IEND" => {
+                seen_end = true;
+                break;
+            }
+            // Unknown critical chunks (uppercase first letter) cannot be skipped safely
+            _ if chunk_type[0].is_ascii_uppercase() => {
+                return Err(format!(
+                    "Unsupported critical PNG chunk '{}'",
+                    String::from_utf8_lossy(&chunk_type)
+                ));
+            }
+            _ => (),
+        }
+    }
+
+    let header = header.ok_or_else(|| "PNG file has no IHDR chunk".to_string())?;
+    if !seen_end {
+        return Err("PNG file has no IEND chunk".to_string());
+    }
+    if compressed.is_empty() {
+        return Err("PNG file has no IDAT chunk".to_string());
+    }
+    if header.color_type == INDEXED && palette.is_empty() {
+        return Err("Indexed PNG file has no PLTE chunk".to_string());
+    }
+
+    let width = header.width as usize;
+    let height = header.height as usize;
+    let passes: Vec<(usize, usize, usize, usize)> = if header.interlaced {
+        ADAM7_PASSES.to_vec()
+    } else {
+        vec![(0, 0, 1, 1)]
+    };
+
+    // Every pass stores `pass_height` rows of a filter byte plus `row_bytes`; the IHDR
+    // dimensions fix the exact size, so inflate stops as soon as the data grows past it.
+    let too_large = || {
+        format!(
+            "PNG dimensions {}x{} are too large",
+            header.width, header.height
+        )
+    };
+    let mut pass_sizes = Vec::with_capacity(passes.len());
+    let mut expected_size = 0usize;
+    for &(x0, y0, dx, dy) in &passes {
+        let pass_width = width.saturating_sub(x0).div_ceil(dx);
+        let pass_height = height.saturating_sub(y0).div_ceil(dy);
+        let pass_size = if pass_width == 0 || pass_height == 0 {
+            0
+        } else {
+            pass_width
+                .checked_mul(header.bits_per_pixel())
+                .and_then(|bits| (bits.div_ceil(8) + 1).checked_mul(pass_height))
+                .ok_or_else(too_large)?
+        };
+        expected_size = expected_size.checked_add(pass_size).ok_or_else(too_large)?;
+        pass_sizes.push((pass_width, pass_height, pass_size));
+    }
+
+    let raw = zlib_decompress(&compressed, expected_size)?;
+    if raw.len() < expected_size {
+        return Err(format!(
+            "PNG image data is too short ({} bytes, expected {})",
+            raw.len(),
+            expected_size
+        ));
+    }
+
+    let mut image = Image::new(header.width, header.height);
+    let mut cursor = 0;
+
+    for (&(x0, y0, dx, dy), &(pass_width, pass_height, pass_size)) in passes.iter().zip(&pass_sizes)
+    {
+        if pass_width == 0 || pass_height == 0 {
+            continue;
+        }
+
+        let row_bytes = header.row_bytes(pass_width);
+        let filtered = &raw[cursor..cursor + pass_size];
+        cursor += pass_size;
+
+        let rows = unfilter(filtered, row_bytes, header.bits_per_pixel().div_ceil(8))?;
+        for (pass_y, row) in rows.chunks_exact(row_bytes).enumerate() {
+            for pass_x in 0..pass_width {
+                let pixel = read_pixel(&header, row, pass_x, &palette, &transparency)?;
+                image.set_pixel((x0 + pass_x * dx) as u32, (y0 + pass_y * dy) as u32, pixel);
+            }
+        }
+    }
+
+    Ok(image)
+}
+
+/// Returns the chunk type, its data and the offset of the next chunk, after checking the CRC.
+fn read_chunk(bytes: &[u8], offset: usize) -> Result<([u8; 4], &[u8], usize), String> {
+    let truncated = || format!("PNG chunk at offset {} is truncated", offset);
+    let length_bytes = bytes.get(offset..offset + 4).ok_or_else(truncated)?;
+    let length = u32::from_be_bytes(length_bytes.try_into().expect("4 bytes")) as usize;
+    let type_and_data = bytes
+        .get(offset + 4..offset + 8 + length)
+        .ok_or_else(truncated)?;
+    let crc_bytes = bytes
+        .get(offset + 8 + length..offset + 12 + length)
+        .ok_or_else(truncated)?;
+
+    let chunk_type: [u8; 4] = type_and_data[..4].try_into().expect("4 bytes");
+    let expected = u32::from_be_bytes(crc_bytes.try_into().expect("4 bytes"));
+    let actual = crc32(type_and_data);
+    if expected != actual {
+        return Err(format!(
+            "PNG chunk '{}' CRC mismatch (expected {:#010x}, computed {:#010x})",
+            String::from_utf8_lossy(&chunk_type),
+            expected,
+            actual
+        ));
+    }
+
+    Ok((chunk_type, &type_and_data[4..], offset + 12 + length))
+}
+
+fn read_header(data: &[u8]) -> Result<PngHeader, String> {
+    if data.len() != 13 {
+        return Err(format!("Invalid PNG IHDR chunk length {}", data.len()));
+    }
+    let header = PngHeader {
+        width: u32::from_be_bytes(data[0..4].try_into().expect("4 bytes")),
+        height: u32::from_be_bytes(data[4..8].try_into().expect("4 bytes")),
+        bit_depth: data[8],
+        color_type: data[9],
+        interlaced: match data[12] {
+            0 => false,
+            1 => true,
+            other => return Err(format!("Unsupported PNG interlace method {}", other)),
+        },
+    };
+
+    let depth_valid = match header.color_type {
+        GRAYSCALE => matches!(header.bit_depth, 1 | 2 | 4 | 8 | 16),
+        INDEXED => matches!(header.bit_depth, 1 | 2 | 4 | 8),
+        TRUECOLOR | GRAYSCALE_ALPHA | TRUECOLOR_ALPHA => matches!(header.bit_depth, 8 | 16),
+        other => return Err(format!("Unsupported PNG color type {}", other)),
+    };
+    if !depth_valid {
+        return Err(format!(
+            "Invalid PNG bit depth {} for color type {}",
+            header.bit_depth, header.color_type
+        ));
+    }
+    if data[10] != 0 || data[11] != 0 {
+        return Err("Unsupported PNG compression or filter method".to_string());
+    }
+    if header.width == 0 || header.height == 0 {
+        return Err(format!(
+            "PNG image has invalid dimensions {}x{}",
+            header.width, header.height
+        ));
+    }
+
+    Ok(header)
+}
+
+fn read_transparency(header: &PngHeader, data: &[u8]) -> Result<Transparency, String> {
+    let sample = |index: usize| -> Result<u16, String> {
+        data.get(index * 2..index * 2 + 2)
+            .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+            .ok_or_else(|| format!("Invalid PNG tRNS chunk length {}", data.len()))
+    };
+    match header.color_type {
+        INDEXED => Ok(Transparency::Palette(data.to_vec())),
+        GRAYSCALE => {
+            let gray = sample(0)?;
+            Ok(Transparency::Key([gray, gray, gray]))
+        }
+        TRUECOLOR => Ok(Transparency::Key([sample(0)?, sample(1)?, sample(2)?])),
+        // Images with an alpha channel must not carry tRNS; ignore it
+        _ => Ok(Transparency::None),
+    }
+}
+
+/// Reverses the per-row filters. `bpp` is the filter's byte distance to the left neighbor.
+fn unfilter(filtered: &[u8], row_bytes: usize, bpp: usize) -> Result<Vec<u8>, String> {
+    let rows = filtered.len() / (row_bytes + 1);
+    let mut output = vec![0u8; rows * row_bytes];
+    let mut previous = vec![0u8; row_bytes];
+
+    for (row_index, line) in filtered.chunks_exact(row_bytes + 1).enumerate() {
+        let (filter, data) = (line[0], &line[1..]);
+        let current = &mut output[row_index * row_bytes..(row_index + 1) * row_bytes];
+        for i in 0..row_bytes {
+            let left = if i >= bpp { current[i - bpp] } else { 0 };
+            let up = previous[i];
+            let up_left = if i >= bpp { previous[i - bpp] } else { 0 };
+            let predictor = match filter {
+                0 => 0,
+                1 => left,
+                2 => up,
+                3 => ((left as u16 + up as u16) / 2) as u8,
+                4 => paeth(left, up, up_left),
+                other => return Err(format!("Invalid PNG filter type {}", other)),
+            };
+            current[i] = data[i].wrapping_add(predictor);
+        }
+        previous.copy_from_slice(current);
+    }
+
+    Ok(output)
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let (pa, pb, pc) = (
+        (p - a as i16).abs(),
+        (p - b as i16).abs(),
+        (p - c as i16).abs(),
+    );
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+/// Reads sample `index` of a row at the image bit depth.
+fn sample(row: &[u8], index: usize, bit_depth: u8) -> u16 {
+    match bit_depth {
+        16 => u16::from_be_bytes([row[index * 2], row[index * 2 + 1]]),
+        8 => row[index] as u16,
+        depth => {
+            let depth = depth as usize;
+            let bit = index * depth;
+            let shift = 8 - depth - bit % 8;
+            ((row[bit / 8] >> shift) & ((1u8 << depth) - 1)) as u16
+        }
+    }
+}
+
+/// Scales a sample at `bit_depth` to 8 bits.
+fn to_8_bit(value: u16, bit_depth: u8) -> u8 {
+    match bit_depth {
+        16 => (value >> 8) as u8,
+        8 => value as u8,
+        depth => (value as u32 * 255 / ((1u32 << depth) - 1)) as u8,
+    }
+}
+
+fn read_pixel(
+    header: &PngHeader,
+    row: &[u8],
+    x: usize,
+    palette: &[Pixel],
+    transparency: &Transparency,
+) -> Result<Pixel, String> {
+    let depth = header.bit_depth;
+    let channels = header.channels();
+    let samples: Vec<u16> = (0..channels)
+        .map(|channel| sample(row, x * channels + channel, depth))
+        .collect();
+
+    let pixel = match header.color_type {
+        INDEXED => {
+            let index = samples[0] as usize;
+            let mut pixel = *palette.get(index).ok_or_else(|| {
+                format!(
+                    "PNG palette index {} out of bounds (palette size={})",
+                    index,
+                    palette.len()
+                )
+            })?;
+            if let Transparency::Palette(alphas) = transparency {
+                pixel.a = alphas.get(index).copied().unwrap_or(255);
+            }
+            pixel
+        }
+        GRAYSCALE | GRAYSCALE_ALPHA => {
+            let gray = to_8_bit(samples[0], depth);
+            let alpha = match (samples.get(1), transparency) {
+                (Some(&alpha), _) => to_8_bit(alpha, depth),
+                (None, Transparency::Key(key)) if key[0] == samples[0] => 0,
+                _ => 255,
+            };
+            Pixel::with_alpha(gray, gray, gray, alpha)
+        }
+        _ => {
+            let [r, g, b] = [0, 1, 2].map(|channel| to_8_bit(samples[channel], depth));
+            let alpha = match (samples.get(3), transparency) {
+                (Some(&alpha), _) => to_8_bit(alpha, depth),
+                (None, Transparency::Key(key)) if key[..] == samples[..3] => 0,
+                _ => 255,
+            };
+            Pixel::with_alpha(r, g, b, alpha)
+        }
+    };
+    Ok(pixel)
+}
+
+const fn crc_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut n = 0;
+    while n < 256 {
+        let mut c = n as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 != 0 {
+                0xEDB8_8320 ^ (c >> 1)
+            } else {
+                c >> 1
+            };
+            k += 1;
+        }
+        table[n] = c;
+        n += 1;
+    }
+    table
+}
+
+/// CRC-32 as used by PNG chunks (over the chunk type and data).
+fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0u32, |crc, &byte| {
+        CRC_TABLE[((crc ^ byte as u32) & 0xFF) as usize] ^ (crc >> 8)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ADAM7_PASSES, SIGNATURE, crc32, decode};
+    use crate::loaders::bmp::image::{Image, Pixel};
+    use crate::loaders::png::inflate::adler32;
+
+    fn chunk(chunk_type: &[u8; 4], data: &[u8]) -> Vec<u8> {
+        let mut bytes = (data.len() as u32).to_be_bytes().to_vec();
+        bytes.extend_from_slice(chunk_type);
+        bytes.extend_from_slice(data);
+        bytes.extend_from_slice(&crc32(&bytes[4..]).to_be_bytes());
+        bytes
+    }
+
+    /// Wraps `data` in a zlib stream made of stored (uncompressed) DEFLATE blocks.
+    fn zlib_stored(data: &[u8]) -> Vec<u8> {
+        let mut bytes = vec![0x78, 0x01];
+        let blocks: Vec<&[u8]> = data.chunks(65_535).collect();
+        for (index, block) in blocks.iter().enumerate() {
+            bytes.push((index + 1 == blocks.len()) as u8);
+            bytes.extend_from_slice(&(block.len() as u16).to_le_bytes());
+            bytes.extend_from_slice(&(!(block.len() as u16)).to_le_bytes());
+            bytes.extend_from_slice(block);
+        }
+        bytes.extend_from_slice(&adler32(data).to_be_bytes());
+        bytes
+    }
+
+    fn png(
+        (width, height): (u32, u32),
+        bit_depth: u8,
+        color_type: u8,
+        interlaced: bool,
+        extra_chunks: &[(&[u8; 4], &[u8])],
+        filtered: &[u8],
+    ) -> Vec<u8> {
+        let mut ihdr = width.to_be_bytes().to_vec();
+        ihdr.extend_from_slice(&height.to_be_bytes());
+        ihdr.extend_from_slice(&[bit_depth, color_type, 0, 0, interlaced as u8]);
+
+        let mut bytes = SIGNATURE.to_vec();
+        bytes.extend(chunk(b"IHDR", &ihdr));
+        for (chunk_type, data) in extra_chunks {
+            bytes.extend(chunk(chunk_type, data));
+        }
+        // Split the image data over two IDAT chunks
+        let stream = zlib_stored(filtered);
+        let (first, second) = stream.split_at(stream.len() / 2);
+        bytes.extend(chunk(b"IDAT", first));
+        bytes.extend(chunk(b"IDAT", second));
+        bytes.extend(chunk(b"IEND", &[]));
+        bytes
+    }
+
+    /// Prefixes every row with filter type 0.
+    fn unfiltered(rows: &[&[u8]]) -> Vec<u8> {
+        rows.iter()
+            .flat_map(|row| std::iter::once(0).chain(row.iter().copied()))
+            .collect()
+    }
+
+    fn filter_row(filter: u8, row: &[u8], previous: &[u8], bpp: usize) -> Vec<u8> {
+        let mut out = vec![filter];
+        for i in 0..row.len() {
+            let left = if i >= bpp { row[i - bpp] } else { 0 };
+            let up = previous[i];
+            let up_left = if i >= bpp { previous[i - bpp] } else { 0 };
+            let predictor = match filter {
+                0 => 0,
+                1 => left,
+                2 => up,
+                3 => ((left as u16 + up as u16) / 2) as u8,
+                _ => super::paeth(left, up, up_left),
+            };
+            out.push(row[i].wrapping_sub(predictor));
+        }
+        out
+    }
+
+    fn rgb_rows(image: &[Vec<Pixel>]) -> Vec<Vec<u8>> {
+        image
+            .iter()
+            .map(|row| row.iter().flat_map(|p| [p.r, p.g, p.b]).collect())
+            .collect()
+    }
+
+    fn assert_image(image: &Image, expected: &[Vec<Pixel>]) {
+        assert_eq!(image.get_height() as usize, expected.len());
+        for (y, row) in expected.iter().enumerate() {
+            assert_eq!(image.get_width() as usize, row.len());
+            for (x, pixel) in row.iter().enumerate() {
+                assert_eq!(
+                    image.get_pixel(x as u32, y as u32),
+                    *pixel,
+                    "at ({}, {})",
+                    x,
+                    y
+                );
+            }
+        }
+    }
+
+    fn test_pattern(width: usize, height: usize) -> Vec<Vec<Pixel>> {
+        (0..height)
+            .map(|y| {
+                (0..width)
+                    .map(|x| Pixel::new((x * 50 + y) as u8, (y * 40 + 7) as u8, (x * y * 9) as u8))
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn decodes_rgba_rows_with_every_filter_type() {
+        let expected: Vec<Vec<Pixel>> = (0..5)
+            .map(|y| {
+                (0..3)
+                    .map(|x| {
+                        Pixel::with_alpha(
+                            (x * 90 + y * 13) as u8,
+                            (250 - y * 30) as u8,
+                            77,
+                            (x * 60 + 100) as u8,
+                        )
+                    })
+                    .collect()
+            })
+            .collect();
+        let rows: Vec<Vec<u8>> = expected
+            .iter()
+            .map(|row| row.iter().flat_map(|p| [p.r, p.g, p.b, p.a]).collect())
+            .collect();
+
+        let mut filtered = Vec::new();
+        let mut previous = vec![0u8; 12];
+        for (filter, row) in rows.iter().enumerate() {
+            filtered.extend(filter_row(filter as u8, row, &previous, 4));
+            previous = row.clone();
+        }
+
+        let image = decode(&png((3, 5), 8, 6, false, &[], &filtered)).expect("PNG should decode");
+        assert_image(&image, &expected);
+        assert!(image.has_alpha());
+    }
+
+    #[test]
+    fn decodes_low_bit_depth_grayscale_with_transparent_key() {
+        // 1-bit: 10110, 2-bit: 0 1 2 3 0, 4-bit: 0 5 10 15 15
+        let cases: [(u8, &[u8], [u8; 5]); 3] = [
+            (1, &[0b1011_0000], [255, 0, 255, 255, 0]),
+            (2, &[0b0001_1011, 0b0000_0000], [0, 85, 170, 255, 0]),
+            (4, &[0x05, 0xAF, 0xF0], [0, 85, 170, 255, 255]),
+        ];
+        for (depth, row, grays) in cases {
+            // Gray level 0 is the transparent key
+            let image = decode(&png(
+                (5, 1),
+                depth,
+                0,
+                false,
+                &[(b"tRNS", &[0, 0])],
+                &unfiltered(&[row]),
+            ))
+            .expect("grayscale PNG should decode");
+            for (x, gray) in grays.into_iter().enumerate() {
+                let alpha = if gray == 0 { 0 } else { 255 };
+                assert_eq!(
+                    image.get_pixel(x as u32, 0),
+                    Pixel::with_alpha(gray, gray, gray, alpha),
+                    "depth {} x {}",
+                    depth,
+                    x
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn decodes_indexed_with_palette_alpha() {
+        let plte = [255, 0, 0, 0, 255, 0, 0, 0, 255];
+        // Only the first entry has an explicit alpha
+        let image = decode(&png(
+            (3, 1),
+            2,
+            3,
+            false,
+            &[(b"PLTE", &plte), (b"tRNS", &[64])],
+            &unfiltered(&[&[0b1001_0000]]),
+        ))
+        .expect("indexed PNG should decode");
+
+        assert_eq!(image.get_pixel(0, 0), Pixel::new(0, 0, 255));
+        assert_eq!(image.get_pixel(1, 0), Pixel::new(0, 255, 0));
+        assert_eq!(image.get_pixel(2, 0), Pixel::with_alpha(255, 0, 0, 64));
+    }
+
+    #[test]
+    fn decodes_16_bit_samples() {
+        // RGB with a transparent color key at full 16-bit precision
+        let row = [
+            0x12, 0x34, 0xAB, 0xCD, 0xFF, 0xFF, 0x12, 0x34, 0xAB, 0xCD, 0xFF, 0xFE,
+        ];
+        let key = [0x12, 0x34, 0xAB, 0xCD, 0xFF, 0xFF];
+        let image = decode(&png(
+            (2, 1),
+            16,
+            2,
+            false,
+            &[(b"tRNS", &key)],
+            &unfiltered(&[&row]),
+        ))
+        .expect("16-bit RGB PNG should decode");
+        assert_eq!(
+            image.get_pixel(0, 0),
+            Pixel::with_alpha(0x12, 0xAB, 0xFF, 0)
+        );
+        assert_eq!(image.get_pixel(1, 0), Pixel::new(0x12, 0xAB, 0xFF));
+
+        let gray_alpha = [0x80, 0x00, 0x40, 0x00];
+        let image = decode(&png((1, 1), 16, 4, false, &[], &unfiltered(&[&gray_alpha])))
+            .expect("16-bit gray + alpha PNG should decode");
+        assert_eq!(
+            image.get_pixel(0, 0),
+            Pixel::with_alpha(0x80, 0x80, 0x80, 0x40)
+        );
+    }
+
+    #[test]
+    fn adam7_interlaced_images_match_progressive_ones() {
+        for (width, height) in [(1, 1), (5, 5), (9, 3), (16, 11)] {
+            let expected = test_pattern(width, height);
+            let rows = rgb_rows(&expected);
+
+            let mut interlaced = Vec::new();
+            for (x0, y0, dx, dy) in ADAM7_PASSES {
+                for y in (y0..height).step_by(dy) {
+                    let pass_row: Vec<u8> = (x0..width)
+                        .step_by(dx)
+                        .flat_map(|x| rows[y][x * 3..x * 3 + 3].to_vec())
+                        .collect();
+                    if !pass_row.is_empty() {
+                        interlaced.push(0);
+                        interlaced.extend(pass_row);
+                    }
+                }
+            }
+
+            let image = decode(&png(
+                (width as u32, height as u32),
+                8,
+                2,
+                true,
+                &[],
+                &interlaced,
+            ))
+            .expect("interlaced PNG should decode");
+            assert_image(&image, &expected);
+        }
+    }
+
+    #[test]
+    fn rejects_corrupt_files() {
+        let row: &[u8] = &[1, 2, 3];
+        let valid = png((1, 1), 8, 2, false, &[], &unfiltered(&[row]));
+        assert!(decode(&valid).is_ok());
+
+        let mut bad_crc = valid.clone();
+        bad_crc[20] ^= 0xFF;
+        assert!(decode(&bad_crc).is_err());
+
+        assert!(decode(&valid[..valid.len() - 12]).is_err(), "missing IEND");
+        assert!(decode(&valid[1..]).is_err(), "bad signature");
+
+        let unknown_critical = png((1, 1), 8, 2, false, &[(b"ABCD", &[])], &unfiltered(&[row]));
+        assert!(decode(&unknown_critical).is_err());
+        let unknown_ancillary = png(
+            (1, 1),
+            8,
+            2,
+            false,
+            &[(b"tEXt", b"a\0b")],
+            &unfiltered(&[row]),
+        );
+        assert!(decode(&unknown_ancillary).is_ok());
+
+        let bad_filter = png((1, 1), 8, 2, false, &[], &[5, 1, 2, 3]);
+        assert!(decode(&bad_filter).is_err());
+
+        let bad_depth = png((1, 1), 4, 2, false, &[], &[0, 1]);
+        assert!(decode(&bad_depth).is_err());
+
+        let missing_palette = png((1, 1), 8, 3, false, &[], &[0, 0]);
+        assert!(decode(&missing_palette).is_err());
+    }
+
+    #[test]
+    fn image_data_must_match_the_ihdr_size() {
+        // Huge dimensions backed by one row: rejected before the image is allocated.
+        let huge = png(
+            (0x7FFF_FFFF, 0x7FFF_FFFF),
+            8,
+            6,
+            false,
+            &[],
+            &[0, 1, 2, 3, 4],
+        );
+        let error = decode(&huge).unwrap_err();
+        assert!(error.contains("too short"), "{}", error);
+
+        // Inflate stops at the size the IHDR allows instead of decompressing everything.
+        let row: &[u8] = &[1, 2, 3];
+        let oversized = png((1, 1), 8, 2, false, &[], &unfiltered(&[row, row]));
+        let error = decode(&oversized).unwrap_err();
+        assert!(error.contains("exceeds the expected 4 bytes"), "{}", error);
+    }
+}
@@ -0,0 +1,430 @@
+//! zlib (RFC 1950) / DEFLATE (RFC 1951) decompression.
+
+const MAX_BITS: usize = 15;
+
+/// Base lengths and extra bits for length codes 257..=285.
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+/// Base distances and extra bits for distance codes 0..=29.
+const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DIST_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+/// Order in which code length code lengths are stored in a dynamic block header.
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+/// Decompresses a zlib stream, verifying its header and Adler-32 checksum. Streams that
+/// inflate to more than `max_output` bytes are rejected as soon as they pass it.
+pub fn zlib_decompress(data: &[u8], max_output: usize) -> Result<Vec<u8>, String> {
+    if data.len() < 6 {
+        return Err("zlib stream is too short".to_string());
+    }
+    let (cmf, flg) = (data[0], data[1]);
+    if cmf & 0x0F != 8 || cmf >> 4 > 7 {
+        return Err(format!(
+            "Unsupported zlib compression method/window: {:#04x}",
+            cmf
+        ));
+    }
+    if !(cmf as u16 * 256 + flg as u16).is_multiple_of(31) {
+        return Err("Invalid zlib header check bits".to_string());
+    }
+    if flg & 0x20 != 0 {
+        return Err("zlib preset dictionaries are not supported".to_string());
+    }
+
+    let (output, consumed) = inflate(&data[2..], max_output)?;
+    let trailer = data
+        .get(2 + consumed..2 + consumed + 4)
+        .ok_or_else(|| "zlib stream is missing its Adler-32 checksum".to_string())?;
+    let expected = u32::from_be_bytes([trailer[0], trailer[1], trailer[2], trailer[3]]);
+    let actual = adler32(&output);
+    if expected != actual {
+        return Err(format!(
+            "zlib Adler-32 mismatch (expected {:#010x}, computed {:#010x})",
+            expected, actual
+        ));
+    }
+
+    Ok(output)
+}
+
+/// Decompresses raw DEFLATE data of at most `max_output` bytes. Returns the output and the
+/// number of input bytes consumed.
+pub fn inflate(data: &[u8], max_output: usize) -> Result<(Vec<u8>, usize), String> {
+    let mut bits = BitReader::new(data);
+    let mut output = Vec::new();
+
+    loop {
+        let is_final = bits.read(1)? == 1;
+        match bits.read(2)? {
+            0 => {
+                bits.align_to_byte();
+                let len = bits.read(16)? as u16;
+                let nlen = bits.read(16)? as u16;
+                if len != !nlen {
+                    return Err("DEFLATE stored block length check failed".to_string());
+                }
+                let stored = bits.read_bytes(len as usize)?;
+                check_output_size(output.len() + stored.len(), max_output)?;
+                output.extend_from_slice(stored);
+            }
+            1 => {
+                let (literals, distances) = fixed_tables();
+                inflate_block(&mut bits, &mut output, max_output, &literals, &distances)?;
+            }
+            2 => {
+                let (literals, distances) = read_dynamic_tables(&mut bits)?;
+                inflate_block(&mut bits, &mut output, max_output, &literals, &distances)?;
+            }
+            _ => return Err("Invalid DEFLATE block type 3".to_string()),
+        }
+        if is_final {
+            break;
+        }
+    }
+
+    Ok((output, bits.bytes_consumed()))
+}
+
+/// Adler-32 checksum, as used by zlib.
+pub fn adler32(data: &[u8]) -> u32 {
+    const MOD_ADLER: u32 = 65521;
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= MOD_ADLER;
+        b %= MOD_ADLER;
+    }
+    (b << 16) | a
+}
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+    bit_buffer: u32,
+    bit_count: u32,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> BitReader<'a> {
+        BitReader {
+            data,
+            position: 0,
+            bit_buffer: 0,
+            bit_count: 0,
+        }
+    }
+
+    /// Reads `count` (at most 16) bits, least significant first.
+    fn read(&mut self, count: u32) -> Result<u32, String> {
+        while self.bit_count < count {
+            let byte = *self
+                .data
+                .get(self.position)
+                .ok_or_else(|| "DEFLATE data ended unexpectedly".to_string())?;
+            self.position += 1;
+            self.bit_buffer |= (byte as u32) << self.bit_count;
+            self.bit_count += 8;
+        }
+        let value = self.bit_buffer & ((1u32 << count) - 1);
+        self.bit_buffer >>= count;
+        self.bit_count -= count;
+        Ok(value)
+    }
+
+    fn align_to_byte(&mut self) {
+        let remainder = self.bit_count % 8;
+        self.bit_buffer >>= remainder;
+        self.bit_count -= remainder;
+    }
+
+    /// Reads whole bytes; only valid after `align_to_byte`.
+    fn read_bytes(&mut self, count: usize) -> Result<&'a [u8], String> {
+        // Give back bytes that were buffered but not consumed
+        self.position -= (self.bit_count / 8) as usize;
+        self.bit_buffer = 0;
+        self.bit_count = 0;
+        let bytes = self
+            .data
+            .get(self.position..self.position + count)
+            .ok_or_else(|| "DEFLATE stored block exceeds input".to_string())?;
+        self.position += count;
+        Ok(bytes)
+    }
+
+    fn bytes_consumed(&self) -> usize {
+        self.position - (self.bit_count / 8) as usize
+    }
+}
+
+/// Canonical Huffman decoding table: code counts per length and symbols ordered by code.
+struct Huffman {
+    counts: [u16; MAX_BITS + 1],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Result<Huffman, String> {
+        let mut counts = [0u16; MAX_BITS + 1];
+        for &length in lengths {
+            counts[length as usize] += 1;
+        }
+        counts[0] = 0;
+
+        // Reject over-subscribed code sets; incomplete ones are allowed (e.g. single distance code)
+        let mut left: i32 = 1;
+        for &count in &counts[1..] {
+            left = (left << 1) - count as i32;
+            if left < 0 {
+                return Err("Over-subscribed Huffman code lengths in DEFLATE data".to_string());
+            }
+        }
+
+        let mut offsets = [0u16; MAX_BITS + 2];
+        for length in 1..=MAX_BITS {
+            offsets[length + 1] = offsets[length] + counts[length];
+        }
+        let mut symbols = vec![0u16; offsets[MAX_BITS + 1] as usize];
+        for (symbol, &length) in lengths.iter().enumerate() {
+            if length != 0 {
+                symbols[offsets[length as usize] as usize] = symbol as u16;
+                offsets[length as usize] += 1;
+            }
+        }
+
+        Ok(Huffman { counts, symbols })
+    }
+
+    fn decode(&self, bits: &mut BitReader) -> Result<u16, String> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for length in 1..=MAX_BITS {
+            code |= bits.read(1)? as i32;
+            let count = self.counts[length] as i32;
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err("Invalid Huffman code in DEFLATE data".to_string())
+    }
+}
+
+fn fixed_tables() -> (Huffman, Huffman) {
+    let mut lengths = [0u8; 288];
+    lengths[..144].fill(8);
+    lengths[144..256].fill(9);
+    lengths[256..280].fill(7);
+    lengths[280..].fill(8);
+    let literals = Huffman::new(&lengths).expect("fixed literal table is valid");
+    let distances = Huffman::new(&[5; 30]).expect("fixed distance table is valid");
+    (literals, distances)
+}
+
+fn read_dynamic_tables(bits: &mut BitReader) -> Result<(Huffman, Huffman), String> {
+    let literal_count = bits.read(5)? as usize + 257;
+    let distance_count = bits.read(5)? as usize + 1;
+    let code_length_count = bits.read(4)? as usize + 4;
+    if literal_count > 286 || distance_count > 30 {
+        return Err("Too many literal/length or distance codes in DEFLATE header".to_string());
+    }
+
+    let mut code_length_lengths = [0u8; 19];
+    for &symbol in &CODE_LENGTH_ORDER[..code_length_count] {
+        code_length_lengths[symbol] = bits.read(3)? as u8;
+    }
+    let code_lengths = Huffman::new(&code_length_lengths)?;
+
+    let mut lengths = vec![0u8; literal_count + distance_count];
+    let mut index = 0;
+    while index < lengths.len() {
+        let symbol = code_lengths.decode(bits)?;
+        let (value, repeat) = match symbol {
+            0..=15 => (symbol as u8, 1),
+            16 => {
+                let previous = *index
+                    .checked_sub(1)
+                    .and_then(|previous| lengths.get(previous))
+                    .ok_or_else(|| "DEFLATE repeat code with no previous length".to_string())?;
+                (previous, 3 + bits.read(2)? as usize)
+            }
+            17 => (0, 3 + bits.read(3)? as usize),
+            _ => (0, 11 + bits.read(7)? as usize),
+        };
+        if index + repeat > lengths.len() {
+            return Err("DEFLATE code lengths overrun the table".to_string());
+        }
+        lengths[index..index + repeat].fill(value);
+        index += repeat;
+    }
+
+    if lengths[256] == 0 {
+        return Err("DEFLATE block has no end-of-block code".to_string());
+    }
+
+    let literals = Huffman::new(&lengths[..literal_count])?;
+    let distances = Huffman::new(&lengths[literal_count..])?;
+    Ok((literals, distances))
+}
+
+fn check_output_size(size: usize, max_output: usize) -> Result<(), String> {
+    if size > max_output {
+        return Err(format!(
+            "DEFLATE output exceeds the expected {} bytes",
+            max_output
+        ));
+    }
+    Ok(())
+}
+
+fn inflate_block(
+    bits: &mut BitReader,
+    output: &mut Vec<u8>,
+    max_output: usize,
+    literals: &Huffman,
+    distances: &Huffman,
+) -> Result<(), String> {
+    loop {
+        let symbol = literals.decode(bits)? as usize;
+        match symbol {
+            0..=255 => {
+                check_output_size(output.len() + 1, max_output)?;
+                output.push(symbol as u8);
+            }
+            256 => return Ok(()),
+            257..=285 => {
+                let code = symbol - 257;
+                let length =
+                    LENGTH_BASE[code] as usize + bits.read(LENGTH_EXTRA[code] as u32)? as usize;
+                let distance_code = distances.decode(bits)? as usize;
+                if distance_code >= 30 {
+                    return Err(format!("Invalid DEFLATE distance code {}", distance_code));
+                }
+                let distance = DIST_BASE[distance_code] as usize
+                    + bits.read(DIST_EXTRA[distance_code] as u32)? as usize;
+                if distance > output.len() {
+                    return Err(format!(
+                        "DEFLATE distance {} reaches before the start of the output",
+                        distance
+                    ));
+                }
+                check_output_size(output.len() + length, max_output)?;
+                // Copies may overlap their own output, so go byte by byte
+                let start = output.len() - distance;
+                for offset in 0..length {
+                    output.push(output[start + offset]);
+                }
+            }
+            _ => return Err(format!("Invalid DEFLATE literal/length symbol {}", symbol)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{adler32, inflate, zlib_decompress};
+
+    /// `zlib.compress(data, 9)` of the 400-byte sequence from `skewed_letters`; the stream uses
+    /// a dynamic Huffman block.
+    const DYNAMIC_STREAM: [u8; 137] = [
+        0x78, 0xda, 0x45, 0x50, 0x89, 0x0d, 0xc4, 0x30, 0x0c, 0x9a, 0xd5, 0xc0, 0xfe, 0x33, 0x5c,
+        0x79, 0xa2, 0x93, 0xaa, 0xd4, 0x01, 0x8c, 0x71, 0xc0, 0xc3, 0xdd, 0x81, 0x3e, 0xbe, 0x0f,
+        0xc7, 0x5c, 0x18, 0x54, 0x27, 0xa3, 0x0c, 0x61, 0x01, 0xa6, 0xa6, 0x21, 0x4b, 0x8d, 0xab,
+        0xb4, 0x9d, 0xf0, 0x51, 0x91, 0xd4, 0x0c, 0xd4, 0x1c, 0x27, 0x0d, 0x4c, 0xb8, 0x06, 0xd3,
+        0xea, 0xaa, 0x03, 0x14, 0xc1, 0xd5, 0xe8, 0xfd, 0xc5, 0x90, 0xcd, 0x14, 0xf0, 0x53, 0x29,
+        0xd1, 0x66, 0xf7, 0xd4, 0x8d, 0xb0, 0xbb, 0xde, 0x4e, 0xb7, 0xa2, 0xb3, 0x32, 0xde, 0x16,
+        0xec, 0xae, 0x68, 0x2a, 0xa7, 0x44, 0x57, 0x82, 0xa2, 0x3d, 0x6f, 0x4e, 0x7a, 0xae, 0xf6,
+        0x42, 0xee, 0x90, 0xe2, 0x9c, 0x2e, 0xeb, 0x95, 0x3c, 0x42, 0xf7, 0x2a, 0xae, 0x0d, 0x5d,
+        0xcf, 0xb2, 0xe1, 0x1f, 0xd2, 0x5c, 0xcc, 0x17, 0x28, 0x79, 0x1e, 0xf5, 0x03, 0x9f, 0x90,
+        0x98, 0xb0,
+    ];
+
+    /// Letters drawn with a skewed distribution by a small LCG, matching the fixture generator.
+    fn skewed_letters() -> Vec<u8> {
+        let alphabet = b"aaaaaaaabbbbccd";
+        let mut state: u32 = 1;
+        (0..400)
+            .map(|_| {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345) & 0x7FFF_FFFF;
+                alphabet[(state >> 16) as usize % alphabet.len()]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn inflates_fixed_huffman_with_overlapping_copies() {
+        // zlib.compress(b"hello hello hello hello", 9)
+        let stream = [
+            0x78, 0xda, 0xcb, 0x48, 0xcd, 0xc9, 0xc9, 0x57, 0xc8, 0x40, 0x27, 0x01, 0x68, 0x03,
+            0x08, 0xb1,
+        ];
+        let output =
+            zlib_decompress(&stream, usize::MAX).expect("fixed Huffman stream should inflate");
+        assert_eq!(output, b"hello hello hello hello");
+    }
+
+    #[test]
+    fn inflates_dynamic_huffman() {
+        let output =
+            zlib_decompress(&DYNAMIC_STREAM, usize::MAX).expect("dynamic stream should inflate");
+        assert_eq!(output, skewed_letters());
+    }
+
+    #[test]
+    fn inflates_stored_blocks() {
+        // Two stored blocks: "abc" (not final) and "de" (final)
+        let deflate = [
+            0x00, 0x03, 0x00, 0xFC, 0xFF, b'a', b'b', b'c', 0x01, 0x02, 0x00, 0xFD, 0xFF, b'd',
+            b'e',
+        ];
+        let (output, consumed) =
+            inflate(&deflate, usize::MAX).expect("stored blocks should inflate");
+        assert_eq!(output, b"abcde");
+        assert_eq!(consumed, deflate.len());
+    }
+
+    #[test]
+    fn rejects_corrupt_streams() {
+        let mut bad_checksum = DYNAMIC_STREAM;
+        let last = bad_checksum.len() - 1;
+        bad_checksum[last] ^= 1;
+        assert!(zlib_decompress(&bad_checksum, usize::MAX).is_err());
+
+        let mut bad_header = DYNAMIC_STREAM;
+        bad_header[1] ^= 1;
+        assert!(zlib_decompress(&bad_header, usize::MAX).is_err());
+
+        assert!(zlib_decompress(&DYNAMIC_STREAM[..40], usize::MAX).is_err());
+        assert!(inflate(&[0x07], usize::MAX).is_err());
+    }
+
+    #[test]
+    fn stops_once_the_output_passes_its_limit() {
+        let full = zlib_decompress(&DYNAMIC_STREAM, usize::MAX).expect("stream should inflate");
+        assert!(zlib_decompress(&DYNAMIC_STREAM, full.len()).is_ok());
+        let error = zlib_decompress(&DYNAMIC_STREAM, full.len() - 1).unwrap_err();
+        assert!(error.contains("exceeds"), "{}", error);
+    }
+
+    #[test]
+    fn computes_adler32() {
+        assert_eq!(adler32(b""), 1);
+        assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+    }
+}
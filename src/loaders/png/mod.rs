@@ -0,0 +1,29 @@
+mod decoder;
+mod inflate;
+
+use std::path::Path;
+
+use crate::loaders::registry::{TextureData, TextureDecoder};
+
+pub use decoder::{decode, from_reader, open};
+
+/// Registry entry for PNG textures.
+pub struct PngDecoder;
+
+impl TextureDecoder for PngDecoder {
+    fn name(&self) -> &'static str {
+        "PNG"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["png"]
+    }
+
+    fn matches_magic(&self, header: &[u8]) -> bool {
+        header.starts_with(b"\x89PNG\r\n\x1a\n")
+    }
+
+    fn decode(&self, path: &Path) -> Result<TextureData, String> {
+        open(path).map(|image| TextureData::Levels(vec![image]))
+    }
+}
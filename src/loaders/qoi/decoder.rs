@@ -0,0 +1,218 @@
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+use super::{
+    END_MARKER, HEADER_SIZE, MAGIC, OP_DIFF, OP_INDEX, OP_LUMA, OP_MASK, OP_RGB, OP_RGBA, OP_RUN,
+    color_hash,
+};
+use crate::loaders::bmp::image::{Image, Pixel};
+
+/// Loads a QOI file into an `Image`.
+pub fn open<P: AsRef<Path>>(path: P) -> Result<Image, String> {
+    let mut file = fs::File::open(&path).map_err(|error| {
+        format!(
+            "Failed to open QOI '{}': {}",
+            path.as_ref().display(),
+            error
+        )
+    })?;
+    from_reader(&mut file)
+}
+
+/// Reads and decodes a QOI image from `source`.
+pub fn from_reader<R: Read>(source: &mut R) -> Result<Image, String> {
+    let mut bytes = Vec::new();
+    source
+        .read_to_end(&mut bytes)
+        .map_err(|error| format!("Failed to read QOI data: {}", error))?;
+    decode(&bytes)
+}
+
+/// Decodes a QOI ("Quite OK Image") file. Three-channel images decode as opaque.
+pub fn decode(bytes: &[u8]) -> Result<Image, String> {
+    if bytes.len() < HEADER_SIZE + END_MARKER.len() || &bytes[..4] != MAGIC {
+        return Err("Not a QOI file (missing 'qoif' header)".to_string());
+    }
+    let width = u32::from_be_bytes(bytes[4..8].try_into().expect("4 bytes"));
+    let height = u32::from_be_bytes(bytes[8..12].try_into().expect("4 bytes"));
+    let channels = bytes[12];
+    if width == 0 || height == 0 {
+        return Err(format!(
+            "QOI image has invalid dimensions {}x{}",
+            width, height
+        ));
+    }
+    if !matches!(channels, 3 | 4) || bytes[13] > 1 {
+        return Err(format!(
+            "Invalid QOI channels ({}) or colorspace ({})",
+            channels, bytes[13]
+        ));
+    }
+    if !bytes.ends_with(&END_MARKER) {
+        return Err("QOI data is missing its end marker".to_string());
+    }
+
+    let data = &bytes[HEADER_SIZE..bytes.len() - END_MARKER.len()];
+    // No op byte decodes to more than a 62-pixel run, which bounds the image by its data.
+    let pixel_count = width
+        .checked_mul(height)
+        .map(|count| count as usize)
+        .filter(|&count| count.div_ceil(62) <= data.len())
+        .ok_or_else(|| {
+            format!(
+                "QOI image {}x{} is too large for its {} bytes of data",
+                width,
+                height,
+                data.len()
+            )
+        })?;
+    let truncated = || "QOI data ended before all pixels were decoded".to_string();
+
+    let mut index = [[0u8; 4]; 64];
+    let mut previous = [0u8, 0, 0, 255];
+    let mut image = Image::new(width, height);
+    let mut position = 0;
+    let mut decoded = 0;
+
+    while decoded < pixel_count {
+        let tag = *data.get(position).ok_or_else(truncated)?;
+        position += 1;
+        let mut run = 1;
+
+        match tag {
+            OP_RGB => {
+                let rgb = data.get(position..position + 3).ok_or_else(truncated)?;
+                previous = [rgb[0], rgb[1], rgb[2], previous[3]];
+                position += 3;
+            }
+            OP_RGBA => {
+                let rgba = data.get(position..position + 4).ok_or_else(truncated)?;
+                previous = [rgba[0], rgba[1], rgba[2], rgba[3]];
+                position += 4;
+            }
+            _ => match tag & OP_MASK {
+                OP_INDEX => previous = index[tag as usize],
+                OP_DIFF => {
+                    previous[0] = previous[0].wrapping_add((tag >> 4) & 0x03).wrapping_sub(2);
+                    previous[1] = previous[1].wrapping_add((tag >> 2) & 0x03).wrapping_sub(2);
+                    previous[2] = previous[2].wrapping_add(tag & 0x03).wrapping_sub(2);
+                }
+                OP_LUMA => {
+                    let second = *data.get(position).ok_or_else(truncated)?;
+                    position += 1;
+                    let dg = (tag & 0x3F).wrapping_sub(32);
+                    previous[0] = previous[0]
+                        .wrapping_add(dg)
+                        .wrapping_add(second >> 4)
+                        .wrapping_sub(8);
+                    previous[1] = previous[1].wrapping_add(dg);
+                    previous[2] = previous[2]
+                        .wrapping_add(dg)
+                        .wrapping_add(second & 0x0F)
+                        .wrapping_sub(8);
+                }
+                OP_RUN => run = (tag & 0x3F) as usize + 1,
+                _ => unreachable!("two-bit tags are exhaustive"),
+            },
+        }
+
+        index[color_hash(previous)] = previous;
+        if decoded + run > pixel_count {
+            return Err(format!(
+                "QOI run of {} pixels overruns the image ({} pixels left)",
+                run,
+                pixel_count - decoded
+            ));
+        }
+        let alpha = if channels == 4 { previous[3] } else { 255 };
+        let pixel = Pixel::with_alpha(previous[0], previous[1], previous[2], alpha);
+        for _ in 0..run {
+            image.set_pixel(
+                (decoded % width as usize) as u32,
+                (decoded / width as usize) as u32,
+                pixel,
+            );
+            decoded += 1;
+        }
+    }
+
+    if position != data.len() {
+        return Err(format!(
+            "QOI data has {} unexpected bytes after the last pixel",
+            data.len() - position
+        ));
+    }
+
+    Ok(image)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::decode;
+    use crate::loaders::bmp::image::Pixel;
+
+    fn header(width: u32, height: u32, channels: u8) -> Vec<u8> {
+        let mut bytes = b"qoif".to_vec();
+        bytes.extend_from_slice(&width.to_be_bytes());
+        bytes.extend_from_slice(&height.to_be_bytes());
+        bytes.extend_from_slice(&[channels, 0]);
+        bytes
+    }
+
+    fn with_end(mut bytes: Vec<u8>) -> Vec<u8> {
+        bytes.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 1]);
+        bytes
+    }
+
+    #[test]
+    fn decodes_every_op() {
+        let mut bytes = header(7, 1, 4);
+        bytes.extend_from_slice(&[
+            0xFE, 100, 50, 20,   // RGB
+            0x7F, // DIFF +1 +1 +1
+            0xA2, 0x99, // LUMA dg=+2, dr-dg=+1, db-dg=+1 -> (104, 53, 24)
+            0xFF, 1, 2, 3, 128,  // RGBA
+            0xC1, // RUN of 2
+        ]);
+        // INDEX of the first RGB pixel
+        bytes.push(((100 * 3 + 50 * 5 + 20 * 7 + 255 * 11) % 64) as u8);
+        let image = decode(&with_end(bytes)).expect("QOI should decode");
+
+        assert_eq!(image.get_pixel(0, 0), Pixel::new(100, 50, 20));
+        assert_eq!(image.get_pixel(1, 0), Pixel::new(101, 51, 21));
+        assert_eq!(image.get_pixel(2, 0), Pixel::new(104, 53, 24));
+        for x in 3..6 {
+            assert_eq!(image.get_pixel(x, 0), Pixel::with_alpha(1, 2, 3, 128));
+        }
+        assert_eq!(image.get_pixel(6, 0), Pixel::new(100, 50, 20));
+    }
+
+    #[test]
+    fn rejects_malformed_streams() {
+        let valid = with_end([header(1, 1, 3), vec![0xFE, 1, 2, 3]].concat());
+        assert!(decode(&valid).is_ok());
+
+        let mut bad_magic = valid.clone();
+        bad_magic[0] = b'x';
+        assert!(decode(&bad_magic).is_err());
+
+        let truncated = with_end([header(2, 1, 3), vec![0xFE, 1, 2, 3]].concat());
+        assert!(decode(&truncated).is_err());
+
+        let overrun = with_end([header(1, 1, 3), vec![0xC1]].concat());
+        assert!(decode(&overrun).is_err());
+
+        let trailing = with_end([header(1, 1, 3), vec![0xFE, 1, 2, 3, 0x00]].concat());
+        assert!(decode(&trailing).is_err());
+
+        assert!(decode(&valid[..valid.len() - 1]).is_err());
+        assert!(decode(&with_end(header(1, 1, 5))).is_err());
+
+        // Huge headers over a few bytes of data: must fail without allocating the image
+        for (width, height) in [(65535, 65535), (100_000, 100_000)] {
+            let huge = with_end([header(width, height, 4), vec![0xFD; 4]].concat());
+            assert!(decode(&huge).is_err());
+        }
+    }
+}
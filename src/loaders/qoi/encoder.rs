@@ -0,0 +1,152 @@
+use std::fs;
+use std::path::Path;
+
+use super::{
+    END_MARKER, HEADER_SIZE, MAGIC, OP_DIFF, OP_INDEX, OP_LUMA, OP_RGB, OP_RGBA, OP_RUN, color_hash,
+};
+use crate::loaders::bmp::image::Image;
+
+/// Longest run a single QOI_OP_RUN can encode; 63 and 64 would collide with the RGB(A) tags.
+const MAX_RUN: u8 = 62;
+
+/// Encodes the `Image` as QOI, with an alpha channel only when some pixel is translucent.
+pub fn encode(image: &Image) -> Result<Vec<u8>, String> {
+    let (width, height) = (image.get_width(), image.get_height());
+    if width == 0 || height == 0 {
+        return Err(format!(
+            "QOI cannot store an image of {}x{} pixels",
+            width, height
+        ));
+    }
+    let channels: u8 = if image.has_alpha() { 4 } else { 3 };
+
+    let mut bytes = Vec::with_capacity(HEADER_SIZE + END_MARKER.len() + image.data.len());
+    bytes.extend_from_slice(MAGIC);
+    bytes.extend_from_slice(&width.to_be_bytes());
+    bytes.extend_from_slice(&height.to_be_bytes());
+    bytes.push(channels);
+    // sRGB with linear alpha
+    bytes.push(0);
+
+    let mut index = [[0u8; 4]; 64];
+    let mut previous = [0u8, 0, 0, 255];
+    let mut run: u8 = 0;
+
+    for (x, y) in image.coordinates() {
+        let pixel = image.get_pixel(x, y);
+        let current = [pixel.r, pixel.g, pixel.b, pixel.a];
+
+        if current == previous {
+            run += 1;
+            if run == MAX_RUN {
+                bytes.push(OP_RUN | (run - 1));
+                run = 0;
+            }
+            continue;
+        }
+        if run > 0 {
+            bytes.push(OP_RUN | (run - 1));
+            run = 0;
+        }
+
+        let hash = color_hash(current);
+        if index[hash] == current {
+            bytes.push(OP_INDEX | hash as u8);
+        } else if current[3] != previous[3] {
+            bytes.push(OP_RGBA);
+            bytes.extend_from_slice(&current);
+        } else {
+            let [dr, dg, db] = [0, 1, 2].map(|c| current[c].wrapping_sub(previous[c]) as i8);
+            let (dr_dg, db_dg) = (dr.wrapping_sub(dg), db.wrapping_sub(dg));
+
+            if [dr, dg, db].iter().all(|d| (-2..=1).contains(d)) {
+                bytes.push(
+                    OP_DIFF | (((dr + 2) as u8) << 4) | (((dg + 2) as u8) << 2) | (db + 2) as u8,
+                );
+            } else if (-32..=31).contains(&dg)
+                && (-8..=7).contains(&dr_dg)
+                && (-8..=7).contains(&db_dg)
+            {
+                bytes.push(OP_LUMA | (dg + 32) as u8);
+                bytes.push((((dr_dg + 8) as u8) << 4) | (db_dg + 8) as u8);
+            } else {
+                bytes.push(OP_RGB);
+                bytes.extend_from_slice(&current[..3]);
+            }
+        }
+        index[hash] = current;
+        previous = current;
+    }
+    if run > 0 {
+        bytes.push(OP_RUN | (run - 1));
+    }
+
+    bytes.extend_from_slice(&END_MARKER);
+    Ok(bytes)
+}
+
+/// Encodes the `Image` as QOI and writes it to `path`.
+pub fn save<P: AsRef<Path>>(image: &Image, path: P) -> Result<(), String> {
+    let bytes = encode(image)?;
+    fs::write(&path, bytes).map_err(|error| {
+        format!(
+            "Failed to write QOI '{}': {}",
+            path.as_ref().display(),
+            error
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::encode;
+    use crate::loaders::bmp::image::{Image, Pixel};
+    use crate::loaders::qoi::decode;
+
+    fn header(width: u32, height: u32, channels: u8) -> Vec<u8> {
+        let mut bytes = b"qoif".to_vec();
+        bytes.extend_from_slice(&width.to_be_bytes());
+        bytes.extend_from_slice(&height.to_be_bytes());
+        bytes.extend_from_slice(&[channels, 0]);
+        bytes
+    }
+
+    fn with_end(mut bytes: Vec<u8>) -> Vec<u8> {
+        bytes.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 1]);
+        bytes
+    }
+
+    #[test]
+    fn encodes_runs_and_minimal_streams() {
+        // Every pixel equals the implicit previous pixel (opaque black): runs only, capped at 62
+        let image = Image::new(100, 1);
+        let bytes = encode(&image).expect("image should encode");
+        assert_eq!(
+            bytes,
+            with_end([header(100, 1, 3), vec![0xC0 | 61, 0xC0 | 37]].concat())
+        );
+    }
+
+    #[test]
+    fn round_trips_images_with_and_without_alpha() {
+        let mut image = Image::new(13, 9);
+        for (x, y) in image.coordinates() {
+            let pixel = match (x + y) % 5 {
+                0 => Pixel::new(200, 10, 10),
+                1 => Pixel::new((x * 3) as u8, (y * 2) as u8, 40),
+                2 => Pixel::new((x * 19) as u8, (y * 47) as u8, (x * y) as u8),
+                _ => Pixel::new(200, 10, 10),
+            };
+            image.set_pixel(x, y, pixel);
+        }
+        let bytes = encode(&image).expect("opaque image should encode");
+        assert_eq!(bytes[12], 3);
+        assert_eq!(decode(&bytes).expect("should decode").data, image.data);
+
+        image.set_pixel(4, 4, Pixel::with_alpha(1, 2, 3, 0));
+        image.set_pixel(5, 4, Pixel::with_alpha(1, 2, 3, 90));
+        let bytes = encode(&image).expect("translucent image should encode");
+        assert_eq!(bytes[12], 4);
+        assert_eq!(decode(&bytes).expect("should decode").data, image.data);
+    }
+}
@@ -0,0 +1,47 @@
+mod decoder;
+mod encoder;
+
+use std::path::Path;
+
+use crate::loaders::registry::{TextureData, TextureDecoder};
+
+pub use decoder::{decode, from_reader, open};
+pub use encoder::{encode, save};
+
+/// Registry entry for QOI textures.
+pub struct QoiDecoder;
+
+impl TextureDecoder for QoiDecoder {
+    fn name(&self) -> &'static str {
+        "QOI"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["qoi"]
+    }
+
+    fn matches_magic(&self, header: &[u8]) -> bool {
+        header.starts_with(MAGIC)
+    }
+
+    fn decode(&self, path: &Path) -> Result<TextureData, String> {
+        open(path).map(|image| TextureData::Levels(vec![image]))
+    }
+}
+
+const MAGIC: &[u8; 4] = b"qoif";
+const HEADER_SIZE: usize = 14;
+const END_MARKER: [u8; 8] = [0, 0, 0, 0, 0, 0, 0, 1];
+
+const OP_INDEX: u8 = 0x00;
+const OP_DIFF: u8 = 0x40;
+const OP_LUMA: u8 = 0x80;
+const OP_RUN: u8 = 0xC0;
+const OP_RGB: u8 = 0xFE;
+const OP_RGBA: u8 = 0xFF;
+const OP_MASK: u8 = 0xC0;
+
+/// Position of a color in the 64-entry table of recently seen pixels.
+fn color_hash([r, g, b, a]: [u8; 4]) -> usize {
+    (r as usize * 3 + g as usize * 5 + b as usize * 7 + a as usize * 11) % 64
+}
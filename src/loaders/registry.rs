@@ -0,0 +1,245 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use super::bmp::image::Image;
+use super::hdr::HdrImage;
+use super::obj::ObjSceneData;
+use super::{bmp, dds, gltf, hdr, netpbm, obj, ply, png, qoi, stl, tga};
+
+/// How many leading bytes are read to recognize a file by its magic number.
+const MAGIC_LENGTH: usize = 16;
+
+/// A model format that loads into the common intermediate scene (`ObjSceneData`), which the
+/// scene builder turns into a `SceneModel`.
+pub trait ModelLoader: Sync {
+    /// Format name used in messages.
+    fn name(&self) -> &'static str;
+
+    /// Lowercase file extensions of the format.
+    fn extensions(&self) -> &'static [&'static str];
+
+    /// Whether the first bytes of a file identify this format. Formats without a reliable
+    /// signature keep the default and are only picked by extension.
+    fn matches_magic(&self, _header: &[u8]) -> bool {
+        false
+    }
+
+    /// Loads the file into the intermediate scene, triangulated and with one index per vertex.
+    fn load(&self, path: &Path) -> Result<ObjSceneData, String>;
+}
+
+/// Pixels decoded by a `TextureDecoder`, ready for upload.
+#[derive(Clone, Debug)]
+pub enum TextureData {
+    /// An 8-bit mip chain, full size first. Formats that do not store mip levels give a single
+    /// image.
+    Levels(Vec<Image>),
+    /// A floating-point image, uploaded as a half-float texture.
+    Hdr(HdrImage),
+}
+
+/// A texture format that decodes into 8-bit `Image`s or a floating-point `HdrImage`.
+pub trait TextureDecoder: Sync {
+    /// Format name used in messages.
+    fn name(&self) -> &'static str;
+
+    /// Lowercase file extensions of the format.
+    fn extensions(&self) -> &'static [&'static str];
+
+    /// Whether the first bytes of a file identify this format.
+    fn matches_magic(&self, _header: &[u8]) -> bool {
+        false
+    }
+
+    /// Decodes the file into its pixels.
+    fn decode(&self, path: &Path) -> Result<TextureData, String>;
+}
+
+/// Every model format, in the order magic numbers are tried.
+pub static MODEL_LOADERS: &[&dyn ModelLoader] = &[
+    &gltf::GltfLoader,
+    &obj::ObjLoader,
+    &ply::PlyLoader,
+    &stl::StlLoader,
+];
+
+/// Every texture format, in the order magic numbers are tried.
+pub static TEXTURE_DECODERS: &[&dyn TextureDecoder] = &[
+    &bmp::BmpDecoder,
+    &dds::DdsDecoder,
+    &hdr::HdrDecoder,
+    &netpbm::NetpbmDecoder,
+    &png::PngDecoder,
+    &qoi::QoiDecoder,
+    &tga::TgaDecoder,
+];
+
+/// Finds the loader for a model file: by magic number when the file can be read, then by
+/// extension.
+pub fn model_loader_for(path: &Path) -> Option<&'static dyn ModelLoader> {
+    let header = read_header(path);
+    MODEL_LOADERS
+        .iter()
+        .find(|loader| {
+            header
+                .as_deref()
+                .is_some_and(|header| loader.matches_magic(header))
+        })
+        .or_else(|| {
+            MODEL_LOADERS
+                .iter()
+                .find(|loader| has_any_extension(path, loader.extensions()))
+        })
+        .copied()
+}
+
+/// Finds the decoder for a texture file: by magic number when the file can be read, then by
+/// extension.
+pub fn texture_decoder_for(path: &Path) -> Option<&'static dyn TextureDecoder> {
+    let header = read_header(path);
+    TEXTURE_DECODERS
+        .iter()
+        .find(|decoder| {
+            header
+                .as_deref()
+                .is_some_and(|header| decoder.matches_magic(header))
+        })
+        .or_else(|| texture_decoder_for_extension(path))
+        .copied()
+}
+
+fn texture_decoder_for_extension(path: &Path) -> Option<&'static &'static dyn TextureDecoder> {
+    TEXTURE_DECODERS
+        .iter()
+        .find(|decoder| has_any_extension(path, decoder.extensions()))
+}
+
+/// Extensions of every registered model format, sorted.
+pub fn model_extensions() -> Vec<&'static str> {
+    sorted_extensions(MODEL_LOADERS.iter().map(|loader| loader.extensions()))
+}
+
+/// Extensions of every registered texture format, sorted.
+pub fn texture_extensions() -> Vec<&'static str> {
+    sorted_extensions(TEXTURE_DECODERS.iter().map(|decoder| decoder.extensions()))
+}
+
+/// Returns `true` if `path` has the extension of a registered texture format
+/// (case-insensitive). The file itself is not read, so it does not need to exist.
+pub fn is_texture_path(path: &str) -> bool {
+    texture_decoder_for_extension(Path::new(path)).is_some()
+}
+
+fn sorted_extensions<'a>(lists: impl Iterator<Item = &'a [&'static str]>) -> Vec<&'static str> {
+    let mut extensions: Vec<&'static str> = lists.flatten().copied().collect();
+    extensions.sort_unstable();
+    extensions
+}
+
+fn has_any_extension(path: &Path, extensions: &[&str]) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            extensions
+                .iter()
+                .any(|supported| ext.eq_ignore_ascii_case(supported))
+        })
+}
+
+fn read_header(path: &Path) -> Option<Vec<u8>> {
+    let mut header = Vec::with_capacity(MAGIC_LENGTH);
+    File::open(path)
+        .ok()?
+        .take(MAGIC_LENGTH as u64)
+        .read_to_end(&mut header)
+        .ok()?;
+    Some(header)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::process;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use super::{
+        MODEL_LOADERS, TEXTURE_DECODERS, is_texture_path, model_extensions, model_loader_for,
+        texture_decoder_for, texture_extensions,
+    };
+
+    fn unique_temp_dir(prefix: &str) -> PathBuf {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("system clock should be after epoch")
+            .as_nanos();
+        let dir = env::temp_dir().join(format!("{}_{}_{}", prefix, process::id(), nanos));
+        fs::create_dir_all(&dir).expect("failed to create temporary test directory");
+        dir
+    }
+
+    #[test]
+    fn every_extension_belongs_to_exactly_one_format() {
+        for extensions in [model_extensions(), texture_extensions()] {
+            let mut unique = extensions.clone();
+            unique.dedup();
+            assert_eq!(unique, extensions);
+            assert!(
+                extensions
+                    .iter()
+                    .all(|ext| *ext == ext.to_ascii_lowercase())
+            );
+        }
+        assert_eq!(model_extensions().len(), 5);
+        assert!(!MODEL_LOADERS.is_empty() && !TEXTURE_DECODERS.is_empty());
+    }
+
+    #[test]
+    fn dispatches_by_extension_when_there_is_no_magic() {
+        let loader = model_loader_for(Path::new("missing/Model.OBJ")).expect("OBJ loader");
+        assert_eq!(loader.name(), "OBJ");
+        assert_eq!(
+            model_loader_for(Path::new("part.stl")).map(|loader| loader.name()),
+            Some("STL")
+        );
+        assert!(model_loader_for(Path::new("notes.txt")).is_none());
+
+        let decoder = texture_decoder_for(Path::new("missing/wood.TGA")).expect("TGA decoder");
+        assert_eq!(decoder.name(), "TGA");
+        assert!(is_texture_path("maps/normal.Png"));
+        assert!(is_texture_path("sky/studio.hdr"));
+        assert!(!is_texture_path("maps/normal.jpg"));
+    }
+
+    #[test]
+    fn magic_numbers_win_over_misleading_extensions() {
+        let dir = unique_temp_dir("scop_registry_magic");
+        let png = dir.join("actually_png.bmp");
+        fs::write(&png, b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR").expect("failed to write fixture");
+        let qoi = dir.join("texture.bin");
+        fs::write(&qoi, b"qoif\0\0\0\x01\0\0\0\x01\x03\0").expect("failed to write fixture");
+        let ply = dir.join("scan.dat");
+        fs::write(&ply, b"ply\nformat ascii 1.0\n").expect("failed to write fixture");
+        let hdr = dir.join("sky.img");
+        fs::write(&hdr, b"#?RADIANCE\n").expect("failed to write fixture");
+        let glb = dir.join("scene.bin");
+        fs::write(&glb, b"glTF\x02\0\0\0").expect("failed to write fixture");
+
+        let name = |decoder: Option<&dyn super::TextureDecoder>| decoder.map(|d| d.name());
+        assert_eq!(name(texture_decoder_for(&png)), Some("PNG"));
+        assert_eq!(name(texture_decoder_for(&qoi)), Some("QOI"));
+        assert_eq!(name(texture_decoder_for(&hdr)), Some("HDR"));
+        assert_eq!(
+            model_loader_for(&ply).map(|loader| loader.name()),
+            Some("PLY")
+        );
+        assert_eq!(
+            model_loader_for(&glb).map(|loader| loader.name()),
+            Some("glTF")
+        );
+
+        fs::remove_dir_all(&dir).expect("failed to cleanup temp directory");
+    }
+}
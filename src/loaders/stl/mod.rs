@@ -0,0 +1,26 @@
+mod parse_stl;
+
+use std::path::Path;
+
+use crate::loaders::obj::ObjSceneData;
+use crate::loaders::registry::ModelLoader;
+
+pub use parse_stl::{load, parse};
+
+/// Registry entry for STL models. Binary STL has no signature and many binary headers start
+/// with ASCII STL's `solid` keyword, so STL is only recognized by its extension.
+pub struct StlLoader;
+
+impl ModelLoader for StlLoader {
+    fn name(&self) -> &'static str {
+        "STL"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["stl"]
+    }
+
+    fn load(&self, path: &Path) -> Result<ObjSceneData, String> {
+        load(path)
+    }
+}
@@ -0,0 +1,377 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::loaders::obj::{ObjMeshData, ObjObjectData, ObjSceneData};
+
+const BINARY_HEADER_SIZE: usize = 80;
+const BINARY_TRIANGLE_SIZE: usize = 50;
+
+/// Loads an ASCII or binary STL file into the same scene data the OBJ loader produces:
+/// one object per `solid`, each with a single welded, triangulated mesh.
+pub fn load(path: &Path) -> Result<ObjSceneData, String> {
+    let bytes = fs::read(path)
+        .map_err(|e| format!("Failed to open STL file '{}': {}", path.display(), e))?;
+    parse(&bytes).map_err(|e| format!("{} (while loading {})", e, path.display()))
+}
+
+/// Parses STL bytes, telling binary from ASCII files by size rather than by the leading
+/// `solid` keyword, which many binary exporters also write into their header.
+pub fn parse(bytes: &[u8]) -> Result<ObjSceneData, String> {
+    if is_binary(bytes) {
+        parse_binary(bytes)
+    } else {
+        let text =
+            std::str::from_utf8(bytes).map_err(|_| "ASCII STL is not valid UTF-8".to_string())?;
+        parse_ascii(text)
+    }
+}
+
+fn is_binary(bytes: &[u8]) -> bool {
+    if let Some(count) = binary_triangle_count(bytes)
+        && BINARY_HEADER_SIZE + 4 + count * BINARY_TRIANGLE_SIZE == bytes.len()
+    {
+        return true;
+    }
+    !bytes.trim_ascii_start().starts_with(b"solid")
+}
+
+fn binary_triangle_count(bytes: &[u8]) -> Option<usize> {
+    let count = bytes.get(BINARY_HEADER_SIZE..BINARY_HEADER_SIZE + 4)?;
+    Some(u32::from_le_bytes(count.try_into().ok()?) as usize)
+}
+
+fn parse_binary(bytes: &[u8]) -> Result<ObjSceneData, String> {
+    let count = binary_triangle_count(bytes)
+        .ok_or_else(|| "Binary STL is shorter than its 84-byte header".to_string())?;
+    let body = &bytes[BINARY_HEADER_SIZE + 4..];
+    if body.len() < count * BINARY_TRIANGLE_SIZE {
+        return Err(format!(
+            "Binary STL declares {} triangles but only holds {}",
+            count,
+            body.len() / BINARY_TRIANGLE_SIZE
+        ));
+    }
+
+    let mut mesh = WeldedMesh::default();
+    for triangle in body.chunks_exact(BINARY_TRIANGLE_SIZE).take(count) {
+        let vector = |index: usize| -> [f32; 3] {
+            std::array::from_fn(|axis| {
+                let offset = (index * 3 + axis) * 4;
+                f32::from_le_bytes(triangle[offset..offset + 4].try_into().expect("4 bytes"))
+            })
+        };
+        mesh.add_facet(vector(0), &[vector(1), vector(2), vector(3)]);
+    }
+
+    Ok(ObjSceneData {
+        objects: vec![mesh.into_object(String::new())],
+        materials: Vec::new(),
+    })
+}
+
+fn parse_ascii(text: &str) -> Result<ObjSceneData, String> {
+    let mut objects = Vec::new();
+    let mut solid: Option<(String, WeldedMesh)> = None;
+    let mut facet: Option<([f32; 3], Vec<[f32; 3]>)> = None;
+
+    for (line_number, line) in text.lines().enumerate() {
+        let line_number = line_number + 1;
+        let line = line.trim();
+        let mut parts = line.split_whitespace();
+        let Some(keyword) = parts.next() else {
+            continue;
+        };
+
+        match keyword {
+            "solid" => {
+                if solid.is_some() {
+                    return Err(format!(
+                        "STL line {}: 'solid' before the previous 'endsolid'",
+                        line_number
+                    ));
+                }
+                let name = line["solid".len()..].trim().to_string();
+                solid = Some((name, WeldedMesh::default()));
+            }
+            "facet" => {
+                if solid.is_none() || facet.is_some() {
+                    return Err(format!("STL line {}: unexpected 'facet'", line_number));
+                }
+                if parts.next() != Some("normal") {
+                    return Err(format!(
+                        "STL line {}: expected 'facet normal nx ny nz'",
+                        line_number
+                    ));
+                }
+                facet = Some((parse_vector(parts, line_number)?, Vec::new()));
+            }
+            "vertex" => {
+                let (_, vertices) = facet
+                    .as_mut()
+                    .ok_or_else(|| format!("STL line {}: vertex outside a facet", line_number))?;
+                vertices.push(parse_vector(parts, line_number)?);
+            }
+            "endfacet" => {
+                let (normal, vertices) = facet
+                    .take()
+                    .ok_or_else(|| format!("STL line {}: unexpected 'endfacet'", line_number))?;
+                if vertices.len() < 3 {
+                    return Err(format!(
+                        "STL line {}: facet has {} vertices, expected 3",
+                        line_number,
+                        vertices.len()
+                    ));
+                }
+                let (_, mesh) = solid.as_mut().expect("facets only start inside a solid");
+                mesh.add_facet(normal, &vertices);
+            }
+            "endsolid" => {
+                let (name, mesh) = solid
+                    .take()
+                    .ok_or_else(|| format!("STL line {}: unexpected 'endsolid'", line_number))?;
+                if facet.is_some() {
+                    return Err(format!(
+                        "STL line {}: 'endsolid' inside an open facet",
+                        line_number
+                    ));
+                }
+                objects.push(mesh.into_object(name));
+            }
+            "outer" | "endloop" => {}
+            _ => {
+                return Err(format!(
+                    "STL line {}: unknown keyword '{}'",
+                    line_number, keyword
+                ));
+            }
+        }
+    }
+
+    if facet.is_some() {
+        return Err("ASCII STL ended inside a facet".to_string());
+    }
+    // Tolerate a missing final 'endsolid'
+    if let Some((name, mesh)) = solid {
+        objects.push(mesh.into_object(name));
+    }
+    if objects.is_empty() {
+        return Err("ASCII STL contains no solid".to_string());
+    }
+
+    Ok(ObjSceneData {
+        objects,
+        materials: Vec::new(),
+    })
+}
+
+fn parse_vector<'a>(
+    mut parts: impl Iterator<Item = &'a str>,
+    line_number: usize,
+) -> Result<[f32; 3], String> {
+    let mut vector = [0.0; 3];
+    for component in &mut vector {
+        *component = parts
+            .next()
+            .and_then(|token| token.parse::<f32>().ok())
+            .ok_or_else(|| format!("STL line {}: expected three numbers", line_number))?;
+    }
+    Ok(vector)
+}
+
+/// Triangle mesh whose corners are merged when they share both position and facet normal,
+/// so flat shading survives while coplanar neighbors share vertices.
+#[derive(Default)]
+struct WeldedMesh {
+    mesh: ObjMeshData,
+    vertex_ids: HashMap<[u32; 6], u32>,
+}
+
+impl WeldedMesh {
+    /// Adds a facet as a triangle fan. Exporters often write a zero normal, in which case it
+    /// is rebuilt from the counter-clockwise winding.
+    fn add_facet(&mut self, normal: [f32; 3], vertices: &[[f32; 3]]) {
+        let normal = normalize(normal)
+            .or_else(|| {
+                normalize(cross(
+                    sub(vertices[1], vertices[0]),
+                    sub(vertices[2], vertices[0]),
+                ))
+            })
+            .unwrap_or([0.0; 3]);
+        for i in 1..vertices.len() - 1 {
+            for corner in [vertices[0], vertices[i], vertices[i + 1]] {
+                let index = self.vertex(corner, normal);
+                self.mesh.indices.push(index);
+            }
+        }
+    }
+
+    fn vertex(&mut self, position: [f32; 3], normal: [f32; 3]) -> u32 {
+        // Adding 0.0 folds -0.0 into 0.0 so both weld together
+        let bits = |v: f32| (v + 0.0).to_bits();
+        let key = [
+            bits(position[0]),
+            bits(position[1]),
+            bits(position[2]),
+            bits(normal[0]),
+            bits(normal[1]),
+            bits(normal[2]),
+        ];
+        let mesh = &mut self.mesh;
+        *self.vertex_ids.entry(key).or_insert_with(|| {
+            mesh.positions.extend_from_slice(&position);
+            mesh.normals.extend_from_slice(&normal);
+            (mesh.positions.len() / 3 - 1) as u32
+        })
+    }
+
+    fn into_object(self, name: String) -> ObjObjectData {
+        ObjObjectData {
+            name,
+            groups: Vec::new(),
+            meshes: vec![self.mesh],
+        }
+    }
+}
+
+fn sub(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn normalize(v: [f32; 3]) -> Option<[f32; 3]> {
+    let length = (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt();
+    (length.is_finite() && length > f32::EPSILON)
+        .then(|| [v[0] / length, v[1] / length, v[2] / length])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+
+    fn binary_stl(header: &[u8], triangles: &[[[f32; 3]; 4]]) -> Vec<u8> {
+        let mut bytes = header.to_vec();
+        bytes.resize(80, b' ');
+        bytes.extend_from_slice(&(triangles.len() as u32).to_le_bytes());
+        for triangle in triangles {
+            for value in triangle.iter().flatten() {
+                bytes.extend_from_slice(&value.to_le_bytes());
+            }
+            bytes.extend_from_slice(&[0, 0]);
+        }
+        bytes
+    }
+
+    const SQUARE: [[[f32; 3]; 4]; 2] = [
+        [
+            [0.0, 0.0, 1.0],
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [1.0, 1.0, 0.0],
+        ],
+        [
+            [0.0, 0.0, 1.0],
+            [0.0, 0.0, 0.0],
+            [1.0, 1.0, 0.0],
+            [0.0, 1.0, 0.0],
+        ],
+    ];
+
+    #[test]
+    fn parses_ascii_solids_and_welds_shared_corners() {
+        let text = "\
+solid first part
+  facet normal 0 0 1
+    outer loop
+      vertex 0 0 0
+      vertex 1 0 0
+      vertex 1 1 0
+    endloop
+  endfacet
+  facet normal 0 0 0
+    outer loop
+      vertex 0 0 0
+      vertex 1 1 0
+      vertex 0 1 0
+    endloop
+  endfacet
+endsolid first part
+solid second
+  facet normal 1 0 0
+    outer loop
+      vertex 0 0 0
+      vertex 0 1 0
+      vertex 0 1 1
+    endloop
+  endfacet
+endsolid second
+";
+        let scene = parse(text.as_bytes()).expect("ASCII STL should parse");
+        assert_eq!(scene.objects.len(), 2);
+        assert_eq!(scene.objects[0].name, "first part");
+        assert_eq!(scene.objects[1].name, "second");
+
+        // The zero normal is rebuilt from the winding, so both facets weld into a quad
+        let square = &scene.objects[0].meshes[0];
+        assert_eq!(square.positions.len(), 4 * 3);
+        assert_eq!(square.indices, vec![0, 1, 2, 0, 2, 3]);
+        assert!(square.normals.chunks(3).all(|n| n == [0.0, 0.0, 1.0]));
+        assert!(square.texcoords.is_empty());
+        assert_eq!(scene.objects[1].meshes[0].normals[..3], [1.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn detects_binary_files_that_start_with_solid() {
+        let bytes = binary_stl(b"solid exported by a CAD tool", &SQUARE);
+        let scene = parse(&bytes).expect("binary STL should parse");
+        assert_eq!(scene.objects.len(), 1);
+        let mesh = &scene.objects[0].meshes[0];
+        assert_eq!(mesh.indices.len(), 6);
+        assert_eq!(mesh.positions.len(), 4 * 3);
+
+        let plain = binary_stl(b"", &SQUARE);
+        assert_eq!(
+            parse(&plain).expect("binary STL").objects[0].meshes[0]
+                .indices
+                .len(),
+            6
+        );
+    }
+
+    #[test]
+    fn facets_with_different_normals_keep_separate_vertices() {
+        let mut cube_corner = SQUARE.to_vec();
+        cube_corner.push([
+            [1.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [1.0, 1.0, 0.0],
+            [1.0, 1.0, 1.0],
+        ]);
+        let scene = parse(&binary_stl(b"", &cube_corner)).expect("binary STL should parse");
+        // Two corners are shared with the square but carry a different normal
+        assert_eq!(scene.objects[0].meshes[0].positions.len(), 7 * 3);
+    }
+
+    #[test]
+    fn rejects_malformed_files() {
+        let mut truncated = binary_stl(b"", &SQUARE);
+        truncated.truncate(truncated.len() - 10);
+        assert!(parse(&truncated).is_err());
+
+        assert!(parse(b"solid x\n facet normal 0 0 1\n vertex 0 0\n").is_err());
+        assert!(parse(b"solid x\n vertex 0 0 0\nendsolid x\n").is_err());
+        assert!(
+            parse(b"solid x\n facet normal 0 0 1\n vertex 0 0 0\n endfacet\nendsolid\n").is_err()
+        );
+        assert!(parse(b"solid x\n polygon\nendsolid\n").is_err());
+        assert!(parse(b"").is_err());
+    }
+}
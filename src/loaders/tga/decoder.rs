@@ -0,0 +1,517 @@
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+use crate::loaders::bmp::image::{Image, Pixel};
+
+const HEADER_SIZE: usize = 18;
+
+/// TGA image types (header byte 2); values above 8 are the RLE variants.
+const COLOR_MAPPED: u8 = 1;
+const TRUECOLOR: u8 = 2;
+const GRAYSCALE: u8 = 3;
+const RLE_FLAG: u8 = 8;
+
+struct TgaHeader {
+    id_length: usize,
+    color_map_type: u8,
+    image_type: u8,
+    color_map_first: usize,
+    color_map_length: usize,
+    color_map_depth: u8,
+    width: u32,
+    height: u32,
+    pixel_depth: u8,
+    alpha_bits: u8,
+    right_to_left: bool,
+    top_to_bottom: bool,
+}
+
+/// Loads a TGA file into an `Image`.
+pub fn open<P: AsRef<Path>>(path: P) -> Result<Image, String> {
+    let mut file = fs::File::open(&path).map_err(|error| {
+        format!(
+            "Failed to open TGA '{}': {}",
+            path.as_ref().display(),
+            error
+        )
+    })?;
+    from_reader(&mut file)
+}
+
+/// Reads and decodes a TGA image from `source`.
+pub fn from_reader<R: Read>(source: &mut R) -> Result<Image, String> {
+    let mut bytes = Vec::new();
+    source
+        .read_to_end(&mut bytes)
+        .map_err(|error| format!("Failed to read TGA data: {}", error))?;
+    decode(&bytes)
+}
+
+/// Decodes uncompressed and RLE truecolor, grayscale and color-mapped TGA images.
+///
+/// Both bottom-left and top-left origins (and right-to-left rows) are honored. 16- and 32-bit
+/// pixels keep their alpha, except that an all-zero 32-bit alpha channel is treated as unset.
+pub fn decode(bytes: &[u8]) -> Result<Image, String> {
+    let header = read_header(bytes)?;
+
+    let color_map_start = HEADER_SIZE + header.id_length;
+    let color_map_size = if header.color_map_type == 1 {
+        header.color_map_length * (header.color_map_depth as usize).div_ceil(8)
+    } else {
+        0
+    };
+    let data_start = color_map_start + color_map_size;
+    if data_start > bytes.len() {
+        return Err(format!(
+            "TGA color map exceeds input bounds (end={}, len={})",
+            data_start,
+            bytes.len()
+        ));
+    }
+
+    // Truecolor and grayscale images may still carry a color map; it is skipped unread.
+    let color_map = if header.image_type & !RLE_FLAG == COLOR_MAPPED {
+        bytes[color_map_start..data_start]
+            .chunks_exact((header.color_map_depth as usize).div_ceil(8))
+            .map(|entry| read_color(entry, header.color_map_depth, true))
+            .collect::<Result<Vec<_>, _>>()?
+    } else {
+        Vec::new()
+    };
+
+    let bytes_per_pixel = (header.pixel_depth as usize).div_ceil(8);
+    let pixel_count = header.width as usize * header.height as usize;
+    let raw_size = pixel_count
+        .checked_mul(bytes_per_pixel)
+        .ok_or_else(|| format!("TGA image {}x{} is too large", header.width, header.height))?;
+    let raw = if header.image_type & RLE_FLAG != 0 {
+        read_rle_pixels(&bytes[data_start..], bytes_per_pixel, raw_size)?
+    } else {
+        let end = data_start.saturating_add(raw_size);
+        bytes
+            .get(data_start..end)
+            .ok_or_else(|| {
+                format!(
+                    "TGA pixel data exceeds input bounds (end={}, len={})",
+                    end,
+                    bytes.len()
+                )
+            })?
+            .to_vec()
+    };
+
+    let base_type = header.image_type & !RLE_FLAG;
+    let mut pixels = Vec::with_capacity(pixel_count);
+    for value in raw.chunks_exact(bytes_per_pixel) {
+        let pixel = match base_type {
+            COLOR_MAPPED => {
+                let index = match *value {
+                    [index] => index as usize,
+                    [lo, hi] => u16::from_le_bytes([lo, hi]) as usize,
+                    _ => unreachable!("color-mapped depth is validated"),
+                };
+                index
+                    .checked_sub(header.color_map_first)
+                    .and_then(|entry| color_map.get(entry))
+                    .copied()
+                    .ok_or_else(|| {
+                        format!(
+                            "TGA color map index {} out of bounds (first={}, length={})",
+                            index,
+                            header.color_map_first,
+                            color_map.len()
+                        )
+                    })?
+            }
+            GRAYSCALE => match *value {
+                [gray] => Pixel::new(gray, gray, gray),
+                [gray, alpha] => Pixel::with_alpha(gray, gray, gray, alpha),
+                _ => unreachable!("grayscale depth is validated"),
+            },
+            _ => read_color(value, header.pixel_depth, header.alpha_bits > 0)?,
+        };
+        pixels.push(pixel);
+    }
+
+    let mut image = Image::new(header.width, header.height);
+    for (index, pixel) in pixels.into_iter().enumerate() {
+        let column = index as u32 % header.width;
+        let row = index as u32 / header.width;
+        let x = if header.right_to_left {
+            header.width - 1 - column
+        } else {
+            column
+        };
+        let y = if header.top_to_bottom {
+            row
+        } else {
+            header.height - 1 - row
+        };
+        image.set_pixel(x, y, pixel);
+    }
+    if base_type == TRUECOLOR && header.pixel_depth == 32 {
+        image.treat_unset_alpha_as_opaque();
+    }
+
+    Ok(image)
+}
+
+fn read_header(bytes: &[u8]) -> Result<TgaHeader, String> {
+    if bytes.len() < HEADER_SIZE {
+        return Err(format!(
+            "TGA data is too short for a header ({} bytes)",
+            bytes.len()
+        ));
+    }
+    let u16_at = |offset: usize| u16::from_le_bytes([bytes[offset], bytes[offset + 1]]);
+
+    let header = TgaHeader {
+        id_length: bytes[0] as usize,
+        color_map_type: bytes[1],
+        image_type: bytes[2],
+        color_map_first: u16_at(3) as usize,
+        color_map_length: u16_at(5) as usize,
+        color_map_depth: bytes[7],
+        width: u16_at(12) as u32,
+        height: u16_at(14) as u32,
+        pixel_depth: bytes[16],
+        alpha_bits: bytes[17] & 0x0F,
+        right_to_left: bytes[17] & 0x10 != 0,
+        top_to_bottom: bytes[17] & 0x20 != 0,
+    };
+
+    if header.color_map_type > 1 {
+        return Err(format!(
+            "Unsupported TGA color map type: {}",
+            header.color_map_type
+        ));
+    }
+
+    let depth_supported = match header.image_type & !RLE_FLAG {
+        COLOR_MAPPED => {
+            if header.color_map_type != 1 {
+                return Err("Color-mapped TGA image has no color map".to_string());
+            }
+            matches!(header.pixel_depth, 8 | 16)
+                && matches!(header.color_map_depth, 15 | 16 | 24 | 32)
+        }
+        TRUECOLOR => matches!(header.pixel_depth, 15 | 16 | 24 | 32),
+        GRAYSCALE => matches!(header.pixel_depth, 8 | 16),
+        _ => {
+            return Err(format!("Unsupported TGA image type: {}", header.image_type));
+        }
+    };
+    if !depth_supported {
+        return Err(format!(
+            "Unsupported TGA pixel depth {} for image type {}",
+            header.pixel_depth, header.image_type
+        ));
+    }
+    if header.width == 0 || header.height == 0 {
+        return Err(format!(
+            "TGA image has invalid dimensions {}x{}",
+            header.width, header.height
+        ));
+    }
+
+    Ok(header)
+}
+
+/// Expands RLE packets into `raw_size` bytes of raw pixels. Packets may span scanlines.
+fn read_rle_pixels(
+    data: &[u8],
+    bytes_per_pixel: usize,
+    raw_size: usize,
+) -> Result<Vec<u8>, String> {
+    // A packet takes at least `1 + bytes_per_pixel` bytes and expands to at most 128 pixels,
+    // so no input byte yields more than 128 output bytes.
+    if raw_size / 128 > data.len() {
+        return Err(format!(
+            "TGA RLE data is too short for the image ({} bytes for {} bytes of pixels)",
+            data.len(),
+            raw_size
+        ));
+    }
+
+    let mut raw = Vec::with_capacity(raw_size);
+    let mut cursor = 0;
+    let truncated = || "TGA RLE data ended before all pixels were decoded".to_string();
+
+    while raw.len() < raw_size {
+        let packet = *data.get(cursor).ok_or_else(truncated)?;
+        cursor += 1;
+        let count = (packet & 0x7F) as usize + 1;
+        let remaining = (raw_size - raw.len()) / bytes_per_pixel;
+        if count > remaining {
+            return Err(format!(
+                "TGA RLE packet of {} pixels overruns the image ({} pixels left)",
+                count, remaining
+            ));
+        }
+
+        if packet & 0x80 != 0 {
+            let value = data
+                .get(cursor..cursor + bytes_per_pixel)
+                .ok_or_else(truncated)?;
+            cursor += bytes_per_pixel;
+            for _ in 0..count {
+                raw.extend_from_slice(value);
+            }
+        } else {
+            let size = count * bytes_per_pixel;
+            raw.extend_from_slice(data.get(cursor..cursor + size).ok_or_else(truncated)?);
+            cursor += size;
+        }
+    }
+
+    Ok(raw)
+}
+
+/// Converts a little-endian BGR(A) or ARGB1555 value to a `Pixel`.
+fn read_color(value: &[u8], depth: u8, use_alpha: bool) -> Result<Pixel, String> {
+    let pixel = match (depth, value) {
+        (15 | 16, &[lo, hi]) => {
+            let packed = u16::from_le_bytes([lo, hi]);
+            let [r, g, b] = [10, 5, 0].map(|shift| {
+                let channel = (packed >> shift) & 0x1F;
+                ((channel * 255 + 15) / 31) as u8
+            });
+            let alpha = if depth == 16 && use_alpha && packed & 0x8000 == 0 {
+                0
+            } else {
+                255
+            };
+            Pixel::with_alpha(r, g, b, alpha)
+        }
+        (24, &[b, g, r]) => Pixel::new(r, g, b),
+        (32, &[b, g, r, a]) => Pixel::with_alpha(r, g, b, a),
+        _ => return Err(format!("Unsupported TGA color depth: {}", depth)),
+    };
+    Ok(pixel)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::decode;
+    use crate::loaders::bmp::image::Pixel;
+
+    struct Fixture<'a> {
+        image_type: u8,
+        color_map: Option<(u16, u8, &'a [u8])>,
+        size: (u16, u16),
+        depth: u8,
+        descriptor: u8,
+        data: &'a [u8],
+    }
+
+    fn tga(fixture: Fixture) -> Vec<u8> {
+        let mut bytes = vec![3, fixture.color_map.is_some() as u8, fixture.image_type];
+        let (first, map_depth, map) = fixture.color_map.unwrap_or((0, 0, &[]));
+        let map_depth_bytes = (map_depth as usize).div_ceil(8).max(1);
+        bytes.extend_from_slice(&first.to_le_bytes());
+        bytes.extend_from_slice(&((map.len() / map_depth_bytes) as u16).to_le_bytes());
+        bytes.push(map_depth);
+        bytes.extend_from_slice(&[0; 4]);
+        bytes.extend_from_slice(&fixture.size.0.to_le_bytes());
+        bytes.extend_from_slice(&fixture.size.1.to_le_bytes());
+        bytes.push(fixture.depth);
+        bytes.push(fixture.descriptor);
+        // Image ID, skipped by the decoder
+        bytes.extend_from_slice(b"id!");
+        bytes.extend_from_slice(map);
+        bytes.extend_from_slice(fixture.data);
+        bytes
+    }
+
+    const RED: Pixel = Pixel {
+        r: 255,
+        g: 0,
+        b: 0,
+        a: 255,
+    };
+    const GREEN: Pixel = Pixel {
+        r: 0,
+        g: 255,
+        b: 0,
+        a: 255,
+    };
+    const BLUE: Pixel = Pixel {
+        r: 0,
+        g: 0,
+        b: 255,
+        a: 255,
+    };
+
+    #[test]
+    fn decodes_uncompressed_truecolor_bottom_left_origin() {
+        // First stored row is the bottom of the image
+        let data = [0, 0, 255, 0, 255, 0, 255, 0, 0, 255, 255, 255];
+        let image = decode(&tga(Fixture {
+            image_type: 2,
+            color_map: None,
+            size: (2, 2),
+            depth: 24,
+            descriptor: 0,
+            data: &data,
+        }))
+        .expect("truecolor TGA should decode");
+
+        assert_eq!(image.get_pixel(0, 1), RED);
+        assert_eq!(image.get_pixel(1, 1), GREEN);
+        assert_eq!(image.get_pixel(0, 0), BLUE);
+        assert_eq!(image.get_pixel(1, 0), Pixel::new(255, 255, 255));
+    }
+
+    #[test]
+    fn decodes_rle_truecolor_with_alpha_and_top_left_origin() {
+        // Run of 3 translucent red pixels spanning both rows, then one raw opaque blue pixel
+        let data = [0x82, 0, 0, 255, 128, 0x00, 255, 0, 0, 255];
+        let image = decode(&tga(Fixture {
+            image_type: 10,
+            color_map: None,
+            size: (2, 2),
+            depth: 32,
+            descriptor: 0x28,
+            data: &data,
+        }))
+        .expect("RLE TGA should decode");
+
+        let translucent = Pixel::with_alpha(255, 0, 0, 128);
+        assert_eq!(image.get_pixel(0, 0), translucent);
+        assert_eq!(image.get_pixel(1, 0), translucent);
+        assert_eq!(image.get_pixel(0, 1), translucent);
+        assert_eq!(image.get_pixel(1, 1), BLUE);
+        assert!(image.has_alpha());
+    }
+
+    #[test]
+    fn decodes_grayscale_and_right_to_left_rows() {
+        let data = [0x81, 10, 0x00, 200];
+        let image = decode(&tga(Fixture {
+            image_type: 11,
+            color_map: None,
+            size: (3, 1),
+            depth: 8,
+            descriptor: 0x10,
+            data: &data,
+        }))
+        .expect("grayscale TGA should decode");
+
+        assert_eq!(image.get_pixel(0, 0), Pixel::new(200, 200, 200));
+        assert_eq!(image.get_pixel(1, 0), Pixel::new(10, 10, 10));
+        assert_eq!(image.get_pixel(2, 0), Pixel::new(10, 10, 10));
+    }
+
+    #[test]
+    fn decodes_color_mapped_with_first_entry_offset() {
+        // Color map entries 4 and 5 (24-bit BGR), indexes refer to them directly
+        let map = [255, 0, 0, 0, 0, 255];
+        let image = decode(&tga(Fixture {
+            image_type: 1,
+            color_map: Some((4, 24, &map)),
+            size: (3, 1),
+            depth: 8,
+            descriptor: 0x20,
+            data: &[5, 4, 5],
+        }))
+        .expect("color-mapped TGA should decode");
+
+        assert_eq!(image.get_pixel(0, 0), RED);
+        assert_eq!(image.get_pixel(1, 0), BLUE);
+        assert_eq!(image.get_pixel(2, 0), RED);
+    }
+
+    #[test]
+    fn decodes_16_bit_attribute_alpha() {
+        // Opaque pure red (attribute bit set) and transparent pure green
+        let data = [0x00, 0xFC, 0xE0, 0x03];
+        let image = decode(&tga(Fixture {
+            image_type: 2,
+            color_map: None,
+            size: (2, 1),
+            depth: 16,
+            descriptor: 0x21,
+            data: &data,
+        }))
+        .expect("16-bit TGA should decode");
+
+        assert_eq!(image.get_pixel(0, 0), RED);
+        assert_eq!(image.get_pixel(1, 0), Pixel::with_alpha(0, 255, 0, 0));
+    }
+
+    #[test]
+    fn skips_unused_color_maps_of_truecolor_and_grayscale_images() {
+        // A zero-depth color map used to reach `chunks_exact(0)`.
+        let mut zero_depth = tga(Fixture {
+            image_type: 2,
+            color_map: None,
+            size: (1, 1),
+            depth: 24,
+            descriptor: 0,
+            data: &[0, 0, 255],
+        });
+        zero_depth[1] = 1;
+        zero_depth[5..7].copy_from_slice(&4u16.to_le_bytes());
+        let image = decode(&zero_depth).expect("unused zero-depth color map should be skipped");
+        assert_eq!(image.get_pixel(0, 0), RED);
+
+        let image = decode(&tga(Fixture {
+            image_type: 3,
+            color_map: Some((0, 8, &[1, 2, 3])),
+            size: (1, 1),
+            depth: 8,
+            descriptor: 0,
+            data: &[128],
+        }))
+        .expect("unused 8-bit color map should be skipped");
+        assert_eq!(image.get_pixel(0, 0), Pixel::new(128, 128, 128));
+    }
+
+    #[test]
+    fn rejects_malformed_images() {
+        let truncated = tga(Fixture {
+            image_type: 10,
+            color_map: None,
+            size: (4, 1),
+            depth: 24,
+            descriptor: 0,
+            data: &[0x83, 1, 2],
+        });
+        assert!(decode(&truncated).is_err());
+
+        let bad_index = tga(Fixture {
+            image_type: 1,
+            color_map: Some((0, 24, &[0, 0, 0])),
+            size: (1, 1),
+            depth: 8,
+            descriptor: 0,
+            data: &[3],
+        });
+        assert!(decode(&bad_index).is_err());
+
+        let unsupported = tga(Fixture {
+            image_type: 32,
+            color_map: None,
+            size: (1, 1),
+            depth: 24,
+            descriptor: 0,
+            data: &[0, 0, 0],
+        });
+        assert!(decode(&unsupported).is_err());
+        assert!(decode(&[0; 10]).is_err());
+
+        // Huge headers over a few bytes of data: must fail without reserving the raster
+        for image_type in [2, 10] {
+            let huge = tga(Fixture {
+                image_type,
+                color_map: None,
+                size: (65535, 65535),
+                depth: 32,
+                descriptor: 0,
+                data: &[0xFF, 1, 2, 3, 4],
+            });
+            assert!(decode(&huge).is_err());
+        }
+    }
+}
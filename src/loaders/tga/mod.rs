@@ -0,0 +1,24 @@
+mod decoder;
+
+use std::path::Path;
+
+use crate::loaders::registry::{TextureData, TextureDecoder};
+
+pub use decoder::{decode, from_reader, open};
+
+/// Registry entry for TGA textures.
+pub struct TgaDecoder;
+
+impl TextureDecoder for TgaDecoder {
+    fn name(&self) -> &'static str {
+        "TGA"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["tga"]
+    }
+
+    fn decode(&self, path: &Path) -> Result<TextureData, String> {
+        open(path).map(|image| TextureData::Levels(vec![image]))
+    }
+}
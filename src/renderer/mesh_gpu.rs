@@ -3,6 +3,7 @@ use std::mem::size_of;
 use std::os::raw::c_void;
 use std::ptr;
 
+use crate::math::{Vector2, Vector3};
 use crate::renderer::shader_program::ShaderProgram;
 use crate::scene::{TextureKind, Vertex};
 
@@ -10,6 +11,8 @@ use crate::scene::{TextureKind, Vertex};
 pub struct GpuTexture {
     pub id: u32,
     pub kind: TextureKind,
+    pub uv_scale: Vector2,
+    pub uv_offset: Vector2,
 }
 
 pub struct MeshGpu {
@@ -17,6 +20,8 @@ pub struct MeshGpu {
     pub indices: Vec<u32>,
     pub textures: Vec<GpuTexture>,
     pub has_uv_mapping: bool,
+    pub has_vertex_colors: bool,
+    pub diffuse_color: Vector3,
     pub vao: u32,
     vbo: u32,
     ebo: u32,
@@ -28,12 +33,16 @@ impl MeshGpu {
         indices: Vec<u32>,
         textures: Vec<GpuTexture>,
         has_uv_mapping: bool,
+        has_vertex_colors: bool,
+        diffuse_color: Vector3,
     ) -> Result<Self, String> {
         let mut mesh = Self {
             vertices,
             indices,
             textures,
             has_uv_mapping,
+            has_vertex_colors,
+            diffuse_color,
             vao: 0,
             vbo: 0,
             ebo: 0,
@@ -73,6 +82,16 @@ impl MeshGpu {
                     gl::GetUniformLocation(shader.id(), sampler.as_ptr()),
                     i as i32,
                 );
+
+                let uv_transform = CString::new(format!("{}{}UvTransform", name, number))
+                    .expect("shader uniform names are static ASCII");
+                gl::Uniform4f(
+                    gl::GetUniformLocation(shader.id(), uv_transform.as_ptr()),
+                    texture.uv_scale.x,
+                    texture.uv_scale.y,
+                    texture.uv_offset.x,
+                    texture.uv_offset.y,
+                );
                 gl::BindTexture(gl::TEXTURE_2D, texture.id);
             }
 
@@ -82,6 +101,19 @@ impl MeshGpu {
                 if self.has_uv_mapping { 0 } else { 1 },
             );
 
+            let use_diffuse_texture = c_str!("useDiffuseTexture");
+            gl::Uniform1i(
+                gl::GetUniformLocation(shader.id(), use_diffuse_texture.as_ptr()),
+                if diffuse_nr > 0 { 1 } else { 0 },
+            );
+            shader.set_vector3(c_str!("materialDiffuseColor"), &self.diffuse_color);
+
+            let use_face_shading = c_str!("useFaceShading");
+            gl::Uniform1i(
+                gl::GetUniformLocation(shader.id(), use_face_shading.as_ptr()),
+                if self.has_vertex_colors { 0 } else { 1 },
+            );
+
             gl::BindVertexArray(self.vao);
             gl::DrawElements(
                 gl::TRIANGLES,
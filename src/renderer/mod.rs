@@ -5,4 +5,4 @@ pub mod mesh_gpu;
 pub mod shader_program;
 pub mod texture_gpu;
 
-pub use runtime::run;
+pub use runtime::{RenderOptions, run};
@@ -10,9 +10,11 @@ use crate::math::{Matrix4, Point3, Vector3};
 use crate::renderer::input_events::process_events;
 use crate::renderer::mesh_gpu::{GpuTexture, MeshGpu};
 use crate::renderer::shader_program::ShaderProgram;
-use crate::renderer::texture_gpu::upload_bmp_texture;
+use crate::renderer::texture_gpu::{
+    load_texture_data, placeholder_image, upload_image_texture, upload_texture_data,
+};
 use crate::rng::Rng;
-use crate::scene::SceneModel;
+use crate::scene::{SceneModel, TextureWrap};
 
 use self::glfw::Context;
 
@@ -48,7 +50,13 @@ impl Default for InputState {
     }
 }
 
-pub fn run(mut scene_model: SceneModel) -> Result<(), String> {
+#[derive(Debug, Clone, Default)]
+pub struct RenderOptions {
+    /// Fail instead of substituting a placeholder when a texture cannot be loaded.
+    pub strict_textures: bool,
+}
+
+pub fn run(mut scene_model: SceneModel, options: RenderOptions) -> Result<(), String> {
     let mut camera = Camera {
         position: Point3::new(0.0, 0.0, 3.0),
         ..Camera::default()
@@ -90,7 +98,7 @@ pub fn run(mut scene_model: SceneModel) -> Result<(), String> {
     }
 
     let shader = ShaderProgram::new("resources/shaders/model.vs", "resources/shaders/model.fs")?;
-    let mut gpu_meshes = build_gpu_meshes(&scene_model)?;
+    let mut gpu_meshes = build_gpu_meshes(&scene_model, &options)?;
 
     let mut position = Vector3::new(0.0, 0.0, 0.0);
     let mut input_state = InputState::default();
@@ -165,24 +173,51 @@ pub fn run(mut scene_model: SceneModel) -> Result<(), String> {
     Ok(())
 }
 
-fn build_gpu_meshes(scene_model: &SceneModel) -> Result<Vec<MeshGpu>, String> {
-    let mut texture_cache: HashMap<String, u32> = HashMap::new();
+fn build_gpu_meshes(
+    scene_model: &SceneModel,
+    options: &RenderOptions,
+) -> Result<Vec<MeshGpu>, String> {
+    // Failed loads are cached too (with their error) so every mesh using them gets a warning.
+    let mut texture_cache: HashMap<(String, TextureWrap), (u32, Option<String>)> = HashMap::new();
+    let mut placeholder_texture: Option<u32> = None;
     let mut gpu_meshes = Vec::with_capacity(scene_model.meshes.len());
 
-    for scene_mesh in &scene_model.meshes {
+    for (mesh_index, scene_mesh) in scene_model.meshes.iter().enumerate() {
         let mut textures = Vec::with_capacity(scene_mesh.textures.len());
         for texture in &scene_mesh.textures {
-            let id = if let Some(existing) = texture_cache.get(&texture.path) {
-                *existing
-            } else {
-                let uploaded = upload_bmp_texture(&texture.path)?;
-                texture_cache.insert(texture.path.clone(), uploaded);
-                uploaded
+            let cache_key = (texture.path.clone(), texture.wrap);
+            let (id, load_error) = match texture_cache.get(&cache_key) {
+                Some(cached) => cached.clone(),
+                None => {
+                    let loaded = match load_texture_data(&texture.path) {
+                        Ok(data) => (upload_texture_data(&data, texture.wrap), None),
+                        Err(error) if options.strict_textures => return Err(error),
+                        Err(error) => {
+                            let id = *placeholder_texture.get_or_insert_with(|| {
+                                upload_image_texture(&placeholder_image(), TextureWrap::Repeat)
+                            });
+                            (id, Some(error))
+                        }
+                    };
+                    texture_cache.insert(cache_key, loaded.clone());
+                    loaded
+                }
             };
 
+            if let Some(error) = load_error {
+                eprintln!(
+                    "Warning: {} uses placeholder for texture '{}': {}",
+                    describe_mesh(scene_model, mesh_index),
+                    texture.path,
+                    error
+                );
+            }
+
             textures.push(GpuTexture {
                 id,
                 kind: texture.kind.clone(),
+                uv_scale: texture.uv_scale,
+                uv_offset: texture.uv_offset,
             });
         }
 
@@ -191,6 +226,8 @@ fn build_gpu_meshes(scene_model: &SceneModel) -> Result<Vec<MeshGpu>, String> {
             scene_mesh.indices.clone(),
             textures,
             scene_mesh.has_uv_mapping,
+            scene_mesh.has_vertex_colors,
+            scene_mesh.diffuse_color,
         )?;
         gpu_meshes.push(mesh);
     }
@@ -198,6 +235,19 @@ fn build_gpu_meshes(scene_model: &SceneModel) -> Result<Vec<MeshGpu>, String> {
     Ok(gpu_meshes)
 }
 
+fn describe_mesh(scene_model: &SceneModel, mesh_index: usize) -> String {
+    match scene_model
+        .objects
+        .iter()
+        .find(|object| object.meshes.contains(&mesh_index))
+    {
+        Some(object) if !object.name.is_empty() => {
+            format!("mesh {} (object '{}')", mesh_index, object.name)
+        }
+        _ => format!("mesh {}", mesh_index),
+    }
+}
+
 fn sync_gpu_vertices(scene_model: &SceneModel, gpu_meshes: &mut [MeshGpu]) -> Result<(), String> {
     if scene_model.meshes.len() != gpu_meshes.len() {
         return Err("Internal renderer error: scene/gpu mesh count mismatch".to_string());
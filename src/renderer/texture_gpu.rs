@@ -1,22 +1,83 @@
 use std::os::raw::c_void;
 
-use crate::loaders::bmp;
+use std::path::Path;
 
-pub fn upload_bmp_texture(texture_path: &str) -> Result<u32, String> {
-    let mut texture_id = 0;
+use crate::loaders::bmp::image::{Image, Pixel};
+use crate::loaders::hdr::HdrImage;
+use crate::loaders::{TextureData, texture_decoder_for};
+use crate::scene::TextureWrap;
+
+const PLACEHOLDER_SIZE: u32 = 64;
+const PLACEHOLDER_CELL_SIZE: u32 = 8;
+
+/// Internal precision of textures created from `HdrImage`s.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HdrTexturePrecision {
+    /// `GL_RGB16F`: half the memory, plenty for lighting and exposure work.
+    Half,
+    /// `GL_RGB32F`: full single precision.
+    Full,
+}
+
+pub fn upload_texture(texture_path: &str, wrap: TextureWrap) -> Result<u32, String> {
+    let data = load_texture_data(texture_path)?;
+    Ok(upload_texture_data(&data, wrap))
+}
 
-    let img = bmp::open(texture_path)
-        .map_err(|error| format!("Failed to open BMP texture '{}': {}", texture_path, error))?;
+/// Decodes a texture file with the registered decoder matching its magic number or extension.
+pub fn load_texture_data(texture_path: &str) -> Result<TextureData, String> {
+    let decoder = texture_decoder_for(Path::new(texture_path))
+        .ok_or_else(|| format!("Unsupported texture format for '{}'", texture_path))?;
+    decoder.decode(Path::new(texture_path)).map_err(|error| {
+        format!(
+            "Failed to open {} texture '{}': {}",
+            decoder.name(),
+            texture_path,
+            error
+        )
+    })
+}
 
-    let width = img.width;
-    let height = img.height;
+/// Uploads decoded texture data: 8-bit mip chains through `upload_texture_levels`, HDR images
+/// as `GL_RGB16F` through `upload_hdr_image_texture`.
+pub fn upload_texture_data(data: &TextureData, wrap: TextureWrap) -> u32 {
+    match data {
+        TextureData::Levels(levels) => upload_texture_levels(levels, wrap),
+        TextureData::Hdr(img) => upload_hdr_image_texture(img, wrap, HdrTexturePrecision::Half),
+    }
+}
 
-    let mut rgb_data: Vec<u8> = Vec::with_capacity((width * height * 3) as usize);
-    for pixel in &img.data {
-        rgb_data.push(pixel.r);
-        rgb_data.push(pixel.g);
-        rgb_data.push(pixel.b);
+/// Magenta/black checkerboard substituted for textures that cannot be loaded.
+pub fn placeholder_image() -> Image {
+    let mut img = Image::new(PLACEHOLDER_SIZE, PLACEHOLDER_SIZE);
+    for (x, y) in img.coordinates() {
+        let is_magenta = (x / PLACEHOLDER_CELL_SIZE + y / PLACEHOLDER_CELL_SIZE).is_multiple_of(2);
+        let pixel = if is_magenta {
+            Pixel::new(255, 0, 255)
+        } else {
+            Pixel::new(0, 0, 0)
+        };
+        img.set_pixel(x, y, pixel);
     }
+    img
+}
+
+pub fn upload_image_texture(img: &Image, wrap: TextureWrap) -> u32 {
+    upload_texture_levels(std::slice::from_ref(img), wrap)
+}
+
+/// Uploads `levels` as the mip chain of a new texture, starting with the full-size image.
+/// A single level gets its mipmaps generated; a longer chain is used as is, with the maximum
+/// level clamped to the last one supplied.
+pub fn upload_texture_levels(levels: &[Image], wrap: TextureWrap) -> u32 {
+    let mut texture_id = 0;
+
+    // Opaque images stay RGB; anything with transparency is uploaded as RGBA.
+    let (format, channels) = if levels.iter().any(Image::has_alpha) {
+        (gl::RGBA, 4)
+    } else {
+        (gl::RGB, 3)
+    };
 
     unsafe {
         gl::GenTextures(1, &mut texture_id);
@@ -27,23 +88,87 @@ pub fn upload_bmp_texture(texture_path: &str) -> Result<u32, String> {
 
         gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
 
+        for (level, img) in levels.iter().enumerate() {
+            let mut pixel_data: Vec<u8> = Vec::with_capacity(img.data.len() * channels);
+            for pixel in &img.data {
+                pixel_data.extend_from_slice(&[pixel.r, pixel.g, pixel.b, pixel.a][..channels]);
+            }
+            gl::TexImage2D(
+                gl::TEXTURE_2D,
+                level as i32,
+                format as i32,
+                img.width as i32,
+                img.height as i32,
+                0,
+                format,
+                gl::UNSIGNED_BYTE,
+                pixel_data.as_ptr() as *const c_void,
+            );
+        }
+        if levels.len() == 1 {
+            gl::GenerateMipmap(gl::TEXTURE_2D);
+        } else {
+            gl::TexParameteri(
+                gl::TEXTURE_2D,
+                gl::TEXTURE_MAX_LEVEL,
+                levels.len() as i32 - 1,
+            );
+        }
+
+        gl::PixelStorei(gl::UNPACK_ALIGNMENT, previous_unpack_alignment);
+
+        set_sampling(wrap);
+    }
+
+    texture_id
+}
+
+/// Uploads a floating-point image as an RGB16F or RGB32F texture with a full mip chain.
+pub fn upload_hdr_image_texture(
+    img: &HdrImage,
+    wrap: TextureWrap,
+    precision: HdrTexturePrecision,
+) -> u32 {
+    let mut texture_id = 0;
+
+    let internal_format = match precision {
+        HdrTexturePrecision::Half => gl::RGB16F,
+        HdrTexturePrecision::Full => gl::RGB32F,
+    };
+
+    unsafe {
+        gl::GenTextures(1, &mut texture_id);
+        gl::BindTexture(gl::TEXTURE_2D, texture_id);
+
+        // Rows of f32 triples are always 4-byte aligned, so the default unpack alignment holds.
         gl::TexImage2D(
             gl::TEXTURE_2D,
             0,
-            gl::RGB as i32,
-            width as i32,
-            height as i32,
+            internal_format as i32,
+            img.width as i32,
+            img.height as i32,
             0,
             gl::RGB,
-            gl::UNSIGNED_BYTE,
-            rgb_data.as_ptr() as *const c_void,
+            gl::FLOAT,
+            img.data.as_ptr() as *const c_void,
         );
         gl::GenerateMipmap(gl::TEXTURE_2D);
 
-        gl::PixelStorei(gl::UNPACK_ALIGNMENT, previous_unpack_alignment);
+        set_sampling(wrap);
+    }
+
+    texture_id
+}
 
-        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::REPEAT as i32);
-        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::REPEAT as i32);
+/// Applies the wrap mode and trilinear filtering to the bound 2D texture.
+unsafe fn set_sampling(wrap: TextureWrap) {
+    let wrap_mode = match wrap {
+        TextureWrap::Repeat => gl::REPEAT,
+        TextureWrap::Clamp => gl::CLAMP_TO_EDGE,
+    };
+    unsafe {
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, wrap_mode as i32);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, wrap_mode as i32);
         gl::TexParameteri(
             gl::TEXTURE_2D,
             gl::TEXTURE_MIN_FILTER,
@@ -51,6 +176,30 @@ pub fn upload_bmp_texture(texture_path: &str) -> Result<u32, String> {
         );
         gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
     }
+}
 
-    Ok(texture_id)
+#[cfg(test)]
+mod tests {
+    use crate::loaders::bmp::image::Pixel;
+
+    use super::{PLACEHOLDER_CELL_SIZE, PLACEHOLDER_SIZE, placeholder_image};
+
+    #[test]
+    fn placeholder_is_magenta_black_checkerboard() {
+        let img = placeholder_image();
+        assert_eq!(img.get_width(), PLACEHOLDER_SIZE);
+        assert_eq!(img.get_height(), PLACEHOLDER_SIZE);
+
+        let magenta = Pixel::new(255, 0, 255);
+        let black = Pixel::new(0, 0, 0);
+        assert_eq!(img.get_pixel(0, 0), magenta);
+        assert_eq!(img.get_pixel(PLACEHOLDER_CELL_SIZE - 1, 0), magenta);
+        assert_eq!(img.get_pixel(PLACEHOLDER_CELL_SIZE, 0), black);
+        assert_eq!(img.get_pixel(0, PLACEHOLDER_CELL_SIZE), black);
+        assert_eq!(
+            img.get_pixel(PLACEHOLDER_CELL_SIZE, PLACEHOLDER_CELL_SIZE),
+            magenta
+        );
+        assert!(img.data.iter().all(|&px| px == magenta || px == black));
+    }
 }
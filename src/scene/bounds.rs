@@ -56,11 +56,10 @@ mod tests {
         SceneMesh {
             vertices,
             indices: vec![0, 1, 2],
-            textures: vec![SceneTextureRef {
-                path: String::new(),
-                kind: TextureKind::Diffuse,
-            }],
+            textures: vec![SceneTextureRef::new(String::new(), TextureKind::Diffuse)],
             has_uv_mapping: false,
+            has_vertex_colors: false,
+            diffuse_color: Vector3::zero(),
         }
     }
 
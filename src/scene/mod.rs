@@ -2,6 +2,11 @@ mod bounds;
 mod coloring;
 mod model;
 mod model_builder;
+mod model_export;
+mod tangents;
 
-pub use model::{SceneMesh, SceneModel, SceneTextureRef, TextureKind, Vertex};
+pub use model::{
+    SceneMesh, SceneModel, SceneObject, SceneTextureRef, TextureKind, TextureWrap, Vertex,
+};
 pub use model_builder::build_scene_model;
+pub use model_export::to_obj_scene;
@@ -1,3 +1,5 @@
+use std::ops::Range;
+
 use crate::math::{Vector2, Vector3};
 
 use super::bounds;
@@ -46,10 +48,32 @@ impl TextureKind {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TextureWrap {
+    Repeat,
+    Clamp,
+}
+
 #[derive(Clone, Debug)]
 pub struct SceneTextureRef {
     pub path: String,
     pub kind: TextureKind,
+    pub uv_scale: Vector2,
+    pub uv_offset: Vector2,
+    pub wrap: TextureWrap,
+}
+
+impl SceneTextureRef {
+    /// A texture sampled with untransformed, repeating UVs.
+    pub fn new(path: String, kind: TextureKind) -> Self {
+        Self {
+            path,
+            kind,
+            uv_scale: Vector2::new(1.0, 1.0),
+            uv_offset: Vector2::zero(),
+            wrap: TextureWrap::Repeat,
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -58,25 +82,50 @@ pub struct SceneMesh {
     pub indices: Vec<u32>,
     pub textures: Vec<SceneTextureRef>,
     pub has_uv_mapping: bool,
+    /// `Vertex::color` comes from the model file and is kept instead of the generated face shading.
+    pub has_vertex_colors: bool,
+    /// Material `Kd` color, used in place of the texture when no diffuse texture is present.
+    pub diffuse_color: Vector3,
+}
+
+impl SceneMesh {
+    pub fn has_diffuse_texture(&self) -> bool {
+        self.textures
+            .iter()
+            .any(|texture| matches!(texture.kind, TextureKind::Diffuse))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SceneObject {
+    pub name: String,
+    pub groups: Vec<String>,
+    pub meshes: Range<usize>,
 }
 
 #[derive(Debug)]
 pub struct SceneModel {
     pub meshes: Vec<SceneMesh>,
+    pub objects: Vec<SceneObject>,
     pub base_color: Vector3,
     center: Vector3,
 }
 
 impl SceneModel {
-    pub fn new(mut meshes: Vec<SceneMesh>, base_color: Vector3) -> Self {
+    pub fn new(mut meshes: Vec<SceneMesh>, objects: Vec<SceneObject>, base_color: Vector3) -> Self {
         for mesh in &mut meshes {
-            coloring::apply_face_shading(&mut mesh.vertices, &mesh.indices, &base_color);
+            if mesh.has_vertex_colors {
+                coloring::apply_vertex_colors(&mut mesh.vertices);
+            } else {
+                coloring::apply_face_shading(&mut mesh.vertices, &base_color);
+            }
         }
 
         let (center_x, center_y, center_z) = bounds::center_all_axes(&meshes);
 
         Self {
             meshes,
+            objects,
             base_color,
             center: Vector3::new(center_x, center_y, center_z),
         }
@@ -88,8 +137,12 @@ impl SceneModel {
 
     pub fn change_color(&mut self, new_color: &Vector3) {
         self.base_color = *new_color;
-        for mesh in &mut self.meshes {
-            coloring::apply_new_color(&mut mesh.vertices, &mesh.indices, new_color);
+        for mesh in self
+            .meshes
+            .iter_mut()
+            .filter(|mesh| !mesh.has_vertex_colors)
+        {
+            coloring::apply_new_color(&mut mesh.vertices, new_color);
         }
     }
 }
@@ -98,6 +151,7 @@ impl Default for SceneModel {
     fn default() -> Self {
         Self {
             meshes: Vec::new(),
+            objects: Vec::new(),
             base_color: Vector3::zero(),
             center: Vector3::zero(),
         }
@@ -1,9 +1,19 @@
 use std::path::Path;
 
-use crate::loaders::obj::{self, ObjLoadOptions};
+use crate::loaders::obj::{self, ObjSceneData, ObjTextureMap};
+use crate::loaders::{is_texture_path, model_extensions, model_loader_for};
 use crate::math::{Vector2, Vector3};
 
-use super::model::{SceneMesh, SceneModel, SceneTextureRef, TextureKind, Vertex};
+use super::model::{
+    SceneMesh, SceneModel, SceneObject, SceneTextureRef, TextureKind, TextureWrap, Vertex,
+};
+use super::tangents;
+
+const DEFAULT_BASE_COLOR: Vector3 = Vector3 {
+    x: 0.6,
+    y: 0.6,
+    z: 0.6,
+};
 
 pub fn build_scene_model(
     model_path: &str,
@@ -12,141 +22,187 @@ pub fn build_scene_model(
     let path = Path::new(model_path);
     let model_dir = path.parent().unwrap_or_else(|| Path::new("")).to_path_buf();
 
-    let obj_scene = obj::load(
-        path,
-        &ObjLoadOptions {
-            triangulate: true,
-            single_index: false,
-        },
-    )?;
+    let obj_scene = load_model(path)?;
 
-    let base_color = Vector3::new(0.6, 0.6, 0.6);
+    let base_color = DEFAULT_BASE_COLOR;
     let mut meshes = Vec::new();
-
-    for object in obj_scene.objects {
-        let mesh = &object.mesh;
-
-        if mesh.positions.len() % 3 != 0 {
-            return Err(
-                "Malformed OBJ mesh: positions array length is not a multiple of 3".to_string(),
-            );
+    let mut objects = Vec::with_capacity(obj_scene.objects.len());
+
+    for object in &obj_scene.objects {
+        let first_mesh = meshes.len();
+        for mesh in &object.meshes {
+            meshes.push(build_scene_mesh(
+                mesh,
+                &obj_scene.materials,
+                &model_dir,
+                fallback_texture_path,
+                model_path,
+            )?);
         }
 
-        let num_vertices = mesh.positions.len() / 3;
-        if !mesh.normals.is_empty() && mesh.normals.len() != mesh.positions.len() {
-            return Err(
-                "Malformed OBJ mesh: normals array length must match positions length".to_string(),
-            );
-        }
-        if !mesh.texcoords.is_empty() && mesh.texcoords.len() != num_vertices * 2 {
-            return Err(
-                "Malformed OBJ mesh: texcoords array length must be vertex_count * 2".to_string(),
-            );
-        }
-        let has_uv_mapping = !mesh.texcoords.is_empty();
-
-        let mut vertices: Vec<Vertex> = Vec::with_capacity(num_vertices);
-        let indices: Vec<u32> = mesh.indices.clone();
-        let (p, n, t) = (&mesh.positions, &mesh.normals, &mesh.texcoords);
-
-        let (min_x, max_x, min_y, max_y) = if mesh.texcoords.is_empty() {
-            let mut min_x = f32::INFINITY;
-            let mut max_x = f32::NEG_INFINITY;
-            let mut min_y = f32::INFINITY;
-            let mut max_y = f32::NEG_INFINITY;
-
-            for i in 0..num_vertices {
-                let x = p[i * 3];
-                let y = p[i * 3 + 1];
-                min_x = min_x.min(x);
-                max_x = max_x.max(x);
-                min_y = min_y.min(y);
-                max_y = max_y.max(y);
-            }
-            (min_x, max_x, min_y, max_y)
-        } else {
-            (0.0, 1.0, 0.0, 1.0)
-        };
+        objects.push(SceneObject {
+            name: object.name.clone(),
+            groups: object.groups.clone(),
+            meshes: first_mesh..meshes.len(),
+        });
+    }
 
-        for i in 0..num_vertices {
-            let mut vertex = Vertex {
-                position: Vector3::new(p[i * 3], p[i * 3 + 1], p[i * 3 + 2]),
-                ..Default::default()
-            };
+    Ok(SceneModel::new(meshes, objects, base_color))
+}
 
-            if !mesh.normals.is_empty() {
-                vertex.normal = Vector3::new(n[i * 3], n[i * 3 + 1], n[i * 3 + 2]);
-            }
+/// Loads the model with the registered loader matching its magic number or extension. Every
+/// loader produces the same scene data as OBJ, so everything downstream is shared.
+fn load_model(path: &Path) -> Result<ObjSceneData, String> {
+    let loader = model_loader_for(path).ok_or_else(|| {
+        format!(
+            "Unsupported model format for '{}' (expected .{})",
+            path.display(),
+            model_extensions().join(" or .")
+        )
+    })?;
+    loader.load(path)
+}
 
-            if has_uv_mapping {
-                vertex.tex_coords = Vector2::new(t[i * 2], t[i * 2 + 1]);
-            } else {
-                let u = if max_x != min_x {
-                    (vertex.position.x - min_x) / (max_x - min_x)
-                } else {
-                    0.5
-                };
-                let v = if max_y != min_y {
-                    (vertex.position.y - min_y) / (max_y - min_y)
-                } else {
-                    0.5
-                };
-                vertex.tex_coords = Vector2::new(u, v);
-            }
+fn build_scene_mesh(
+    mesh: &obj::ObjMeshData,
+    materials: &[obj::ObjMaterialData],
+    model_dir: &Path,
+    fallback_texture_path: &str,
+    model_path: &str,
+) -> Result<SceneMesh, String> {
+    if !mesh.positions.len().is_multiple_of(3) {
+        return Err(
+            "Malformed OBJ mesh: positions array length is not a multiple of 3".to_string(),
+        );
+    }
+
+    let num_vertices = mesh.positions.len() / 3;
+    if !mesh.normals.is_empty() && mesh.normals.len() != mesh.positions.len() {
+        return Err(
+            "Malformed OBJ mesh: normals array length must match positions length".to_string(),
+        );
+    }
+    if !mesh.texcoords.is_empty() && mesh.texcoords.len() != num_vertices * 2 {
+        return Err(
+            "Malformed OBJ mesh: texcoords array length must be vertex_count * 2".to_string(),
+        );
+    }
+    if !mesh.colors.is_empty() && mesh.colors.len() != mesh.positions.len() {
+        return Err(
+            "Malformed OBJ mesh: colors array length must match positions length".to_string(),
+        );
+    }
+    let has_uv_mapping = !mesh.texcoords.is_empty();
+    let has_vertex_colors = !mesh.colors.is_empty();
+
+    let mut vertices: Vec<Vertex> = Vec::with_capacity(num_vertices);
+    let indices: Vec<u32> = mesh.indices.clone();
+    let (p, n, t, c) = (
+        &mesh.positions,
+        &mesh.normals,
+        &mesh.texcoords,
+        &mesh.colors,
+    );
+
+    let (min_x, max_x, min_y, max_y) = if mesh.texcoords.is_empty() {
+        let mut min_x = f32::INFINITY;
+        let mut max_x = f32::NEG_INFINITY;
+        let mut min_y = f32::INFINITY;
+        let mut max_y = f32::NEG_INFINITY;
 
-            vertices.push(vertex);
+        for i in 0..num_vertices {
+            let x = p[i * 3];
+            let y = p[i * 3 + 1];
+            min_x = min_x.min(x);
+            max_x = max_x.max(x);
+            min_y = min_y.min(y);
+            max_y = max_y.max(y);
         }
+        (min_x, max_x, min_y, max_y)
+    } else {
+        (0.0, 1.0, 0.0, 1.0)
+    };
 
-        let mut textures = Vec::new();
-        let material = if let Some(material_id) = mesh.material_id {
-            Some(obj_scene.materials.get(material_id).ok_or_else(|| {
-                format!(
-                    "OBJ mesh references unknown material id {} while loading {}",
-                    material_id, model_path
-                )
-            })?)
-        } else {
-            None
+    for i in 0..num_vertices {
+        let mut vertex = Vertex {
+            position: Vector3::new(p[i * 3], p[i * 3 + 1], p[i * 3 + 2]),
+            ..Default::default()
         };
 
-        let diffuse_path =
-            resolve_diffuse_texture_path(&model_dir, fallback_texture_path, material, model_path)?;
-        textures.push(SceneTextureRef {
-            path: diffuse_path,
-            kind: TextureKind::Diffuse,
-        });
+        if !mesh.normals.is_empty() {
+            vertex.normal = Vector3::new(n[i * 3], n[i * 3 + 1], n[i * 3 + 2]);
+        }
 
-        if let Some(specular_path) = material
-            .and_then(|mat| mat.specular_texture.as_deref())
-            .filter(|path| !path.is_empty())
-            .and_then(|path| resolve_optional_bmp_material_path(&model_dir, path))
-        {
-            textures.push(SceneTextureRef {
-                path: specular_path,
-                kind: TextureKind::Specular,
-            });
+        if has_vertex_colors {
+            vertex.color = Vector3::new(c[i * 3], c[i * 3 + 1], c[i * 3 + 2]);
         }
 
-        if let Some(normal_path) = material
-            .and_then(|mat| mat.normal_texture.as_deref())
-            .filter(|path| !path.is_empty())
-            .and_then(|path| resolve_optional_bmp_material_path(&model_dir, path))
-        {
-            textures.push(SceneTextureRef {
-                path: normal_path,
-                kind: TextureKind::Normal,
-            });
+        if has_uv_mapping {
+            vertex.tex_coords = Vector2::new(t[i * 2], t[i * 2 + 1]);
+        } else {
+            let u = if max_x != min_x {
+                (vertex.position.x - min_x) / (max_x - min_x)
+            } else {
+                0.5
+            };
+            let v = if max_y != min_y {
+                (vertex.position.y - min_y) / (max_y - min_y)
+            } else {
+                0.5
+            };
+            vertex.tex_coords = Vector2::new(u, v);
         }
 
-        meshes.push(SceneMesh {
-            vertices,
-            indices,
-            textures,
-            has_uv_mapping,
-        });
+        vertices.push(vertex);
+    }
+
+    if has_uv_mapping {
+        tangents::compute_tangent_frames(&mut vertices, &indices);
+    }
+
+    let mut textures = Vec::new();
+    let material = if let Some(material_id) = mesh.material_id {
+        Some(materials.get(material_id).ok_or_else(|| {
+            format!(
+                "OBJ mesh references unknown material id {} while loading {}",
+                material_id, model_path
+            )
+        })?)
+    } else {
+        None
+    };
+
+    if let Some(diffuse) = resolve_diffuse_texture(model_dir, fallback_texture_path, material)? {
+        textures.push(diffuse);
+    }
+
+    let diffuse_color = material
+        .and_then(|mat| mat.diffuse_color)
+        .map(|[r, g, b]| Vector3::new(r, g, b))
+        .unwrap_or(DEFAULT_BASE_COLOR);
+
+    if let Some(specular) = material
+        .and_then(|mat| mat.specular_texture.as_ref())
+        .and_then(|map| resolve_optional_material_texture(model_dir, map, TextureKind::Specular))
+    {
+        textures.push(specular);
+    }
+
+    if let Some(normal) = material
+        .and_then(|mat| mat.normal_texture.as_ref())
+        .and_then(|map| resolve_optional_material_texture(model_dir, map, TextureKind::Normal))
+    {
+        textures.push(normal);
     }
 
-    Ok(SceneModel::new(meshes, base_color))
+    Ok(SceneMesh {
+        vertices,
+        indices,
+        textures,
+        has_uv_mapping,
+        has_vertex_colors,
+        diffuse_color,
+    })
 }
 
 fn resolve_material_path(base_dir: &Path, relative_path: &str) -> Result<String, String> {
@@ -156,49 +212,61 @@ fn resolve_material_path(base_dir: &Path, relative_path: &str) -> Result<String,
         .ok_or_else(|| format!("Invalid UTF-8 in material texture path: {}", path.display()))
 }
 
-fn resolve_optional_bmp_material_path(base_dir: &Path, relative_path: &str) -> Option<String> {
-    if is_bmp_path(relative_path) {
-        resolve_material_path(base_dir, relative_path).ok()
+fn material_texture_ref(path: String, kind: TextureKind, map: &ObjTextureMap) -> SceneTextureRef {
+    SceneTextureRef {
+        uv_scale: Vector2::new(map.scale[0], map.scale[1]),
+        uv_offset: Vector2::new(map.offset[0], map.offset[1]),
+        wrap: if map.clamp {
+            TextureWrap::Clamp
+        } else {
+            TextureWrap::Repeat
+        },
+        ..SceneTextureRef::new(path, kind)
+    }
+}
+
+fn resolve_optional_material_texture(
+    base_dir: &Path,
+    map: &ObjTextureMap,
+    kind: TextureKind,
+) -> Option<SceneTextureRef> {
+    if is_texture_path(&map.path) {
+        resolve_material_path(base_dir, &map.path)
+            .ok()
+            .map(|path| material_texture_ref(path, kind, map))
     } else {
         None
     }
 }
 
-fn resolve_diffuse_texture_path(
+/// Picks the diffuse texture for a mesh; `None` means the mesh is drawn with its `Kd` color.
+/// The file is not checked here: textures that cannot be decoded, including unsupported
+/// formats, get the placeholder (or fail with `--strict`) when the renderer loads them.
+fn resolve_diffuse_texture(
     model_dir: &Path,
     fallback_texture_path: &str,
     material: Option<&obj::ObjMaterialData>,
-    model_path: &str,
-) -> Result<String, String> {
+) -> Result<Option<SceneTextureRef>, String> {
     if !fallback_texture_path.is_empty() {
-        return Ok(fallback_texture_path.to_string());
+        return Ok(Some(SceneTextureRef::new(
+            fallback_texture_path.to_string(),
+            TextureKind::Diffuse,
+        )));
     }
 
     if let Some(diffuse_texture) = material
-        .and_then(|mat| mat.diffuse_texture.as_deref())
-        .filter(|texture| !texture.is_empty())
+        .and_then(|mat| mat.diffuse_texture.as_ref())
+        .filter(|texture| !texture.path.is_empty())
     {
-        if !is_bmp_path(diffuse_texture) {
-            return Err(format!(
-                "Material diffuse texture for '{}' must be a .bmp file when no CLI fallback texture is provided: {}",
-                model_path, diffuse_texture
-            ));
-        }
-        return resolve_material_path(model_dir, diffuse_texture);
+        let path = resolve_material_path(model_dir, &diffuse_texture.path)?;
+        return Ok(Some(material_texture_ref(
+            path,
+            TextureKind::Diffuse,
+            diffuse_texture,
+        )));
     }
 
-    Err(format!(
-        "No diffuse texture available for '{}' (expected CLI fallback BMP or material map_Kd BMP)",
-        model_path
-    ))
-}
-
-fn is_bmp_path(path: &str) -> bool {
-    Path::new(path)
-        .extension()
-        .and_then(|ext| ext.to_str())
-        .map(|ext| ext.eq_ignore_ascii_case("bmp"))
-        .unwrap_or(false)
+    Ok(None)
 }
 
 #[cfg(test)]
@@ -210,6 +278,8 @@ mod tests {
     use std::time::{SystemTime, UNIX_EPOCH};
 
     use super::build_scene_model;
+    use crate::math::Vector3;
+    use crate::scene::TextureWrap;
 
     fn unique_temp_dir(prefix: &str) -> PathBuf {
         let nanos = SystemTime::now()
@@ -221,6 +291,52 @@ mod tests {
         dir
     }
 
+    #[test]
+    fn welded_grids_keep_their_shared_vertices_through_face_shading() {
+        let dir = unique_temp_dir("scop_model_builder_face_shading");
+        let obj_path = dir.join("grid.obj");
+        let mut obj = String::from("vn 0 0 1\n");
+        for y in 0..4 {
+            for x in 0..4 {
+                obj.push_str(&format!("v {} {} 0\n", x, y));
+            }
+        }
+        for y in 0..3 {
+            for x in 0..3 {
+                let corner = y * 4 + x + 1;
+                obj.push_str(&format!(
+                    "f {}//1 {}//1 {}//1 {}//1\n",
+                    corner,
+                    corner + 1,
+                    corner + 5,
+                    corner + 4
+                ));
+            }
+        }
+        fs::write(&obj_path, obj).expect("failed to write OBJ fixture");
+
+        let mut scene = build_scene_model(
+            obj_path
+                .to_str()
+                .expect("temporary path should be valid UTF-8"),
+            "resources/textures/brickwall.bmp",
+        )
+        .expect("scene should build from OBJ");
+
+        let mesh = &scene.meshes[0];
+        assert_eq!(mesh.indices.len(), 54);
+        assert_eq!(mesh.vertices.len(), 16);
+
+        scene.change_color(&Vector3::new(1.0, 0.5, 0.0));
+        let mesh = &scene.meshes[0];
+        assert_eq!(mesh.vertices.len(), 16);
+        assert!(mesh.vertices.iter().all(|vertex| {
+            (vertex.new_color.x, vertex.new_color.y, vertex.new_color.z) == (1.0, 0.5, 0.0)
+        }));
+
+        fs::remove_dir_all(&dir).expect("failed to cleanup temp directory");
+    }
+
     #[test]
     fn cli_fallback_diffuse_texture_overrides_material_map_kd() {
         let dir = unique_temp_dir("scop_model_builder_fallback");
@@ -260,4 +376,369 @@ f 1 2 3
 
         fs::remove_dir_all(&dir).expect("failed to cleanup temp directory");
     }
+
+    #[test]
+    fn unsupported_material_textures_are_left_to_the_renderer() {
+        let dir = unique_temp_dir("scop_model_builder_unsupported_texture");
+        let obj_path = dir.join("mesh.obj");
+        fs::write(dir.join("mesh.mtl"), "newmtl Mat\nmap_Kd wood.jpg\n")
+            .expect("failed to write MTL fixture");
+        fs::write(
+            &obj_path,
+            "\
+mtllib mesh.mtl
+usemtl Mat
+v 0 0 0
+v 1 0 0
+v 0 1 0
+f 1 2 3
+",
+        )
+        .expect("failed to write OBJ fixture");
+
+        // The renderer substitutes the placeholder (or fails with --strict) when loading it
+        let scene = build_scene_model(
+            obj_path
+                .to_str()
+                .expect("temporary path should be valid UTF-8"),
+            "",
+        )
+        .expect("scene should build with an unsupported texture format");
+
+        assert!(scene.meshes[0].textures[0].path.ends_with("wood.jpg"));
+
+        fs::remove_dir_all(&dir).expect("failed to cleanup temp directory");
+    }
+
+    #[test]
+    fn preserves_object_structure_and_mesh_order() {
+        let dir = unique_temp_dir("scop_model_builder_objects");
+        let obj_path = dir.join("mesh.obj");
+        fs::write(
+            &obj_path,
+            "\
+v 0 0 0
+v 1 0 0
+v 0 1 0
+o First
+g left
+f 1 2 3
+o Second
+f 1 2 3
+f 3 2 1
+",
+        )
+        .expect("failed to write OBJ fixture");
+
+        let scene = build_scene_model(
+            obj_path
+                .to_str()
+                .expect("temporary path should be valid UTF-8"),
+            "resources/textures/brickwall.bmp",
+        )
+        .expect("scene should build with multiple objects");
+
+        assert_eq!(scene.meshes.len(), 2);
+        assert_eq!(scene.objects.len(), 2);
+        assert_eq!(scene.objects[0].name, "First");
+        assert_eq!(scene.objects[0].groups, vec!["left"]);
+        assert_eq!(scene.objects[0].meshes, 0..1);
+        assert_eq!(scene.objects[1].name, "Second");
+        assert_eq!(scene.objects[1].meshes, 1..2);
+        assert_eq!(scene.meshes[0].indices.len(), 3);
+        assert_eq!(scene.meshes[1].indices.len(), 6);
+
+        fs::remove_dir_all(&dir).expect("failed to cleanup temp directory");
+    }
+
+    #[test]
+    fn fills_tangent_frames_for_uv_mapped_meshes() {
+        let dir = unique_temp_dir("scop_model_builder_tangents");
+        let obj_path = dir.join("mesh.obj");
+        fs::write(
+            &obj_path,
+            "\
+v 0 0 0
+v 1 0 0
+v 0 1 0
+vt 0 0
+vt 1 0
+vt 0 1
+vn 0 0 1
+f 1/1/1 2/2/1 3/3/1
+",
+        )
+        .expect("failed to write OBJ fixture");
+
+        let scene = build_scene_model(
+            obj_path
+                .to_str()
+                .expect("temporary path should be valid UTF-8"),
+            "resources/textures/brickwall.bmp",
+        )
+        .expect("scene should build with UV mapped mesh");
+
+        for vertex in &scene.meshes[0].vertices {
+            assert_eq!(
+                (vertex.tangent.x, vertex.tangent.y, vertex.tangent.z),
+                (1.0, 0.0, 0.0)
+            );
+            assert_eq!(
+                (vertex.bitangent.x, vertex.bitangent.y, vertex.bitangent.z),
+                (0.0, 1.0, 0.0)
+            );
+        }
+
+        fs::remove_dir_all(&dir).expect("failed to cleanup temp directory");
+    }
+
+    #[test]
+    fn material_texture_options_reach_scene_texture_refs() {
+        let dir = unique_temp_dir("scop_model_builder_texture_options");
+        let obj_path = dir.join("mesh.obj");
+        fs::write(
+            dir.join("mesh.mtl"),
+            "\
+newmtl Mat
+map_Kd -s 2 3 1 -o 0.5 0.25 -clamp on wood.bmp
+map_Ks specular.bmp
+bump normal.TGA
+",
+        )
+        .expect("failed to write MTL fixture");
+        fs::write(
+            &obj_path,
+            "\
+mtllib mesh.mtl
+usemtl Mat
+v 0 0 0
+v 1 0 0
+v 0 1 0
+f 1 2 3
+",
+        )
+        .expect("failed to write OBJ fixture");
+
+        let scene = build_scene_model(
+            obj_path
+                .to_str()
+                .expect("temporary path should be valid UTF-8"),
+            "",
+        )
+        .expect("scene should build with material textures");
+
+        let textures = &scene.meshes[0].textures;
+        assert_eq!(textures.len(), 3);
+
+        let diffuse = &textures[0];
+        assert!(diffuse.path.ends_with("wood.bmp"));
+        assert_eq!((diffuse.uv_scale.x, diffuse.uv_scale.y), (2.0, 3.0));
+        assert_eq!((diffuse.uv_offset.x, diffuse.uv_offset.y), (0.5, 0.25));
+        assert_eq!(diffuse.wrap, TextureWrap::Clamp);
+
+        let specular = &textures[1];
+        assert!(specular.path.ends_with("specular.bmp"));
+        assert_eq!((specular.uv_scale.x, specular.uv_scale.y), (1.0, 1.0));
+        assert_eq!(specular.wrap, TextureWrap::Repeat);
+
+        assert!(textures[2].path.ends_with("normal.TGA"));
+
+        fs::remove_dir_all(&dir).expect("failed to cleanup temp directory");
+    }
+
+    #[test]
+    fn stl_models_build_like_obj_models() {
+        let dir = unique_temp_dir("scop_model_builder_stl");
+        let stl_path = dir.join("part.STL");
+        fs::write(
+            &stl_path,
+            "\
+solid part
+facet normal 0 0 1
+outer loop
+vertex 0 0 0
+vertex 2 0 0
+vertex 0 2 0
+endloop
+endfacet
+endsolid part
+",
+        )
+        .expect("failed to write STL fixture");
+
+        let scene = build_scene_model(
+            stl_path
+                .to_str()
+                .expect("temporary path should be valid UTF-8"),
+            "",
+        )
+        .expect("scene should build from STL");
+
+        assert_eq!(scene.objects.len(), 1);
+        assert_eq!(scene.objects[0].name, "part");
+        let mesh = &scene.meshes[0];
+        assert_eq!(mesh.indices, vec![0, 1, 2]);
+        assert!(!mesh.has_uv_mapping);
+        assert!(mesh.textures.is_empty());
+        let corner = &mesh.vertices[1];
+        assert_eq!(
+            (corner.normal.x, corner.normal.y, corner.normal.z),
+            (0.0, 0.0, 1.0)
+        );
+        assert_eq!((corner.tex_coords.x, corner.tex_coords.y), (1.0, 0.0));
+
+        fs::remove_dir_all(&dir).expect("failed to cleanup temp directory");
+    }
+
+    #[test]
+    fn ply_vertex_colors_survive_face_shading() {
+        let dir = unique_temp_dir("scop_model_builder_ply");
+        let ply_path = dir.join("scan.ply");
+        fs::write(
+            &ply_path,
+            "\
+ply
+format ascii 1.0
+element vertex 3
+property float x
+property float y
+property float z
+property uchar red
+property uchar green
+property uchar blue
+element face 1
+property list uchar int vertex_indices
+end_header
+0 0 0 255 0 0
+1 0 0 0 255 0
+0 1 0 0 0 255
+3 0 1 2
+",
+        )
+        .expect("failed to write PLY fixture");
+
+        let mut scene = build_scene_model(
+            ply_path
+                .to_str()
+                .expect("temporary path should be valid UTF-8"),
+            "",
+        )
+        .expect("scene should build from PLY");
+
+        let mesh = &scene.meshes[0];
+        assert!(mesh.has_vertex_colors);
+        let colors: Vec<_> = mesh
+            .vertices
+            .iter()
+            .map(|vertex| (vertex.new_color.x, vertex.new_color.y, vertex.new_color.z))
+            .collect();
+        assert_eq!(
+            colors,
+            vec![(1.0, 0.0, 0.0), (0.0, 1.0, 0.0), (0.0, 0.0, 1.0)]
+        );
+
+        scene.change_color(&Vector3::new(0.2, 0.2, 0.2));
+        let first = &scene.meshes[0].vertices[0].new_color;
+        assert_eq!((first.x, first.y, first.z), (1.0, 0.0, 0.0));
+
+        fs::remove_dir_all(&dir).expect("failed to cleanup temp directory");
+    }
+
+    #[test]
+    fn gltf_models_resolve_external_buffers_and_textures() {
+        let dir = unique_temp_dir("scop_model_builder_gltf");
+        let gltf_path = dir.join("scene.gltf");
+        let buffer: Vec<u8> = [0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0]
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect();
+        fs::write(dir.join("mesh.bin"), buffer).expect("failed to write glTF buffer");
+        fs::write(
+            &gltf_path,
+            r#"{
+  "asset": {"version": "2.0"},
+  "nodes": [{"name": "Widget", "mesh": 0}],
+  "meshes": [{"primitives": [{"attributes": {"POSITION": 0}, "material": 0}]}],
+  "materials": [{"pbrMetallicRoughness": {"baseColorTexture": {"index": 0}}}],
+  "textures": [{"source": 0}],
+  "images": [{"uri": "albedo.png"}],
+  "buffers": [{"uri": "mesh.bin", "byteLength": 36}],
+  "bufferViews": [{"buffer": 0, "byteLength": 36}],
+  "accessors": [{"bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3"}]
+}"#,
+        )
+        .expect("failed to write glTF fixture");
+
+        let scene = build_scene_model(
+            gltf_path
+                .to_str()
+                .expect("temporary path should be valid UTF-8"),
+            "",
+        )
+        .expect("scene should build from glTF");
+
+        assert_eq!(scene.objects[0].name, "Widget");
+        let mesh = &scene.meshes[0];
+        assert_eq!(mesh.indices, vec![0, 1, 2]);
+        assert_eq!(mesh.textures.len(), 1);
+        assert_eq!(
+            mesh.textures[0].path,
+            dir.join("albedo.png").to_str().expect("valid UTF-8")
+        );
+        let white = &mesh.diffuse_color;
+        assert_eq!((white.x, white.y, white.z), (1.0, 1.0, 1.0));
+
+        fs::remove_dir_all(&dir).expect("failed to cleanup temp directory");
+    }
+
+    #[test]
+    fn untextured_material_uses_diffuse_color() {
+        let dir = unique_temp_dir("scop_model_builder_untextured");
+        let obj_path = dir.join("mesh.obj");
+        fs::write(
+            dir.join("mesh.mtl"),
+            "\
+newmtl Red
+Kd 0.8 0.1 0.1
+",
+        )
+        .expect("failed to write MTL fixture");
+        fs::write(
+            &obj_path,
+            "\
+mtllib mesh.mtl
+v 0 0 0
+v 1 0 0
+v 0 1 0
+f 1 2 3
+usemtl Red
+f 3 2 1
+",
+        )
+        .expect("failed to write OBJ fixture");
+
+        let scene = build_scene_model(
+            obj_path
+                .to_str()
+                .expect("temporary path should be valid UTF-8"),
+            "",
+        )
+        .expect("scene without any texture should build");
+
+        assert_eq!(scene.meshes.len(), 2);
+        for mesh in &scene.meshes {
+            assert!(mesh.textures.is_empty());
+            assert!(!mesh.has_diffuse_texture());
+        }
+
+        let default_color = &scene.meshes[0].diffuse_color;
+        assert_eq!(
+            (default_color.x, default_color.y, default_color.z),
+            (0.6, 0.6, 0.6)
+        );
+        let red = &scene.meshes[1].diffuse_color;
+        assert_eq!((red.x, red.y, red.z), (0.8, 0.1, 0.1));
+
+        fs::remove_dir_all(&dir).expect("failed to cleanup temp directory");
+    }
 }
@@ -0,0 +1,201 @@
+use crate::loaders::obj::{
+    ObjMaterialData, ObjMeshData, ObjObjectData, ObjSceneData, ObjTextureMap,
+};
+
+use super::model::{SceneMesh, SceneModel, SceneTextureRef, TextureKind, TextureWrap};
+
+/// Converts a `SceneModel` back into scene data that `loaders::obj::save` can write. Meshes
+/// with the same color and textures share one material; generated planar UVs and face
+/// shading are not exported.
+pub fn to_obj_scene(model: &SceneModel) -> ObjSceneData {
+    let mut materials: Vec<ObjMaterialData> = Vec::new();
+    let mut export_mesh = |mesh: &SceneMesh| {
+        let material = mesh_material(mesh);
+        let material_id = materials
+            .iter()
+            .position(|existing| same_material(existing, &material))
+            .unwrap_or_else(|| {
+                materials.push(ObjMaterialData {
+                    name: format!("material_{}", materials.len()),
+                    ..material
+                });
+                materials.len() - 1
+            });
+        mesh_data(mesh, material_id)
+    };
+
+    let objects = if model.objects.is_empty() {
+        vec![ObjObjectData {
+            meshes: model.meshes.iter().map(&mut export_mesh).collect(),
+            ..Default::default()
+        }]
+    } else {
+        model
+            .objects
+            .iter()
+            .map(|object| ObjObjectData {
+                name: object.name.clone(),
+                groups: object.groups.clone(),
+                meshes: model.meshes[object.meshes.clone()]
+                    .iter()
+                    .map(&mut export_mesh)
+                    .collect(),
+            })
+            .collect()
+    };
+
+    ObjSceneData { objects, materials }
+}
+
+fn mesh_data(mesh: &SceneMesh, material_id: usize) -> ObjMeshData {
+    let mut data = ObjMeshData {
+        indices: mesh.indices.clone(),
+        material_id: Some(material_id),
+        ..Default::default()
+    };
+    for vertex in &mesh.vertices {
+        let (p, n) = (vertex.position, vertex.normal);
+        data.positions.extend_from_slice(&[p.x, p.y, p.z]);
+        data.normals.extend_from_slice(&[n.x, n.y, n.z]);
+        if mesh.has_uv_mapping {
+            let uv = vertex.tex_coords;
+            data.texcoords.extend_from_slice(&[uv.x, uv.y]);
+        }
+        if mesh.has_vertex_colors {
+            let c = vertex.color;
+            data.colors.extend_from_slice(&[c.x, c.y, c.z]);
+        }
+    }
+    data
+}
+
+fn mesh_material(mesh: &SceneMesh) -> ObjMaterialData {
+    let color = mesh.diffuse_color;
+    let mut material = ObjMaterialData {
+        diffuse_color: Some([color.x, color.y, color.z]),
+        ..Default::default()
+    };
+    for texture in &mesh.textures {
+        let slot = match texture.kind {
+            TextureKind::Diffuse => &mut material.diffuse_texture,
+            TextureKind::Specular => &mut material.specular_texture,
+            TextureKind::Normal => &mut material.normal_texture,
+        };
+        slot.get_or_insert_with(|| texture_map(texture));
+    }
+    material
+}
+
+fn texture_map(texture: &SceneTextureRef) -> ObjTextureMap {
+    ObjTextureMap {
+        path: texture.path.clone(),
+        scale: [texture.uv_scale.x, texture.uv_scale.y, 1.0],
+        offset: [texture.uv_offset.x, texture.uv_offset.y, 0.0],
+        clamp: texture.wrap == TextureWrap::Clamp,
+        ..Default::default()
+    }
+}
+
+fn same_material(a: &ObjMaterialData, b: &ObjMaterialData) -> bool {
+    a.diffuse_color == b.diffuse_color
+        && a.diffuse_texture == b.diffuse_texture
+        && a.specular_texture == b.specular_texture
+        && a.normal_texture == b.normal_texture
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::process;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use super::to_obj_scene;
+    use crate::loaders::obj::{self, ObjLoadOptions, ObjWriteOptions};
+    use crate::scene::build_scene_model;
+
+    fn unique_temp_dir(prefix: &str) -> PathBuf {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("system clock should be after epoch")
+            .as_nanos();
+        let dir = env::temp_dir().join(format!("{}_{}_{}", prefix, process::id(), nanos));
+        fs::create_dir_all(&dir).expect("failed to create temporary test directory");
+        dir
+    }
+
+    #[test]
+    fn scene_models_export_with_shared_materials() {
+        let dir = unique_temp_dir("scop_scene_export");
+        fs::write(
+            dir.join("panels.mtl"),
+            "\
+newmtl Painted
+Kd 0.2 0.4 0.6
+newmtl Textured
+map_Kd -clamp on paint.tga
+",
+        )
+        .expect("failed to write MTL fixture");
+        let model_path = dir.join("panels.obj");
+        fs::write(
+            &model_path,
+            "\
+mtllib panels.mtl
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+vt 0 0
+vt 1 0
+vt 1 1
+vt 0 1
+o Left
+g panels
+usemtl Painted
+f 1/1 2/2 3/3 4/4
+o Right
+usemtl Textured
+f 1/1 2/2 3/3
+usemtl Painted
+f 1/1 3/3 4/4
+",
+        )
+        .expect("failed to write OBJ fixture");
+
+        let model = build_scene_model(model_path.to_str().expect("utf-8 path"), "")
+            .expect("model should build");
+        let scene = to_obj_scene(&model);
+        assert_eq!(scene.materials.len(), 2);
+        assert_eq!(scene.objects[1].meshes[0].material_id, Some(1));
+        assert_eq!(scene.objects[1].meshes[1].material_id, Some(0));
+
+        let exported = dir.join("exported.obj");
+        obj::save(&scene, &exported, &ObjWriteOptions::default()).expect("scene should save");
+        let reloaded = obj::load(
+            &exported,
+            &ObjLoadOptions {
+                triangulate: true,
+                single_index: true,
+                crease_angle_degrees: None,
+            },
+        )
+        .expect("exported OBJ should load");
+
+        let names: Vec<_> = reloaded.objects.iter().map(|o| o.name.as_str()).collect();
+        assert_eq!(names, vec!["Left", "Right"]);
+        assert_eq!(reloaded.objects[0].groups, vec!["panels"]);
+        assert_eq!(reloaded.objects[0].meshes[0].indices.len(), 6);
+        assert_eq!(reloaded.objects[0].meshes[0].texcoords.len(), 8);
+        assert_eq!(reloaded.materials[0].diffuse_color, Some([0.2, 0.4, 0.6]));
+        let texture = reloaded.materials[1]
+            .diffuse_texture
+            .as_ref()
+            .expect("diffuse texture should be exported");
+        assert!(texture.path.ends_with("paint.tga"));
+        assert!(texture.clamp);
+
+        fs::remove_dir_all(&dir).expect("failed to cleanup temp directory");
+    }
+}
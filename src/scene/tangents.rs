@@ -0,0 +1,189 @@
+use crate::math::Vector3;
+
+use super::model::Vertex;
+
+const EPSILON: f32 = 1e-8;
+
+/// Fills `tangent` and `bitangent` for every vertex referenced by `indices`.
+///
+/// Each triangle contributes its UV-aligned tangent and bitangent directions to its three
+/// corners, weighted by the corner angle as MikkTSpace does. Per vertex, the tangent is then
+/// Gram-Schmidt orthonormalized against the normal, and the bitangent is rebuilt as
+/// `cross(normal, tangent)` flipped by the handedness of the UV mapping, so mirrored UVs keep
+/// their orientation.
+pub fn compute_tangent_frames(vertices: &mut [Vertex], indices: &[u32]) {
+    let mut tangents = vec![Vector3::zero(); vertices.len()];
+    let mut bitangents = vec![Vector3::zero(); vertices.len()];
+
+    for triangle in indices.chunks_exact(3) {
+        let corners = [
+            triangle[0] as usize,
+            triangle[1] as usize,
+            triangle[2] as usize,
+        ];
+        let [v0, v1, v2] = corners.map(|index| vertices[index]);
+
+        let edge1 = v1.position - v0.position;
+        let edge2 = v2.position - v0.position;
+        let du1 = v1.tex_coords.x - v0.tex_coords.x;
+        let dv1 = v1.tex_coords.y - v0.tex_coords.y;
+        let du2 = v2.tex_coords.x - v0.tex_coords.x;
+        let dv2 = v2.tex_coords.y - v0.tex_coords.y;
+
+        let determinant = du1 * dv2 - du2 * dv1;
+        if determinant.abs() <= EPSILON {
+            continue;
+        }
+
+        let tangent = safe_normalize((edge1 * dv2 - edge2 * dv1) / determinant);
+        let bitangent = safe_normalize((edge2 * du1 - edge1 * du2) / determinant);
+
+        let positions = [v0.position, v1.position, v2.position];
+        for (corner, &vertex_index) in corners.iter().enumerate() {
+            let weight = corner_angle(
+                positions[corner],
+                positions[(corner + 1) % 3],
+                positions[(corner + 2) % 3],
+            );
+            tangents[vertex_index] += tangent * weight;
+            bitangents[vertex_index] += bitangent * weight;
+        }
+    }
+
+    for (vertex, (tangent, bitangent)) in vertices
+        .iter_mut()
+        .zip(tangents.into_iter().zip(bitangents))
+    {
+        let normal = safe_normalize(vertex.normal);
+        if normal.magnitude() <= EPSILON || tangent.magnitude() <= EPSILON {
+            continue;
+        }
+
+        let mut orthogonal = safe_normalize(tangent - normal * normal.dot(tangent));
+        if orthogonal.magnitude() <= EPSILON {
+            orthogonal = any_perpendicular(normal);
+        }
+
+        let handedness = if normal.cross(orthogonal).dot(bitangent) < 0.0 {
+            -1.0
+        } else {
+            1.0
+        };
+
+        vertex.tangent = orthogonal;
+        vertex.bitangent = normal.cross(orthogonal) * handedness;
+    }
+}
+
+fn corner_angle(corner: Vector3, next: Vector3, previous: Vector3) -> f32 {
+    let a = safe_normalize(next - corner);
+    let b = safe_normalize(previous - corner);
+    a.dot(b).clamp(-1.0, 1.0).acos()
+}
+
+fn safe_normalize(v: Vector3) -> Vector3 {
+    if v.magnitude() <= EPSILON {
+        Vector3::zero()
+    } else {
+        v.normalize()
+    }
+}
+
+fn any_perpendicular(normal: Vector3) -> Vector3 {
+    let axis = if normal.x.abs() < 0.9 {
+        Vector3::new(1.0, 0.0, 0.0)
+    } else {
+        Vector3::new(0.0, 1.0, 0.0)
+    };
+    safe_normalize(axis - normal * normal.dot(axis))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::math::{Vector2, Vector3};
+    use crate::scene::model::Vertex;
+
+    use super::compute_tangent_frames;
+
+    fn vertex(position: [f32; 3], uv: [f32; 2]) -> Vertex {
+        Vertex {
+            position: Vector3::new(position[0], position[1], position[2]),
+            normal: Vector3::new(0.0, 0.0, 1.0),
+            tex_coords: Vector2::new(uv[0], uv[1]),
+            ..Default::default()
+        }
+    }
+
+    fn assert_close(actual: Vector3, expected: [f32; 3]) {
+        assert!(
+            (actual.x - expected[0]).abs() < 1e-5
+                && (actual.y - expected[1]).abs() < 1e-5
+                && (actual.z - expected[2]).abs() < 1e-5,
+            "expected {:?}, got {:?}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn tangent_frame_follows_uv_axes() {
+        let mut vertices = vec![
+            vertex([0.0, 0.0, 0.0], [0.0, 0.0]),
+            vertex([2.0, 0.0, 0.0], [1.0, 0.0]),
+            vertex([2.0, 2.0, 0.0], [1.0, 1.0]),
+            vertex([0.0, 2.0, 0.0], [0.0, 1.0]),
+        ];
+        compute_tangent_frames(&mut vertices, &[0, 1, 2, 0, 2, 3]);
+
+        for vertex in &vertices {
+            assert_close(vertex.tangent, [1.0, 0.0, 0.0]);
+            assert_close(vertex.bitangent, [0.0, 1.0, 0.0]);
+        }
+    }
+
+    #[test]
+    fn mirrored_uvs_flip_handedness() {
+        let mut vertices = vec![
+            vertex([0.0, 0.0, 0.0], [1.0, 0.0]),
+            vertex([1.0, 0.0, 0.0], [0.0, 0.0]),
+            vertex([0.0, 1.0, 0.0], [1.0, 1.0]),
+        ];
+        compute_tangent_frames(&mut vertices, &[0, 1, 2]);
+
+        for vertex in &vertices {
+            assert_close(vertex.tangent, [-1.0, 0.0, 0.0]);
+            assert_close(vertex.bitangent, [0.0, 1.0, 0.0]);
+        }
+    }
+
+    #[test]
+    fn tangent_is_orthogonalized_against_normal() {
+        let mut vertices = vec![
+            vertex([0.0, 0.0, 0.0], [0.0, 0.0]),
+            vertex([1.0, 0.0, 1.0], [1.0, 0.0]),
+            vertex([0.0, 1.0, 0.0], [0.0, 1.0]),
+        ];
+        compute_tangent_frames(&mut vertices, &[0, 1, 2]);
+
+        for vertex in &vertices {
+            assert!(vertex.tangent.dot(vertex.normal).abs() < 1e-5);
+            assert!((vertex.tangent.magnitude() - 1.0).abs() < 1e-5);
+            assert_close(vertex.tangent, [1.0, 0.0, 0.0]);
+        }
+    }
+
+    #[test]
+    fn degenerate_uvs_leave_frame_zeroed() {
+        let mut vertices = vec![
+            vertex([0.0, 0.0, 0.0], [0.5, 0.5]),
+            vertex([1.0, 0.0, 0.0], [0.5, 0.5]),
+            vertex([0.0, 1.0, 0.0], [0.5, 0.5]),
+        ];
+        compute_tangent_frames(&mut vertices, &[0, 1, 2]);
+
+        for vertex in &vertices {
+            assert_close(vertex.tangent, [0.0, 0.0, 0.0]);
+            assert_close(vertex.bitangent, [0.0, 0.0, 0.0]);
+        }
+    }
+}